@@ -221,6 +221,15 @@ mod tests {
         tagged_test!(Object, "");
     }
 
+    #[test]
+    fn item_ids_with_separators_parse_correctly() {
+        assert_eq!(
+            "(WP)MoreWalls:11".parse::<ItemId>().unwrap(),
+            ItemId::Wallpaper(xxh32("MoreWalls:11".as_bytes(), 0))
+        );
+        assert!("(WP)MoreWalls:".parse::<ItemId>().is_err());
+    }
+
     #[test]
     fn unknown_item_tag_returns_error() {
         assert!("(💣)123".parse::<ItemId>().is_err());
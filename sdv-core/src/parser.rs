@@ -117,7 +117,11 @@ pub fn identifier(input: &str) -> IResult<&str, &str> {
 }
 
 fn bare_item_id(input: &str) -> IResult<&str, u32> {
-    let (input, id) = alt((identifier, alphanumeric1))(input)?;
+    // Some IDs are made of `:` separated parts (i.e. `(WP)MoreWalls:11`).
+    let (input, id) = recognize(pair(
+        alt((identifier, alphanumeric1)),
+        many0_count(pair(tag(":"), alt((identifier, alphanumeric1)))),
+    ))(input)?;
     let hashed_id = xxh32(id.as_bytes(), 0);
 
     Ok((input, hashed_id))
@@ -15,16 +15,27 @@ use image::{codecs::png::PngEncoder, RgbaImage};
 use indexmap::IndexMap;
 use itertools::Itertools;
 use sdv::{
-    analyzer::perfection::analyze_perfection,
-    common::{DayOfWeek, ObjectCategory, Point},
+    analyzer::{
+        conditions::analyze_conditions,
+        crops::{simulate_crop, CropConditions, Fertilizer},
+        friendship::analyze_friendship,
+        geodes::{optimize_geodes, GeodeGoal},
+        perfection::analyze_perfection,
+    },
+    common::{DayOfWeek, ItemId, ObjectCategory, Point, Weather},
     gamedata::{Fish, GameData, Locale, ObjectTaste},
-    item_id,
     predictor::{
-        self,
+        bubbles::{calculate_bubbles, predict_bubbles_for_day, Bubbles},
         garbage::{predict_garbage, GarbageCan, GarbageCanLocation},
         geode::{predict_single_geode, Geode, GeodeType},
+        shop::{predict_shop_stock, Shop, ShopStockPrediction},
+        traveling_cart::{is_traveling_cart_day, predict_traveling_cart, TravelingCart},
+        weather::{
+            forecast_weather, predict_weather, ForecastDay, WeatherLocation, WeatherPrediction,
+        },
         PredictionGameState,
     },
+    query::{QueryContext, QuerySupport},
     rng::HashedSeedGenerator,
     save::Object,
     SaveGame,
@@ -80,6 +91,54 @@ impl Default for Format {
     }
 }
 
+#[derive(Debug, StructOpt)]
+struct PredictStateOpt {
+    /// Save file to read the game state from.
+    #[structopt(long, parse(from_os_str))]
+    save: Option<PathBuf>,
+
+    /// Game seed.  Required without `--save`.
+    #[structopt(long)]
+    seed: Option<u32>,
+
+    /// First day (in days played) to predict.  Defaults to the save's current
+    /// day or 1 without `--save`.
+    #[structopt(long)]
+    days_played: Option<u32>,
+}
+
+impl PredictStateOpt {
+    fn get(&self) -> Result<PredictionGameState> {
+        let mut state = match &self.save {
+            Some(path) => {
+                let f = File::open(path)?;
+                let mut r = BufReader::new(f);
+                let save = SaveGame::from_reader(&mut r)?;
+                let (state, undetermined) = PredictionGameState::from_save(&save);
+                if !undetermined.is_empty() {
+                    eprintln!("Could not determine from save: {}", undetermined.join(", "));
+                }
+                state
+            }
+            None => PredictionGameState {
+                game_id: self
+                    .seed
+                    .ok_or_else(|| anyhow!("--seed is required without --save"))?,
+                days_played: 1,
+                ..Default::default()
+            },
+        };
+
+        if let Some(seed) = self.seed {
+            state.game_id = seed;
+        }
+        if let Some(days_played) = self.days_played {
+            state.days_played = days_played;
+        }
+        Ok(state)
+    }
+}
+
 #[derive(Debug, StructOpt)]
 struct DumpOpts {
     #[structopt(flatten)]
@@ -142,6 +201,19 @@ enum DumpOpt {
     Save(SaveFileLoc),
 }
 
+#[derive(Debug, StructOpt)]
+struct AuditConditionsOpt {
+    #[structopt(flatten)]
+    content: GameContentLoc,
+
+    #[structopt(long, default_value = "text")]
+    format: Format,
+
+    /// List every condition that is not fully supported.
+    #[structopt(long)]
+    verbose: bool,
+}
+
 #[derive(Debug, StructOpt)]
 struct ItemsOpt {
     #[structopt(flatten)]
@@ -174,8 +246,17 @@ struct BubblesOpt {
     #[structopt(flatten)]
     content: GameContentLoc,
 
+    /// Map to predict.  Every fishable location is predicted if not given.
     #[structopt(long)]
-    map_name: String,
+    map_name: Option<String>,
+
+    /// Location in `save` used for object placement with `map_name`.
+    #[structopt(long)]
+    location: Option<String>,
+
+    /// Save file used for object placement.
+    #[structopt(long, parse(from_os_str))]
+    save: Option<PathBuf>,
 
     #[structopt(long)]
     days_played: u32,
@@ -189,27 +270,157 @@ struct GeodesOpt {
     #[structopt(flatten)]
     content: GameContentLoc,
 
+    #[structopt(flatten)]
+    state: PredictStateOpt,
+
     #[structopt(long)]
     geode_type: GeodeType,
 
+    /// Overrides the value read from the save.
     #[structopt(long)]
-    geodes_cracked: u32,
+    geodes_cracked: Option<u32>,
 
+    /// Used instead of `geodes_cracked` for mystery boxes.  Overrides the
+    /// value read from the save.
     #[structopt(long)]
-    multiplayer_id: i64,
+    mystery_boxes_opened: Option<u32>,
 
+    /// Overrides the value read from the save.
     #[structopt(long)]
-    deepest_mine_level: u32,
+    multiplayer_id: Option<i64>,
+
+    /// Overrides the value read from the save.
+    #[structopt(long)]
+    deepest_mine_level: Option<u32>,
+}
+
+#[derive(Debug, StructOpt)]
+struct GeodesCmdOpt {
+    #[structopt(flatten)]
+    loc: GameAndSaveOpt,
+
+    /// Maximum number of geodes to crack.
+    #[structopt(long, default_value = "20")]
+    max_cracks: usize,
+
+    /// Item ID (e.g. `(O)74`) to find within `max_cracks` cracks.
+    #[structopt(long)]
+    target: Option<String>,
+
+    /// Item IDs not yet donated to the museum.
+    #[structopt(long)]
+    museum: Vec<String>,
+}
+
+#[derive(Debug, StructOpt)]
+struct CropsOpt {
+    #[structopt(flatten)]
+    content: GameContentLoc,
+
+    /// Seed item ID of the crop (i.e. `472` for parsnips).
+    crop: String,
 
     #[structopt(long)]
     seed: u32,
+
+    /// Day (in days played) the crop is planted.
+    #[structopt(long, default_value = "1")]
+    days_played: u32,
+
+    /// Number of days to simulate.
+    #[structopt(long, default_value = "28")]
+    days: u32,
+
+    /// One of `None`, `Basic`, `Quality`, `Deluxe`, `SpeedGro`,
+    /// `DeluxeSpeedGro` or `HyperSpeedGro`.
+    #[structopt(long, default_value = "None")]
+    fertilizer: Fertilizer,
+
+    #[structopt(long)]
+    agriculturist: bool,
+
+    #[structopt(long, default_value = "0")]
+    farming_level: i32,
+
+    /// Grow the crop where seasons don't apply like the greenhouse.
+    #[structopt(long)]
+    greenhouse: bool,
+
+    /// Tiles (e.g. `10,12`) to predict harvest quality for.
+    #[structopt(long)]
+    tile: Vec<String>,
+}
+
+#[derive(Debug, StructOpt)]
+struct CartOpt {
+    #[structopt(flatten)]
+    content: GameContentLoc,
+
+    #[structopt(flatten)]
+    state: PredictStateOpt,
+
+    /// Number of days to predict.  Only days the cart visits are listed.
+    #[structopt(long, default_value = "28")]
+    days: u32,
+}
+
+#[derive(Debug, StructOpt)]
+struct ShopOpt {
+    #[structopt(flatten)]
+    content: GameContentLoc,
+
+    /// Shop ID from `Data/Shops` (i.e. `ShadowShop` or `DesertTrade`).
+    shop: String,
+
+    #[structopt(flatten)]
+    state: PredictStateOpt,
+
+    /// Number of days to predict.
+    #[structopt(long, default_value = "7")]
+    days: u32,
+}
+
+#[derive(Debug, StructOpt)]
+struct WeatherOpt {
+    #[structopt(flatten)]
+    content: GameContentLoc,
+
+    #[structopt(flatten)]
+    state: PredictStateOpt,
+
+    /// Number of days to predict.
+    #[structopt(long, default_value = "28")]
+    days: u32,
+
+    /// Location context to predict.  Every context is predicted if not given.
+    #[structopt(long)]
+    context: Option<String>,
+
+    #[structopt(long, default_value = "text")]
+    format: Format,
+}
+
+#[derive(Debug, StructOpt)]
+struct GarbageOpt {
+    #[structopt(flatten)]
+    content: GameContentLoc,
+
+    #[structopt(flatten)]
+    state: PredictStateOpt,
+
+    /// Overrides the daily luck read from the save.
+    #[structopt(long)]
+    daily_luck: Option<f64>,
 }
 
 #[derive(Debug, StructOpt)]
 enum PredictOpt {
     Bubbles(BubblesOpt),
-    Garbage(GameContentLoc),
+    Cart(CartOpt),
+    Garbage(GarbageOpt),
     Geode(GeodesOpt),
+    Shop(ShopOpt),
+    Weather(WeatherOpt),
 }
 
 #[derive(Debug, StructOpt)]
@@ -219,12 +430,14 @@ enum GenerateOpt {
 
 #[derive(Debug, StructOpt)]
 enum Opt {
+    AuditConditions(AuditConditionsOpt),
     Bundles(GameAndSaveOpt),
+    Crops(CropsOpt),
     Dump(DumpOpt),
     Fish(GameAndSaveOpt),
     Food(GameAndSaveOpt),
     Generate(GenerateOpt),
-    //Geodes(GameAndSaveOpt),
+    Geodes(GeodesCmdOpt),
     Items(ItemsOpt),
     RenderMap(RenderMapOpt),
     Package(PackageOpt),
@@ -351,7 +564,7 @@ fn get_all_items(save: &SaveGame, all: bool) -> Vec<Item> {
         });
     }
 
-    for (name, location) in &save.locations {
+    for (name, location) in save.all_locations() {
         for (pos, object) in &location.objects {
             if let Some(chest_items) = &object.items {
                 for item in chest_items {
@@ -594,152 +807,164 @@ fn cmd_items(opt: &ItemsOpt) -> Result<()> {
     Ok(())
 }
 
-// fn optimize_geodes(
-//     geode_count: &mut Vec<(GeodeType, i32)>,
-//     predictions: &HashMap<GeodeType, Vec<save::Object>>,
-//     professions: &IndexSet<Profession>,
-//     level: usize,
-//     num_predictions: usize,
-//     memo: &mut HashMap<Vec<(GeodeType, i32)>, (i32, Vec<(GeodeType, i32, i32)>)>,
-// ) -> (i32, Vec<(GeodeType, i32, i32)>) {
-//     if level >= num_predictions {
-//         return (0, Vec::with_capacity(num_predictions));
-//     }
-
-//     if let Some(result) = memo.get(geode_count) {
-//         return (result.0, result.1.clone());
-//     }
-
-//     let chain = GeodeType::iter()
-//         .enumerate()
-//         .filter_map(|(i, ty)| {
-//             if geode_count[i].1 <= 0 {
-//                 None
-//             } else {
-//                 geode_count[i].1 -= 1;
-//                 let reward = &predictions.get(&ty).unwrap()[level];
-//                 let value = reward.stack_price(professions)
-//                     - 25
-//                     - match ty {
-//                         GeodeType::Geode => 50,
-//                         GeodeType::FrozenGeode => 100,
-//                         GeodeType::MagmaGeode => 150,
-//                         GeodeType::OmniGeode => 0,
-//                         GeodeType::ArtifactTrove => 0,
-//                         GeodeType::GoldenCoconut => 0,
-//                     };
-//                 let mut new_chain = optimize_geodes(
-//                     geode_count,
-//                     predictions,
-//                     professions,
-//                     level + 1,
-//                     num_predictions,
-//                     memo,
-//                 );
-//                 geode_count[i].1 += 1;
-
-//                 new_chain.0 += value;
-//                 new_chain.1.push((ty, reward.id(), value));
-//                 Some(new_chain)
-//             }
-//         })
-//         .max_by(|a, b| a.0.cmp(&b.0))
-//         .unwrap();
-
-//     memo.insert(geode_count.clone(), chain.clone());
-
-//     chain
-// }
-
-// fn cmd_geodes(opt: &GameAndSaveOpt) -> Result<()> {
-//     let data = GameData::load(&opt.content.game_content)?;
-//     let f = File::open(&opt.file)?;
-//     let mut r = BufReader::new(f);
-//     let save = SaveGame::from_reader(&mut r)?;
-
-//     let geodes_map = get_all_items(&save, false)
-//         .iter()
-//         .filter(|i| i.object.is_geode())
-//         .fold(HashMap::new(), |mut map, item| {
-//             *map.entry(GeodeType::from_i32(item.object.id()).unwrap())
-//                 .or_insert(0) += item.object.stack;
-//             map
-//         });
-
-//     let mut geodes: Vec<(GeodeType, i32)> = GeodeType::iter()
-//         .map(|t| (t, *geodes_map.get(&t).unwrap_or(&0)))
-//         .collect();
-
-//     let num_geodes = geodes.iter().fold(0, |count, (_id, num)| count + num);
-
-//     let predictions = Geode::predict(num_geodes, 1, &data, &save)?;
-//     let chain = optimize_geodes(
-//         &mut geodes,
-//         &predictions,
-//         &save.player.professions,
-//         0,
-//         num_geodes as usize,
-//         &mut HashMap::new(),
-//     );
-
-//     println!("chain value: {}", chain.0);
-//     for (i, entry) in chain.1.iter().rev().enumerate() {
-//         let object = data.get_object(entry.1).unwrap();
-//         println!(" {} {:?} -> {} {}", i + 1, entry.0, object.name, entry.2);
-//     }
-
-//     let prediction = Geode::predict(10, 1, &data, &save)?;
-//     let mut skin = MadSkin::default();
-//     skin.set_headers_fg(rgb(255, 187, 0));
-//     skin.bold.set_fg(Yellow);
-//     skin.italic.set_fgbg(Magenta, rgb(30, 30, 40));
-//     skin.paragraph.align = Alignment::Center;
-//     skin.table.align = Alignment::Center;
-//     let text_template = TextTemplate::from(
-//         r#"
-//     |:-:|:-|:-|:-|:-|:-|:-|
-//     |**N**|**Geode**|**Frozen Geode**|**Magma Geode**|**Omni Geode**|**Artifact Trove**|**Golden Coconut**|
-//     |:-:|:-|:-|:-|:-|:-|:-|
-//     ${rows
-//     |**${i}**|${geode}|${frozen_geode}|${magma_geode}|${omni_geode}|${artifact_trove}|${golden_coconut}|
-//     }
-//     |:-:|:-|:-|:-|:-|:-|:-|
-//     "#,
-//     );
-
-//     let mut expander = text_template.expander();
-//     let indexes: Vec<String> = (0..10).map(|i| format!("{}", i + 1)).collect();
-//     for i in 0..10 {
-//         expander
-//             .sub("rows")
-//             .set("i", &indexes[i])
-//             .set("geode", &prediction.get(&GeodeType::Geode).unwrap()[i].name)
-//             .set(
-//                 "frozen_geode",
-//                 &prediction.get(&GeodeType::FrozenGeode).unwrap()[i].name,
-//             )
-//             .set(
-//                 "magma_geode",
-//                 &prediction.get(&GeodeType::MagmaGeode).unwrap()[i].name,
-//             )
-//             .set(
-//                 "omni_geode",
-//                 &prediction.get(&GeodeType::OmniGeode).unwrap()[i].name,
-//             )
-//             .set(
-//                 "artifact_trove",
-//                 &prediction.get(&GeodeType::ArtifactTrove).unwrap()[i].name,
-//             )
-//             .set(
-//                 "golden_coconut",
-//                 &prediction.get(&GeodeType::GoldenCoconut).unwrap()[i].name,
-//             );
-//     }
-
-//     skin.print_expander(expander);
-
-//     Ok(())
-// }
+fn cmd_crops(opt: &CropsOpt) -> Result<()> {
+    let data = GameData::from_content_dir(opt.content.get()?)?;
+    let crop = data
+        .crops
+        .get(&opt.crop)
+        .ok_or_else(|| anyhow!("crop {} not found", opt.crop))?;
+    let tiles = opt
+        .tile
+        .iter()
+        .map(|tile| {
+            let (x, y) = tile
+                .split_once(',')
+                .ok_or_else(|| anyhow!("tile {} is not of the form x,y", tile))?;
+            Ok(Point {
+                x: x.trim().parse()?,
+                y: y.trim().parse()?,
+            })
+        })
+        .collect::<Result<Vec<Point<i32>>>>()?;
+
+    let conditions = CropConditions {
+        fertilizer: opt.fertilizer,
+        agriculturist: opt.agriculturist,
+        farming_level: opt.farming_level,
+        near_water: false,
+        ignore_seasons: opt.greenhouse,
+    };
+    let state = PredictionGameState {
+        game_id: opt.seed,
+        days_played: opt.days_played,
+        ..Default::default()
+    };
+    let simulation = simulate_crop::<HashedSeedGenerator>(
+        crop,
+        &conditions,
+        &state,
+        &tiles,
+        opt.days_played + opt.days - 1,
+    )?;
+
+    let percent = |chance: f64| format!("{:.1}%", chance * 100.0);
+    let skin = mad_skin();
+    let mut text = String::new();
+    text.push_str("|:-:|:-|:-:|:-:|:-:|:-:|\n");
+    text.push_str("|**Day**|**Date**|**Normal**|**Silver**|**Gold**|**Iridium**|\n");
+    text.push_str("|:-:|:-|:-:|:-:|:-:|:-:|\n");
+    for harvest in &simulation.harvests {
+        let date = PredictionGameState {
+            days_played: harvest.days_played,
+            ..Default::default()
+        };
+        let distribution = &harvest.distribution;
+        text.push_str(&format!(
+            "|{}|{} {} year {}{}|{}|{}|{}|{}|\n",
+            harvest.days_played,
+            date.season(),
+            date.day_of_month(),
+            date.year(),
+            if harvest.regrowth { " (regrowth)" } else { "" },
+            percent(distribution.normal),
+            percent(distribution.silver),
+            percent(distribution.gold),
+            percent(distribution.iridium),
+        ));
+    }
+    text.push_str("|:-:|:-|:-:|:-:|:-:|:-:|\n");
+    println!("{}", skin.term_text(&text));
+
+    if let Some(day) = simulation.dies_on {
+        println!("Crop dies on day {}.", day);
+    }
+
+    Ok(())
+}
+
+fn cmd_geodes(opt: &GeodesCmdOpt) -> Result<()> {
+    let data = GameData::from_content_dir(opt.loc.content.get()?)?;
+    let f = File::open(&opt.loc.file)?;
+    let mut r = BufReader::new(f);
+    let save = SaveGame::from_reader(&mut r)?;
+
+    // Mystery boxes use their own counter and can't be optimized with geodes.
+    let geode_types: Vec<GeodeType> = GeodeType::iter()
+        .filter(|ty| !ty.is_mystery_box())
+        .collect();
+    let geodes_map = get_all_items(&save, false)
+        .iter()
+        .fold(HashMap::new(), |mut map, item| {
+            let name = item.object.name.as_deref().unwrap_or_default();
+            if let Some(ty) = geode_types.iter().find(|ty| ty.item_name() == name) {
+                *map.entry(*ty).or_insert(0) += item.object.stack as u32;
+            }
+            map
+        });
+    let geodes: Vec<(GeodeType, u32)> = geode_types
+        .iter()
+        .map(|ty| (*ty, *geodes_map.get(ty).unwrap_or(&0)))
+        .collect();
+
+    let goal = if let Some(target) = &opt.target {
+        GeodeGoal::Item {
+            item: target.parse()?,
+            within: opt.max_cracks,
+        }
+    } else if !opt.museum.is_empty() {
+        GeodeGoal::Museum(
+            opt.museum
+                .iter()
+                .map(|item| item.parse::<ItemId>())
+                .collect::<Result<_>>()?,
+        )
+    } else {
+        GeodeGoal::Value
+    };
+
+    let (state, _) = PredictionGameState::from_save(&save);
+    let plan = optimize_geodes::<HashedSeedGenerator>(
+        &data,
+        &state,
+        &geodes,
+        &save.player.professions,
+        &goal,
+        opt.max_cracks,
+    )?;
+
+    let skin = mad_skin();
+    let mut text = String::new();
+    text.push_str("|:-:|:-|:-|:-:|:-:|\n");
+    text.push_str("|**N**|**Geode**|**Reward**|**Quantity**|**Value**|\n");
+    text.push_str("|:-:|:-|:-|:-:|:-:|\n");
+    for (i, step) in plan.steps.iter().enumerate() {
+        let name = data
+            .get_object_by_id(&step.reward.item)
+            .map(|object| object.name.clone())
+            .unwrap_or_else(|_| step.reward.item.to_string());
+        let name = if step.goal_item {
+            format!("**{name}**")
+        } else {
+            name
+        };
+        text.push_str(&format!(
+            "|{}|{}|{}|{}|{}|\n",
+            i + 1,
+            step.ty.item_name(),
+            name,
+            step.reward.quantity,
+            step.value
+        ));
+    }
+    text.push_str("|:-:|:-|:-|:-:|:-:|\n");
+    text.push_str(&format!("|**Total**||||{}|\n", plan.value));
+    text.push_str("|-\n");
+    println!("{}", skin.term_text(&text));
+    if !matches!(goal, GeodeGoal::Value) {
+        println!("goal items found: {}", plan.goal_items);
+    }
+    Ok(())
+}
 
 fn cmd_bundles(opt: &GameAndSaveOpt) -> Result<()> {
     let data = GameData::from_content_dir(opt.content.get()?)?;
@@ -773,13 +998,32 @@ fn cmd_bundles(opt: &GameAndSaveOpt) -> Result<()> {
     Ok(())
 }
 
+const WEATHERS: [Weather; 7] = [
+    Weather::Sun,
+    Weather::Rain,
+    Weather::Wind,
+    Weather::Storm,
+    Weather::Snow,
+    Weather::Festival,
+    Weather::GreenRain,
+];
+
+fn format_weather_prediction(prediction: &WeatherPrediction) -> String {
+    WEATHERS
+        .iter()
+        .filter_map(|weather| {
+            let chance = prediction.chance(weather);
+            (chance > 0.0).then(|| format!("{weather:?} {:.0}%", chance * 100.0))
+        })
+        .join(", ")
+}
+
 fn cmd_todo(opt: &GameAndSaveOpt) -> Result<()> {
     let data = GameData::from_content_dir(opt.content.get()?)?;
     let f = File::open(&opt.file)?;
     let mut r = BufReader::new(f);
     let save = SaveGame::from_reader(&mut r)?;
 
-    let season = &save.current_season;
     let day = &save.day_of_month;
 
     let mut text = String::new();
@@ -793,12 +1037,8 @@ fn cmd_todo(opt: &GameAndSaveOpt) -> Result<()> {
         &save.current_season, &save.day_of_month, &save.year
     )?;
 
-    let birthday = data.characters.iter().find(|(_name, character)| {
-        let Some(birth_season) = &character.birth_season else {
-            return false;
-        };
-        birth_season == season && character.birthday == *day
-    });
+    let friendships = analyze_friendship(&save, &data);
+    let birthday = friendships.iter().find(|npc| npc.is_birthday());
 
     let day_of_week = DayOfWeek::try_from(*day)?;
     if day_of_week == DayOfWeek::Wednesday || day_of_week == DayOfWeek::Sunday {
@@ -807,8 +1047,13 @@ fn cmd_todo(opt: &GameAndSaveOpt) -> Result<()> {
     if day_of_week == DayOfWeek::Friday || day_of_week == DayOfWeek::Sunday {
         writeln!(&mut text, "*Traveling cart is here!*")?;
     }
-    if let Some((name, _character)) = birthday {
-        writeln!(&mut text, "*It's {name}'s birthday today!*")?;
+    if let Some(npc) = birthday {
+        let name = &npc.name.to_string();
+        writeln!(
+            &mut text,
+            "*It's {name}'s birthday today!* ({} hearts)",
+            npc.hearts()
+        )?;
         let loved: Vec<_> = aggregate_items
             .iter()
             .filter_map(|(_, info)| {
@@ -845,6 +1090,31 @@ fn cmd_todo(opt: &GameAndSaveOpt) -> Result<()> {
         writeln!(&mut text, "Liked items you own: {liked:?}")?;
     }
 
+    let upcoming: Vec<_> = friendships
+        .iter()
+        .filter(|npc| matches!(npc.days_until_birthday, Some(1..=7)))
+        .map(|npc| {
+            format!(
+                "{} in {} days ({} hearts)",
+                npc.name,
+                npc.days_until_birthday.unwrap_or_default(),
+                npc.hearts()
+            )
+        })
+        .collect();
+    if !upcoming.is_empty() {
+        writeln!(&mut text, "Upcoming birthdays: {}", upcoming.join(", "))?;
+    }
+
+    let not_gifted: Vec<_> = friendships
+        .iter()
+        .filter(|npc| npc.not_gifted_this_week())
+        .map(|npc| format!("{} ({} hearts)", npc.name, npc.hearts()))
+        .collect();
+    if !not_gifted.is_empty() {
+        writeln!(&mut text, "Not gifted this week: {}", not_gifted.join(", "))?;
+    }
+
     let luck_text = if save.daily_luck > 0.5 {
         format!("*{}*", &save.daily_luck)
     } else if save.daily_luck > 0.0 {
@@ -859,9 +1129,19 @@ fn cmd_todo(opt: &GameAndSaveOpt) -> Result<()> {
     writeln!(&mut text, "Today's weather:")?;
     writeln!(&mut text, "  Farm: {:?}", default_weather.today())?;
     writeln!(&mut text, "  Island: {:?}", island_weather.today())?;
+    // The island's weather for tomorrow is predicted rather than read from
+    // the save.
+    let (mut state, _) = PredictionGameState::from_save(&save);
+    state.days_played += 1;
+    let island = WeatherLocation::new("Island", &data)?;
+    let island_prediction = predict_weather::<HashedSeedGenerator>(&island, &state)?;
     writeln!(&mut text, "Tomorrow's weather:")?;
     writeln!(&mut text, "  Farm: {:?}", default_weather.tomorrow())?;
-    writeln!(&mut text, "  Island: {:?}", island_weather.tomorrow())?;
+    writeln!(
+        &mut text,
+        "  Island: {}",
+        format_weather_prediction(&island_prediction)
+    )?;
 
     writeln!(&mut text, "\nLevels:")?;
     for (skill, (level, xp_to_go)) in save.player.levels() {
@@ -1193,6 +1473,64 @@ fn cmd_package(opt: &PackageOpt) -> Result<()> {
     }
 }
 
+fn cmd_audit_conditions(opt: &AuditConditionsOpt) -> Result<()> {
+    let data = GameData::from_content_dir(opt.content.get()?)?;
+    let coverage = analyze_conditions(&data);
+
+    if let Format::Json = opt.format {
+        println!("{}", serde_json::to_string_pretty(&coverage)?);
+        return Ok(());
+    }
+
+    let mut text = String::new();
+    writeln!(&mut text, "# Condition support")?;
+    writeln!(&mut text, "|:-|-:|-:|-:|-:|")?;
+    writeln!(
+        &mut text,
+        "|**Source**|**Full**|**Partial**|**Unsupported**|**Total**|"
+    )?;
+    writeln!(&mut text, "|-")?;
+    for (source, counts) in coverage.counts() {
+        writeln!(
+            &mut text,
+            "|{source}|{}|{}|{}|{}|",
+            counts.full,
+            counts.partial,
+            counts.unsupported,
+            counts.total()
+        )?;
+    }
+    writeln!(&mut text, "|-")?;
+
+    writeln!(&mut text, "# Unsupported queries")?;
+    writeln!(&mut text, "|:-|-:|")?;
+    writeln!(&mut text, "|**Query**|**Count**|")?;
+    writeln!(&mut text, "|-")?;
+    for (query, count) in coverage.unsupported_queries() {
+        writeln!(&mut text, "|{query}|{count}|")?;
+    }
+    writeln!(&mut text, "|-")?;
+    println!("{}", mad_skin().term_text(&text));
+
+    if opt.verbose {
+        for condition in coverage
+            .conditions
+            .iter()
+            .filter(|condition| condition.support != QuerySupport::Full)
+        {
+            println!(
+                "{} {} ({}): {}",
+                condition.source, condition.entry, condition.support, condition.condition
+            );
+            if let Some(error) = &condition.error {
+                println!("  error: {error}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
 fn cmd_perfection(opt: &GameAndSaveOpt) -> Result<()> {
     let data = GameData::from_content_dir(opt.content.get()?)?;
     let f = File::open(&opt.file)?;
@@ -1206,38 +1544,174 @@ fn cmd_perfection(opt: &GameAndSaveOpt) -> Result<()> {
 
 fn cmd_predict_bubbles(opt: &BubblesOpt) -> Result<()> {
     let data = GameData::from_content_dir(opt.content.get()?)?;
-    let map = data.load_map(&opt.map_name)?;
-    let bubbles = predictor::bubbles::calculate_bubbles::<HashedSeedGenerator>(
-        &map,
-        opt.days_played,
-        opt.seed as u32,
-    )?;
+    let save = opt
+        .save
+        .as_ref()
+        .map(|path| -> Result<SaveGame> {
+            let f = File::open(path)?;
+            let mut r = BufReader::new(f);
+            SaveGame::from_reader(&mut r)
+        })
+        .transpose()?;
+
+    let frenzy = |bubbles: &Bubbles| if bubbles.frenzy { " frenzy" } else { "" };
+
+    let Some(map_name) = &opt.map_name else {
+        let schedule = predict_bubbles_for_day::<HashedSeedGenerator>(
+            &data,
+            save.as_ref(),
+            opt.days_played,
+            opt.seed as u32,
+        )?;
+        for entry in schedule {
+            println!(
+                "{} (x:{} y:{}) {}{}",
+                entry.location,
+                entry.bubbles.location.x,
+                entry.bubbles.location.y,
+                entry.bubbles.span,
+                frenzy(&entry.bubbles),
+            );
+        }
+        return Ok(());
+    };
+
+    let map = data.load_map(map_name)?;
+    let location = save
+        .as_ref()
+        .zip(opt.location.as_ref())
+        .and_then(|(save, name)| save.locations.get(name));
+    let bubbles =
+        calculate_bubbles::<HashedSeedGenerator>(&map, location, opt.days_played, opt.seed as u32)?;
     for bubble in bubbles {
         println!(
-            "(x:{} y:{}) {}",
-            bubble.location.x, bubble.location.y, bubble.span
+            "(x:{} y:{}) {}{}",
+            bubble.location.x,
+            bubble.location.y,
+            bubble.span,
+            frenzy(&bubble),
         );
     }
     Ok(())
 }
 
-fn cmd_predict_garbage(opt: &GameContentLoc) -> Result<()> {
-    let data = GameData::from_content_dir(opt.get()?)?;
-    let state = PredictionGameState {
-        game_id: 254546202,
-        days_played: 1,
-        daily_luck: 0.0999,
-        ..Default::default()
+fn item_names(data: &GameData) -> Result<HashMap<ItemId, &String>> {
+    data.objects
+        .iter()
+        .map(|(id, object)| (format!("(O){id}"), &object.name))
+        .chain(
+            data.furniture
+                .iter()
+                .map(|(id, furniture)| (format!("(F){id}"), &furniture.name)),
+        )
+        .chain(
+            data.big_craftables
+                .iter()
+                .map(|(id, big_craftable)| (format!("(BC){id}"), &big_craftable.name)),
+        )
+        .map(|(id, name)| Ok((id.parse::<ItemId>()?, name)))
+        .collect()
+}
+
+fn print_shop_stock(stock: &ShopStockPrediction, names: &HashMap<ItemId, &String>) {
+    let name = |item: &ItemId| match names.get(item) {
+        Some(name) => name.to_string(),
+        None => format!("{:?}", item),
     };
+
+    for item in &stock.items {
+        let price = match (item.price, &item.trade_item) {
+            (Some(price), Some(trade)) => {
+                format!("{}g + {} {}", price, trade.quantity, name(&trade.item))
+            }
+            (Some(price), None) => format!("{}g", price),
+            (None, Some(trade)) => format!("{} {}", trade.quantity, name(&trade.item)),
+            (None, None) => "?".to_string(),
+        };
+        let in_stock = item
+            .stock
+            .map(|stock| format!(" ({} in stock)", stock))
+            .unwrap_or_default();
+        let uncertain = if item.uncertain { " (uncertain)" } else { "" };
+        println!(
+            "  {} x{} {}{}{}",
+            name(&item.item),
+            item.quantity,
+            price,
+            in_stock,
+            uncertain
+        );
+    }
+    if let Some(query) = &stock.unsupported_query {
+        println!("  ... stock after unsupported item query {} unknown", query);
+    }
+}
+
+fn cmd_predict_cart(opt: &CartOpt) -> Result<()> {
+    let data = GameData::from_content_dir(opt.content.get()?)?;
+    let cart = TravelingCart::new(&data)?;
+    let names = item_names(&data)?;
+    let base_state = opt.state.get()?;
+
+    let first_day = base_state.days_played;
+    for days_played in first_day..(first_day + opt.days) {
+        if !is_traveling_cart_day(days_played) {
+            continue;
+        }
+
+        let state = PredictionGameState {
+            days_played,
+            ..base_state.clone()
+        };
+        let stock = predict_traveling_cart::<HashedSeedGenerator>(&cart, &state)?;
+
+        println!(
+            "{:?} {} year {}:",
+            state.season(),
+            state.day_of_month(),
+            state.year()
+        );
+        print_shop_stock(&stock, &names);
+    }
+
+    Ok(())
+}
+
+fn cmd_predict_shop(opt: &ShopOpt) -> Result<()> {
+    let data = GameData::from_content_dir(opt.content.get()?)?;
+    let shop = Shop::new(&opt.shop, &data)?;
+    let names = item_names(&data)?;
+
+    let base_state = opt.state.get()?;
+    let first_day = base_state.days_played;
+    for days_played in first_day..(first_day + opt.days) {
+        let state = PredictionGameState {
+            days_played,
+            ..base_state.clone()
+        };
+        let stock = predict_shop_stock::<HashedSeedGenerator>(&shop, &state)?;
+
+        println!(
+            "{:?} {} year {}:",
+            state.season(),
+            state.day_of_month(),
+            state.year()
+        );
+        print_shop_stock(&stock, &names);
+    }
+
+    Ok(())
+}
+
+fn cmd_predict_garbage(opt: &GarbageOpt) -> Result<()> {
+    let data = GameData::from_content_dir(opt.content.get()?)?;
+    let mut state = opt.state.get()?;
+    if let Some(daily_luck) = opt.daily_luck {
+        state.daily_luck = daily_luck;
+    }
     let cans = GarbageCanLocation::iter()
         .map(|location| GarbageCan::new(location, &data.garbage_cans))
         .collect::<Result<Vec<_>>>()?;
-    println!("{:?}", item_id!("RANDOM_BASE_SEASON_ITEM"));
-    let special_items = HashMap::from([
-        (item_id!("RANDOM_BASE_SEASON_ITEM"), "Random season item"),
-        (item_id!("DISH_OF_THE_DAY"), "Dish of the Day"),
-    ]);
-
     for can in &cans {
         if let Some((reward, min_luck)) = predict_garbage::<HashedSeedGenerator>(can, &state)? {
             if let Ok(item) = data.get_object_by_id(&reward.item) {
@@ -1245,11 +1719,6 @@ fn cmd_predict_garbage(opt: &GameContentLoc) -> Result<()> {
                     "{}: {} {} (min luck {})",
                     can.location, reward.quantity, item.name, min_luck
                 );
-            } else if let Some(name) = special_items.get(&reward.item) {
-                println!(
-                    "{}: {} {} (min luck {})",
-                    can.location, reward.quantity, name, min_luck
-                );
             } else {
                 println!(
                     "{}: {} {:?} (min luck {})",
@@ -1265,19 +1734,95 @@ fn cmd_predict_garbage(opt: &GameContentLoc) -> Result<()> {
 fn cmd_predict_geode(opt: &GeodesOpt) -> Result<()> {
     let data = GameData::from_content_dir(opt.content.get()?)?;
     let geode = Geode::new(opt.geode_type, &data)?;
+    let mut base_state = opt.state.get()?;
+    if let Some(geodes_cracked) = opt.geodes_cracked {
+        base_state.geodes_cracked = geodes_cracked;
+    }
+    if let Some(mystery_boxes_opened) = opt.mystery_boxes_opened {
+        base_state.mystery_boxes_opened = mystery_boxes_opened;
+    }
+    if let Some(multiplayer_id) = opt.multiplayer_id {
+        base_state.multiplayer_id = multiplayer_id;
+    }
+    if let Some(deepest_mine_level) = opt.deepest_mine_level {
+        base_state.deepest_mine_level = deepest_mine_level;
+    }
 
     for i in 0..10 {
         let state = PredictionGameState {
-            game_id: opt.seed,
-            multiplayer_id: opt.multiplayer_id,
-            geodes_cracked: opt.geodes_cracked + i,
-            deepest_mine_level: opt.deepest_mine_level,
-            qi_beans_quest_active: false,
-            ..Default::default()
+            geodes_cracked: base_state.geodes_cracked + i,
+            mystery_boxes_opened: base_state.mystery_boxes_opened + i,
+            ..base_state.clone()
         };
-        let reward = predict_single_geode::<HashedSeedGenerator>(&geode, &state)?;
-        let object = data.get_object_by_id(&reward.item)?;
-        println!("{i}: {} {}", object.name, reward.quantity);
+        // Some mystery box drops can't be predicted.
+        let reward = match predict_single_geode::<HashedSeedGenerator>(&geode, &state) {
+            Ok(reward) => reward,
+            Err(e) => {
+                println!("{i}: {e}");
+                continue;
+            }
+        };
+        // Mystery boxes can contain items that aren't objects.
+        let name = data
+            .get_object_by_id(&reward.item)
+            .map(|object| object.name.clone())
+            .unwrap_or_else(|_| reward.item.to_string());
+        println!("{i}: {} {}", name, reward.quantity);
+    }
+    Ok(())
+}
+
+// Most likely weather along with its chance if not certain.
+fn weather_grid_cell(day: &ForecastDay) -> String {
+    let weather = WEATHERS
+        .iter()
+        .max_by(|a, b| day.weather.chance(a).total_cmp(&day.weather.chance(b)))
+        .expect("weather list is not empty");
+    let chance = day.weather.chance(weather);
+    if chance < 1.0 {
+        format!("{} {weather:?} {:.0}%", day.day_of_month, chance * 100.0)
+    } else {
+        format!("{} {weather:?}", day.day_of_month)
+    }
+}
+
+fn cmd_predict_weather(opt: &WeatherOpt) -> Result<()> {
+    let data = GameData::from_content_dir(opt.content.get()?)?;
+    let locations = match &opt.context {
+        Some(context) => vec![WeatherLocation::new(context, &data)?],
+        None => WeatherLocation::all(&data)?,
+    };
+    let state = opt.state.get()?;
+    let forecast =
+        forecast_weather::<HashedSeedGenerator>(&locations, &state, state.days_played, opt.days)?;
+
+    if let Format::Json = opt.format {
+        println!("{}", serde_json::to_string_pretty(&forecast)?);
+        return Ok(());
+    }
+
+    let skin = mad_skin();
+    for (context, calendar) in &forecast.contexts {
+        for ((year, season), days) in &calendar
+            .iter()
+            .group_by(|day| (day.year, day.season.clone()))
+        {
+            let mut cells = vec![String::new(); 28];
+            for day in days {
+                cells[day.day_of_month as usize - 1] = weather_grid_cell(day);
+            }
+
+            let mut text = String::new();
+            writeln!(&mut text, "## {context}: {season} year {year}")?;
+            text.push_str("|:-|:-|:-|:-|:-|:-|:-|\n");
+            text.push_str("|**Mon**|**Tue**|**Wed**|**Thu**|**Fri**|**Sat**|**Sun**|\n");
+            text.push_str("|:-|:-|:-|:-|:-|:-|:-|\n");
+            for week in cells.chunks(7) {
+                writeln!(&mut text, "|{}|", week.join("|"))?;
+            }
+            text.push_str("|-\n");
+            println!("{}", skin.term_text(&text));
+        }
     }
     Ok(())
 }
@@ -1285,8 +1830,11 @@ fn cmd_predict_geode(opt: &GeodesOpt) -> Result<()> {
 fn cmd_predict(opt: &PredictOpt) -> Result<()> {
     match opt {
         PredictOpt::Bubbles(o) => cmd_predict_bubbles(o),
+        PredictOpt::Cart(o) => cmd_predict_cart(o),
         PredictOpt::Garbage(o) => cmd_predict_garbage(o),
         PredictOpt::Geode(o) => cmd_predict_geode(o),
+        PredictOpt::Shop(o) => cmd_predict_shop(o),
+        PredictOpt::Weather(o) => cmd_predict_weather(o),
     }
 }
 
@@ -1391,12 +1939,14 @@ fn main() -> Result<()> {
     let opt = Opt::from_args();
 
     match opt {
+        Opt::AuditConditions(o) => cmd_audit_conditions(&o)?,
         Opt::Dump(o) => cmd_dump(&o)?,
         Opt::Bundles(o) => cmd_bundles(&o)?,
+        Opt::Crops(o) => cmd_crops(&o)?,
         Opt::Fish(o) => cmd_fish(&o)?,
         Opt::Food(o) => cmd_food(&o)?,
         Opt::Generate(o) => cmd_generate(&o)?,
-        //Opt::Geodes(o) => cmd_geodes(&o)?,
+        Opt::Geodes(o) => cmd_geodes(&o)?,
         Opt::Items(o) => cmd_items(&o)?,
         Opt::Package(o) => cmd_package(&o)?,
         Opt::Perfection(o) => cmd_perfection(&o)?,
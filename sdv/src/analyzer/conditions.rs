@@ -0,0 +1,287 @@
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::{
+    common::GenericSpawnItemDataWithCondition,
+    predictor::PredictionGameState,
+    query::{GameStateQuery, QuerySupport},
+    rng::HashedSeedGenerator,
+    GameData,
+};
+
+/// The game data file a condition was found in.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum ConditionSource {
+    Characters,
+    GarbageCans,
+    LocationContexts,
+    Locations,
+    Objects,
+    PassiveFestivals,
+}
+
+impl ConditionSource {
+    pub fn file(&self) -> &'static str {
+        match self {
+            Self::Characters => "Data/Characters",
+            Self::GarbageCans => "Data/GarbageCans",
+            Self::LocationContexts => "Data/LocationContexts",
+            Self::Locations => "Data/Locations",
+            Self::Objects => "Data/Objects",
+            Self::PassiveFestivals => "Data/PassiveFestivals",
+        }
+    }
+}
+
+impl std::fmt::Display for ConditionSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.file())
+    }
+}
+
+/// Support information for a single condition string.
+#[derive(Clone, Debug, Serialize)]
+pub struct ConditionReport {
+    pub source: ConditionSource,
+
+    /// Path to the entry containing the condition (i.e. `535/GeodeDrops/Limestone`).
+    pub entry: String,
+    pub condition: String,
+    pub support: QuerySupport,
+
+    /// Names of the queries in the condition that can't be evaluated.
+    pub unsupported_queries: Vec<String>,
+
+    /// Set if the condition failed to parse.
+    pub error: Option<String>,
+}
+
+impl ConditionReport {
+    // Support is judged by whether each query evaluates against a
+    // `PredictionGameState`, which is what the predictors pass to it.  Queries
+    // that parse but ask for state it doesn't track are unsupported.
+    fn new(source: ConditionSource, entry: String, condition: &str) -> Self {
+        let state = PredictionGameState::default();
+        let (support, unsupported_queries, error) = match GameStateQuery::parse(condition) {
+            Ok(query) => (
+                query.support_for::<HashedSeedGenerator, _>(&state),
+                query
+                    .unsupported_queries_for::<HashedSeedGenerator, _>(&state)
+                    .into_iter()
+                    .map(str::to_string)
+                    .collect(),
+                None,
+            ),
+            Err(e) => (QuerySupport::Unsupported, Vec::new(), Some(e.to_string())),
+        };
+
+        Self {
+            source,
+            entry,
+            condition: condition.to_string(),
+            support,
+            unsupported_queries,
+            error,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct SupportCounts {
+    pub full: usize,
+    pub partial: usize,
+    pub unsupported: usize,
+}
+
+impl SupportCounts {
+    fn add(&mut self, support: QuerySupport) {
+        match support {
+            QuerySupport::Full => self.full += 1,
+            QuerySupport::Partial => self.partial += 1,
+            QuerySupport::Unsupported => self.unsupported += 1,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.full + self.partial + self.unsupported
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ConditionCoverage {
+    pub conditions: Vec<ConditionReport>,
+}
+
+impl ConditionCoverage {
+    /// Support counts for each source file.
+    pub fn counts(&self) -> IndexMap<ConditionSource, SupportCounts> {
+        let mut counts: IndexMap<ConditionSource, SupportCounts> = IndexMap::new();
+        for condition in &self.conditions {
+            counts
+                .entry(condition.source)
+                .or_default()
+                .add(condition.support);
+        }
+        counts.sort_keys();
+        counts
+    }
+
+    /// Number of occurrences of each unsupported query, most common first.
+    pub fn unsupported_queries(&self) -> IndexMap<String, usize> {
+        let mut queries: IndexMap<String, usize> = IndexMap::new();
+        for name in self
+            .conditions
+            .iter()
+            .flat_map(|condition| &condition.unsupported_queries)
+        {
+            *queries.entry(name.clone()).or_default() += 1;
+        }
+        queries.sort_by(|a_name, a_count, b_name, b_count| {
+            b_count.cmp(a_count).then_with(|| a_name.cmp(b_name))
+        });
+        queries
+    }
+
+    fn add(&mut self, source: ConditionSource, entry: String, condition: Option<&String>) {
+        if let Some(condition) = condition {
+            self.conditions
+                .push(ConditionReport::new(source, entry, condition));
+        }
+    }
+
+    fn add_spawn_items<'a>(
+        &mut self,
+        source: ConditionSource,
+        prefix: &str,
+        items: impl IntoIterator<Item = &'a GenericSpawnItemDataWithCondition>,
+    ) {
+        for item in items {
+            self.add(
+                source,
+                format!("{prefix}/{}", item.parent.id),
+                item.condition.as_ref(),
+            );
+        }
+    }
+}
+
+/// Walk every condition string in `data` and report how well the query
+/// evaluator supports it.
+pub fn analyze_conditions(data: &GameData) -> ConditionCoverage {
+    let mut coverage = ConditionCoverage::default();
+
+    for (id, character) in &data.characters {
+        for appearance in &character.appearance {
+            coverage.add(
+                ConditionSource::Characters,
+                format!("{id}/Appearance/{}", appearance.id),
+                appearance.condition.as_ref(),
+            );
+        }
+        for home in character.home.iter().flatten() {
+            coverage.add(
+                ConditionSource::Characters,
+                format!("{id}/Home/{}", home.id),
+                home.condition.as_ref(),
+            );
+        }
+    }
+
+    let garbage = &data.garbage_cans;
+    coverage.add_spawn_items(
+        ConditionSource::GarbageCans,
+        "BeforeAll",
+        garbage.before_all.iter().map(|item| &item.parent),
+    );
+    coverage.add_spawn_items(
+        ConditionSource::GarbageCans,
+        "AfterAll",
+        garbage.after_all.iter().map(|item| &item.parent),
+    );
+    for (id, can) in &garbage.garbage_cans {
+        coverage.add_spawn_items(
+            ConditionSource::GarbageCans,
+            id,
+            can.items.iter().map(|item| &item.parent),
+        );
+    }
+
+    for (id, context) in &data.location_contexts {
+        for weather in &context.weather_condidtions {
+            coverage.add(
+                ConditionSource::LocationContexts,
+                format!("{id}/WeatherConditions/{}", weather.id),
+                weather.condition.as_ref(),
+            );
+        }
+    }
+
+    for (id, location) in &data.locations {
+        coverage.add_spawn_items(
+            ConditionSource::Locations,
+            &format!("{id}/ArtifactSpots"),
+            location
+                .artifact_spots
+                .iter()
+                .flatten()
+                .map(|spot| &spot.parent),
+        );
+        coverage.add_spawn_items(
+            ConditionSource::Locations,
+            &format!("{id}/Fish"),
+            location.fish.iter().flatten().map(|fish| &fish.parent),
+        );
+        coverage.add_spawn_items(
+            ConditionSource::Locations,
+            &format!("{id}/Forage"),
+            location.forage.iter().map(|forage| &forage.parent),
+        );
+    }
+
+    for (id, object) in &data.objects {
+        coverage.add_spawn_items(
+            ConditionSource::Objects,
+            &format!("{id}/GeodeDrops"),
+            object.geode_drops.iter().flatten().map(|drop| &drop.parent),
+        );
+    }
+
+    for (id, festival) in &data.passive_festivals {
+        coverage.add(
+            ConditionSource::PassiveFestivals,
+            id.clone(),
+            Some(&festival.condition),
+        );
+    }
+
+    coverage
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(condition: &str) -> ConditionReport {
+        ConditionReport::new(ConditionSource::Objects, "test".to_string(), condition)
+    }
+
+    #[test]
+    fn support_is_judged_against_prediction_state() {
+        let condition = report("SEASON spring, PLAYER_HAS_MAIL Current Got_Capsule");
+        assert_eq!(condition.support, QuerySupport::Full);
+        assert!(condition.unsupported_queries.is_empty());
+
+        // The query parses but the prediction state doesn't track the mail.
+        let condition = report("SEASON spring, PLAYER_HAS_MAIL Current someModMail");
+        assert_eq!(condition.support, QuerySupport::Partial);
+        assert_eq!(condition.unsupported_queries, vec!["PLAYER_HAS_MAIL"]);
+
+        let condition = report("ITEM_ID Target (O)74");
+        assert_eq!(condition.support, QuerySupport::Unsupported);
+        assert_eq!(condition.error, None);
+
+        let condition = report("PLAYER_STAT Current");
+        assert_eq!(condition.support, QuerySupport::Unsupported);
+        assert!(condition.error.is_some());
+    }
+}
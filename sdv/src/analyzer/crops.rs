@@ -0,0 +1,460 @@
+use anyhow::{anyhow, Result};
+use strum::EnumString;
+
+use crate::{
+    common::Point,
+    gamedata::CropData,
+    generate_seed,
+    predictor::PredictionGameState,
+    rng::{Rng, SeedGenerator},
+};
+
+#[derive(Clone, Copy, Debug, EnumString, Eq, PartialEq)]
+#[strum(ascii_case_insensitive)]
+pub enum Fertilizer {
+    None,
+    Basic,
+    Quality,
+    Deluxe,
+    SpeedGro,
+    DeluxeSpeedGro,
+    HyperSpeedGro,
+}
+
+impl Fertilizer {
+    // Logic found in `HoeDirt.GetFertilizerQualityBoostLevel()`.
+    const fn quality_level(self) -> i32 {
+        match self {
+            Fertilizer::Basic => 1,
+            Fertilizer::Quality => 2,
+            Fertilizer::Deluxe => 3,
+            _ => 0,
+        }
+    }
+
+    // Logic found in `HoeDirt.GetFertilizerSpeedBoost()`.
+    const fn speed_boost(self) -> f32 {
+        match self {
+            Fertilizer::SpeedGro => 0.1,
+            Fertilizer::DeluxeSpeedGro => 0.25,
+            Fertilizer::HyperSpeedGro => 0.33,
+            _ => 0.0,
+        }
+    }
+}
+
+/// How and where a crop is grown.
+#[derive(Clone, Debug)]
+pub struct CropConditions {
+    pub fertilizer: Fertilizer,
+    pub agriculturist: bool,
+    pub farming_level: i32,
+
+    /// Set for paddy crops planted next to water.
+    pub near_water: bool,
+
+    /// Set for locations where crops grow in any season like the greenhouse
+    /// or Ginger Island.
+    pub ignore_seasons: bool,
+}
+
+/// Chance of a harvest being each quality.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct QualityDistribution {
+    pub normal: f64,
+    pub silver: f64,
+    pub gold: f64,
+    pub iridium: f64,
+}
+
+impl QualityDistribution {
+    fn add(&mut self, quality: i32, chance: f64) {
+        match quality {
+            0 => self.normal += chance,
+            1 => self.silver += chance,
+            2 => self.gold += chance,
+            _ => self.iridium += chance,
+        }
+    }
+}
+
+/// A single tile's harvest.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CropYield {
+    pub quality: i32,
+    pub quantity: u32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CropHarvest {
+    pub days_played: u32,
+
+    /// Set for harvests after the first one of a regrowing crop.
+    pub regrowth: bool,
+
+    /// Yields of each simulated tile in order.
+    pub yields: Vec<CropYield>,
+
+    /// Distribution of the qualities in `yields`.  When no tiles are
+    /// simulated this is the chance of each quality instead.
+    pub distribution: QualityDistribution,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CropSimulation {
+    pub harvests: Vec<CropHarvest>,
+
+    /// Day the crop dies because its season ended.
+    pub dies_on: Option<u32>,
+}
+
+// Logic found in `HoeDirt.applySpeedIncreases()`.  The float math is kept
+// since it can round the number of days removed up.
+fn growth_days(crop: &CropData, conditions: &CropConditions) -> u32 {
+    let mut phases = crop.days_in_phase.clone();
+    let mut speed_increase = conditions.fertilizer.speed_boost();
+    if crop.is_paddy_crop && conditions.near_water {
+        speed_increase += 0.25;
+    }
+    if conditions.agriculturist {
+        speed_increase += 0.1;
+    }
+
+    let total: i32 = phases.iter().sum();
+    let mut days_to_remove = (total as f32 * speed_increase).ceil() as i32;
+    let mut tries = 0;
+    while days_to_remove > 0 && tries < 3 {
+        for (i, days) in phases.iter_mut().enumerate() {
+            if (i > 0 || *days > 1) && *days > 0 {
+                *days -= 1;
+                days_to_remove -= 1;
+            }
+            if days_to_remove <= 0 {
+                break;
+            }
+        }
+        tries += 1;
+    }
+
+    phases.iter().sum::<i32>().max(0) as u32
+}
+
+// Logic found in `Crop.harvest()`.
+fn quality_chances(farming_level: i32, fertilizer: Fertilizer) -> (f64, f64) {
+    let farming_level = farming_level as f64;
+    let gold = 0.2 * (farming_level / 10.0)
+        + 0.2 * fertilizer.quality_level() as f64 * ((farming_level + 2.0) / 12.0)
+        + 0.01;
+    let silver = (gold * 2.0).min(0.75);
+    (gold, silver)
+}
+
+fn clamp_quality(crop: &CropData, quality: i32) -> i32 {
+    let max = crop.harvest_max_quality.unwrap_or(quality);
+    quality.max(crop.harvest_min_quality).min(max)
+}
+
+/// Chance of a harvest of `crop` being each quality.
+pub fn expected_quality(crop: &CropData, conditions: &CropConditions) -> QualityDistribution {
+    let (gold, silver) = quality_chances(conditions.farming_level, conditions.fertilizer);
+    let deluxe = conditions.fertilizer.quality_level() >= 3;
+
+    let iridium = if deluxe { gold / 2.0 } else { 0.0 };
+    let gold = (1.0 - iridium) * gold;
+    let rest = 1.0 - iridium - gold;
+    let (silver, normal) = if deluxe {
+        (rest, 0.0)
+    } else {
+        (rest * silver, rest * (1.0 - silver))
+    };
+
+    let mut distribution = QualityDistribution::default();
+    for (quality, chance) in [(0, normal), (1, silver), (2, gold), (4, iridium)].iter() {
+        distribution.add(clamp_quality(crop, *quality), *chance);
+    }
+    distribution
+}
+
+/// Predict the harvest of `crop` from the tile at `tile` on `days_played`.
+pub fn predict_crop_yield<G: SeedGenerator>(
+    crop: &CropData,
+    conditions: &CropConditions,
+    game_id: u32,
+    days_played: u32,
+    tile: &Point<i32>,
+) -> Result<CropYield> {
+    // Logic found in `Crop.harvest()`.
+    let mut rng = Rng::new(generate_seed!(
+        G,
+        tile.x as f64 * 7.0,
+        tile.y as f64 * 11.0,
+        days_played,
+        game_id
+    ));
+
+    let (gold, silver) = quality_chances(conditions.farming_level, conditions.fertilizer);
+    let deluxe = conditions.fertilizer.quality_level() >= 3;
+    // The order of these conditionals is important as they contain rng pulls.
+    let quality = if deluxe && rng.next_double() < gold / 2.0 {
+        4
+    } else if rng.next_double() < gold {
+        2
+    } else if rng.next_double() < silver || deluxe {
+        1
+    } else {
+        0
+    };
+
+    let mut quantity = 1;
+    if crop.harvest_min_stack > 1 || crop.harvest_max_stack > 1 {
+        let max = crop.harvest_max_stack
+            + (conditions.farming_level as f32 * crop.harvest_max_increase_per_farming_level)
+                as i32;
+        quantity = rng.next_range(crop.harvest_min_stack, max.max(crop.harvest_min_stack) + 1)?;
+    }
+    if crop.extra_harvest_chance > 0.0 {
+        while rng.next_double() < crop.extra_harvest_chance.min(0.9) {
+            quantity += 1;
+        }
+    }
+
+    Ok(CropYield {
+        quality: clamp_quality(crop, quality),
+        quantity: quantity.max(0) as u32,
+    })
+}
+
+/// Simulate a crop planted on `state.days_played` through `until`.
+///
+/// The crop is assumed to be watered every day and harvested as soon as it's
+/// ready.  Each harvest is predicted for every tile in `tiles`.
+pub fn simulate_crop<G: SeedGenerator>(
+    crop: &CropData,
+    conditions: &CropConditions,
+    state: &PredictionGameState,
+    tiles: &[Point<i32>],
+    until: u32,
+) -> Result<CropSimulation> {
+    let in_season = |days_played: u32| {
+        let state = PredictionGameState {
+            days_played,
+            ..Default::default()
+        };
+        conditions.ignore_seasons || crop.seasons.contains(&state.season())
+    };
+
+    if !in_season(state.days_played) {
+        return Err(anyhow!(
+            "crop {} can't be planted in {}",
+            crop.id,
+            state.season()
+        ));
+    }
+
+    // Out of season crops die overnight at the start of the new season.
+    let mut simulation = CropSimulation {
+        dies_on: ((state.days_played + 1)..=until).find(|day| !in_season(*day)),
+        ..Default::default()
+    };
+    let last_day = simulation.dies_on.map_or(until, |day| day - 1);
+
+    let expected = expected_quality(crop, conditions);
+    let mut harvest_day = state.days_played + growth_days(crop, conditions);
+    let mut regrowth = false;
+    while harvest_day <= last_day {
+        let yields = tiles
+            .iter()
+            .map(|tile| predict_crop_yield::<G>(crop, conditions, state.game_id, harvest_day, tile))
+            .collect::<Result<Vec<_>>>()?;
+
+        let distribution = if yields.is_empty() {
+            expected.clone()
+        } else {
+            let mut distribution = QualityDistribution::default();
+            for crop_yield in &yields {
+                distribution.add(crop_yield.quality, 1.0 / yields.len() as f64);
+            }
+            distribution
+        };
+
+        simulation.harvests.push(CropHarvest {
+            days_played: harvest_day,
+            regrowth,
+            yields,
+            distribution,
+        });
+
+        if crop.regrow_days <= 0 {
+            break;
+        }
+        harvest_day += crop.regrow_days as u32;
+        regrowth = true;
+    }
+
+    Ok(simulation)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{common::Season, gamedata::crop::HarvestMethod, rng::HashedSeedGenerator};
+
+    use super::*;
+
+    fn crop(seasons: Vec<Season>, days_in_phase: Vec<i32>, regrow_days: i32) -> CropData {
+        CropData {
+            id: "Test".to_string(),
+            seasons,
+            days_in_phase,
+            regrow_days,
+            is_raised: false,
+            is_paddy_crop: false,
+            needs_watering: true,
+            harvest_item_id: "(O)24".to_string(),
+            harvest_min_stack: 1,
+            harvest_max_stack: 1,
+            harvest_min_quality: 0,
+            harvest_max_quality: None,
+            harvest_max_increase_per_farming_level: 0.0,
+            extra_harvest_chance: 0.0,
+            harvest_method: HarvestMethod::Grab,
+            plantable_location_rules: None,
+            tint_colors: None,
+            texture: None,
+            sprite_index: 0,
+            count_for_monoculture: false,
+            count_for_polyculture: false,
+            custom_fields: None,
+        }
+    }
+
+    fn conditions(fertilizer: Fertilizer, agriculturist: bool) -> CropConditions {
+        CropConditions {
+            fertilizer,
+            agriculturist,
+            farming_level: 0,
+            near_water: false,
+            ignore_seasons: false,
+        }
+    }
+
+    #[test]
+    fn speed_increases_remove_growth_days() {
+        // Parsnips
+        let parsnip = crop(vec![Season::Spring], vec![1, 1, 1, 1], -1);
+        assert_eq!(
+            growth_days(&parsnip, &conditions(Fertilizer::None, false)),
+            4
+        );
+        assert_eq!(
+            growth_days(&parsnip, &conditions(Fertilizer::SpeedGro, false)),
+            3
+        );
+
+        // Cauliflower
+        let cauliflower = crop(vec![Season::Spring], vec![1, 2, 4, 4, 1], -1);
+        assert_eq!(
+            growth_days(&cauliflower, &conditions(Fertilizer::None, false)),
+            12
+        );
+        assert_eq!(
+            growth_days(&cauliflower, &conditions(Fertilizer::HyperSpeedGro, true)),
+            6
+        );
+    }
+
+    #[test]
+    fn regrowing_crops_die_at_season_end() {
+        // Strawberries planted on spring 1.
+        let strawberry = crop(vec![Season::Spring], vec![1, 1, 2, 2, 2], 4);
+        let state = PredictionGameState {
+            game_id: 254546202,
+            days_played: 1,
+            ..Default::default()
+        };
+        let simulation = simulate_crop::<HashedSeedGenerator>(
+            &strawberry,
+            &conditions(Fertilizer::None, false),
+            &state,
+            &[],
+            56,
+        )
+        .unwrap();
+
+        assert_eq!(
+            simulation
+                .harvests
+                .iter()
+                .map(|harvest| harvest.days_played)
+                .collect::<Vec<_>>(),
+            vec![9, 13, 17, 21, 25]
+        );
+        assert!(!simulation.harvests[0].regrowth);
+        assert!(simulation.harvests[1].regrowth);
+        assert_eq!(simulation.dies_on, Some(29));
+    }
+
+    #[test]
+    fn deluxe_fertilizer_is_never_normal_quality() {
+        let parsnip = crop(vec![Season::Spring], vec![1, 1, 1, 1], -1);
+        let distribution = expected_quality(&parsnip, &conditions(Fertilizer::Deluxe, false));
+        assert_eq!(distribution.normal, 0.0);
+        let total =
+            distribution.normal + distribution.silver + distribution.gold + distribution.iridium;
+        assert!((total - 1.0).abs() < 1e-9);
+    }
+
+    fn yields(crop: &CropData, conditions: &CropConditions, y: i32) -> Vec<(i32, u32)> {
+        (0..6)
+            .map(|x| {
+                let crop_yield = predict_crop_yield::<HashedSeedGenerator>(
+                    crop,
+                    conditions,
+                    254546202,
+                    9,
+                    &Point { x, y },
+                )
+                .unwrap();
+                (crop_yield.quality, crop_yield.quantity)
+            })
+            .collect()
+    }
+
+    // Expected values were computed with a separate Python port of the
+    // game's seeding, `System.Random` and `Crop.harvest()`.
+    #[test]
+    fn crop_yields_are_predicted() {
+        let mut potato = crop(vec![Season::Spring], vec![1, 1, 1, 2, 1], -1);
+        potato.extra_harvest_chance = 0.2;
+        let leveled = CropConditions {
+            farming_level: 10,
+            ..conditions(Fertilizer::Quality, false)
+        };
+        assert_eq!(
+            yields(&potato, &leveled, 0),
+            vec![(1, 1), (2, 2), (0, 1), (2, 1), (2, 1), (2, 1)]
+        );
+
+        let parsnip = crop(vec![Season::Spring], vec![1, 1, 1, 1], -1);
+        let leveled = CropConditions {
+            farming_level: 10,
+            ..conditions(Fertilizer::Deluxe, false)
+        };
+        assert_eq!(
+            yields(&parsnip, &leveled, 3),
+            vec![(2, 1), (2, 1), (1, 1), (4, 1), (4, 1), (2, 1)]
+        );
+
+        // Stacks grow with the farming level.
+        let mut stacked = crop(vec![Season::Spring], vec![1, 1, 1, 1], -1);
+        stacked.harvest_max_stack = 2;
+        stacked.harvest_max_increase_per_farming_level = 0.5;
+        let leveled = CropConditions {
+            farming_level: 4,
+            ..conditions(Fertilizer::None, false)
+        };
+        assert_eq!(
+            yields(&stacked, &leveled, 0),
+            vec![(0, 2), (1, 3), (0, 4), (0, 1), (0, 1), (0, 3)]
+        );
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{
+    gamedata::CharacterData,
+    query::QueryContext,
+    save::{Friendship, SaveGame},
+    GameData,
+};
+
+const DAYS_PER_YEAR: u32 = 28 * 4;
+
+/// A player's friendship with a single NPC.
+#[derive(Clone, Debug)]
+pub struct NpcFriendship<'a> {
+    pub name: &'a str,
+    pub character: &'a CharacterData,
+
+    /// `None` if the player hasn't met the NPC yet.
+    pub friendship: Option<&'a Friendship>,
+
+    /// Days until the NPC's next birthday.  `0` if it's today.
+    pub days_until_birthday: Option<u32>,
+}
+
+impl<'a> NpcFriendship<'a> {
+    pub fn hearts(&self) -> i32 {
+        self.friendship.map_or(0, Friendship::hearts)
+    }
+
+    pub fn is_birthday(&self) -> bool {
+        self.days_until_birthday == Some(0)
+    }
+
+    /// Whether the NPC has been met and will accept a gift today.
+    pub fn can_gift_today(&self) -> bool {
+        self.character.can_receive_gifts
+            && self
+                .friendship
+                .is_some_and(|friendship| friendship.can_gift_today(self.is_birthday()))
+    }
+
+    /// Whether the NPC has been met and hasn't received a gift this week.
+    pub fn not_gifted_this_week(&self) -> bool {
+        self.character.can_receive_gifts
+            && self
+                .friendship
+                .is_some_and(|friendship| friendship.gifts_this_week == 0)
+    }
+}
+
+/// Join the player's friendship data against the NPCs in `game_data`.
+///
+/// NPCs are listed in game data order.  NPCs that can't receive gifts are
+/// only listed if the player has friendship data for them.
+pub fn analyze_friendship<'a>(
+    save: &'a SaveGame,
+    game_data: &'a GameData,
+) -> Vec<NpcFriendship<'a>> {
+    let today = (save.days_played() - 1) % DAYS_PER_YEAR;
+    game_data
+        .characters
+        .iter()
+        .filter_map(|(name, character)| {
+            let friendship = save.player.friendship.get(name);
+            if friendship.is_none() && !character.can_receive_gifts {
+                return None;
+            }
+
+            let days_until_birthday = character.birth_season.as_ref().map(|season| {
+                let birthday = season.clone() as u32 * 28 + (character.birthday.max(1) as u32 - 1);
+                (birthday + DAYS_PER_YEAR - today) % DAYS_PER_YEAR
+            });
+
+            Some(NpcFriendship {
+                name,
+                character,
+                friendship,
+                days_until_birthday,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,352 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{anyhow, Result};
+use indexmap::IndexSet;
+
+use crate::{
+    common::ItemId,
+    predictor::{
+        geode::{predict_single_geode, Geode, GeodeType},
+        DropReward, PredictionGameState,
+    },
+    rng::SeedGenerator,
+    save::{Object, Profession},
+    GameData,
+};
+
+/// Clint's fee for cracking open a geode.
+pub const CRACKING_FEE: i32 = 25;
+
+/// What a geode cracking plan optimizes for.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GeodeGoal {
+    /// Maximize the sale value of the rewards minus the sale value of the
+    /// geodes and cracking fees.
+    Value,
+
+    /// Get `item` within the first `within` cracks.
+    Item { item: ItemId, within: usize },
+
+    /// Get as many of these items, usually ones not yet donated to the
+    /// museum, as possible.
+    Museum(HashSet<ItemId>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeodeStep {
+    pub ty: GeodeType,
+    pub reward: DropReward,
+
+    /// Value of the reward minus the geode's sale price and the cracking
+    /// fee.
+    pub value: i32,
+
+    /// Set when the reward is a goal item that wasn't already found earlier
+    /// in the plan.
+    pub goal_item: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GeodePlan {
+    pub steps: Vec<GeodeStep>,
+    pub value: i32,
+    pub goal_items: usize,
+}
+
+// A single prediction along with its precomputed value.
+struct Prediction {
+    reward: DropReward,
+    value: i32,
+    // Index into the goal item list.
+    goal: Option<usize>,
+}
+
+// Goal items found, value and the index of the geode type to crack next.
+type Choice = (usize, i32, Option<usize>);
+
+struct Optimizer {
+    types: Vec<GeodeType>,
+    // Indexed by geode type then crack number.
+    predictions: Vec<Vec<Prediction>>,
+    max_cracks: usize,
+    total: u32,
+    // Keyed by remaining geode counts and the bitmask of goal items found.
+    memo: HashMap<(Vec<u32>, u64), Choice>,
+}
+
+impl Optimizer {
+    // Returns the best (goal items, value) from this point on along with the
+    // geode type to crack next.  `None` means stop cracking.
+    fn best(&mut self, counts: &mut [u32], found: u64) -> Choice {
+        let level = self.cracked(counts);
+        if level >= self.max_cracks {
+            return (0, 0, None);
+        }
+
+        let key = (counts.to_vec(), found);
+        if let Some(result) = self.memo.get(&key) {
+            return *result;
+        }
+
+        let mut best = (0, 0, None);
+        for i in 0..self.types.len() {
+            if counts[i] == 0 {
+                continue;
+            }
+            let prediction = &self.predictions[i][level];
+            let (mut goal_items, mut found_after) = (0, found);
+            if let Some(goal) = prediction.goal {
+                if found & (1 << goal) == 0 {
+                    goal_items = 1;
+                    found_after |= 1 << goal;
+                }
+            }
+            let value = prediction.value;
+
+            counts[i] -= 1;
+            let (next_goal_items, next_value, _) = self.best(counts, found_after);
+            counts[i] += 1;
+
+            let candidate = (goal_items + next_goal_items, value + next_value, Some(i));
+            if (candidate.0, candidate.1) > (best.0, best.1) {
+                best = candidate;
+            }
+        }
+
+        self.memo.insert(key, best);
+        best
+    }
+
+    // Walk the memoized choices to build the plan.
+    fn plan(&mut self, mut counts: Vec<u32>) -> GeodePlan {
+        let mut found = 0u64;
+        let mut plan = GeodePlan::default();
+        while let (_, _, Some(i)) = self.best(&mut counts, found) {
+            let prediction = &self.predictions[i][self.cracked(&counts)];
+            let goal_item = prediction.goal.is_some_and(|goal| found & (1 << goal) == 0);
+            if let Some(goal) = prediction.goal {
+                found |= 1 << goal;
+            }
+            plan.value += prediction.value;
+            plan.goal_items += goal_item as usize;
+            plan.steps.push(GeodeStep {
+                ty: self.types[i],
+                reward: prediction.reward.clone(),
+                value: prediction.value,
+                goal_item,
+            });
+            counts[i] -= 1;
+        }
+        plan
+    }
+
+    fn cracked(&self, counts: &[u32]) -> usize {
+        (self.total - counts.iter().sum::<u32>()) as usize
+    }
+}
+
+/// Pick which geode to crack at each step.
+///
+/// Geodes of all types share the `GeodesCracked` stat as their seed, so the
+/// order they're cracked in changes what they contain.  `geodes` is the
+/// number of each type available and `state` the game state before any of
+/// them are cracked.  At most `max_cracks` geodes are cracked.  The search
+/// grows quickly with the number of geode types and cracks.
+///
+/// Mystery boxes are seeded with their own stat and are not supported.
+pub fn optimize_geodes<G: SeedGenerator>(
+    game_data: &GameData,
+    state: &PredictionGameState,
+    geodes: &[(GeodeType, u32)],
+    professions: &IndexSet<Profession>,
+    goal: &GeodeGoal,
+    max_cracks: usize,
+) -> Result<GeodePlan> {
+    if let Some((ty, _)) = geodes.iter().find(|(ty, _)| ty.is_mystery_box()) {
+        return Err(anyhow!("{:?} can't be optimized with geodes", ty));
+    }
+
+    let (goal_items, max_cracks): (Vec<&ItemId>, usize) = match goal {
+        GeodeGoal::Value => (Vec::new(), max_cracks),
+        GeodeGoal::Item { item, within } => (vec![item], max_cracks.min(*within)),
+        GeodeGoal::Museum(items) => (items.iter().collect(), max_cracks),
+    };
+    if goal_items.len() > 64 {
+        return Err(anyhow!("at most 64 goal items are supported"));
+    }
+
+    let geodes: Vec<(GeodeType, u32)> = geodes
+        .iter()
+        .filter(|(_, count)| *count > 0)
+        .copied()
+        .collect();
+    let total: u32 = geodes.iter().map(|(_, count)| count).sum();
+    let max_cracks = max_cracks.min(total as usize);
+
+    let price = |item: &ItemId, quantity: u32| -> i32 {
+        game_data
+            .get_object_by_id(item)
+            .map(|object| Object::from_gamedata(object, quantity as i32).stack_price(professions))
+            .unwrap_or(0)
+    };
+
+    let mut predictions = Vec::new();
+    for (ty, _) in &geodes {
+        let geode = Geode::new(*ty, game_data)?;
+        let geode_price = game_data
+            .get_object_by_name(ty.item_name())
+            .map(|object| Object::from_gamedata(object, 1).stack_price(professions))?;
+        let mut type_predictions = Vec::new();
+        for level in 0..max_cracks {
+            let state = PredictionGameState {
+                geodes_cracked: state.geodes_cracked + level as u32,
+                ..state.clone()
+            };
+            let reward = predict_single_geode::<G>(&geode, &state)?;
+            let value = price(&reward.item, reward.quantity) - geode_price - CRACKING_FEE;
+            let goal = goal_items.iter().position(|item| **item == reward.item);
+            type_predictions.push(Prediction {
+                reward,
+                value,
+                goal,
+            });
+        }
+        predictions.push(type_predictions);
+    }
+
+    let mut optimizer = Optimizer {
+        types: geodes.iter().map(|(ty, _)| *ty).collect(),
+        predictions,
+        max_cracks,
+        total,
+        memo: HashMap::new(),
+    };
+
+    let counts: Vec<u32> = geodes.iter().map(|(_, count)| *count).collect();
+    Ok(optimizer.plan(counts))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{common::items, item_id, rng::HashedSeedGenerator};
+
+    use super::*;
+
+    fn prediction(item: ItemId, value: i32, goal: Option<usize>) -> Prediction {
+        Prediction {
+            reward: DropReward::new(item, 1),
+            value,
+            goal,
+        }
+    }
+
+    #[test]
+    fn goal_items_beat_value() {
+        // Cracking the frozen geode second gets the goal item along with the
+        // most value.
+        let mut optimizer = Optimizer {
+            types: vec![GeodeType::Geode, GeodeType::FrozenGeode],
+            predictions: vec![
+                vec![
+                    prediction(item_id!("(O)80"), 100, None),
+                    prediction(item_id!("(O)86"), -10, None),
+                ],
+                vec![
+                    prediction(item_id!("(O)82"), 10, None),
+                    prediction(item_id!("(O)74"), 2000, Some(0)),
+                ],
+            ],
+            max_cracks: 2,
+            total: 2,
+            memo: HashMap::new(),
+        };
+        let plan = optimizer.plan(vec![1, 1]);
+        assert_eq!(
+            plan.steps.iter().map(|step| step.ty).collect::<Vec<_>>(),
+            vec![GeodeType::Geode, GeodeType::FrozenGeode]
+        );
+        assert_eq!(plan.goal_items, 1);
+        assert_eq!(plan.value, 2100);
+    }
+
+    #[test]
+    fn goal_items_cost_value() {
+        // Cracking the geode first is worth the most but only cracking the
+        // frozen geode first gets the goal item.
+        let mut optimizer = Optimizer {
+            types: vec![GeodeType::Geode, GeodeType::FrozenGeode],
+            predictions: vec![
+                vec![
+                    prediction(item_id!("(O)80"), 500, None),
+                    prediction(item_id!("(O)86"), 50, None),
+                ],
+                vec![
+                    prediction(item_id!("(O)74"), -20, Some(0)),
+                    prediction(item_id!("(O)82"), 10, None),
+                ],
+            ],
+            max_cracks: 2,
+            total: 2,
+            memo: HashMap::new(),
+        };
+        let plan = optimizer.plan(vec![1, 1]);
+        assert_eq!(
+            plan.steps.iter().map(|step| step.ty).collect::<Vec<_>>(),
+            vec![GeodeType::FrozenGeode, GeodeType::Geode]
+        );
+        assert_eq!(plan.goal_items, 1);
+        assert_eq!(plan.value, 30);
+    }
+
+    #[test]
+    fn optimizes_known_geode_drops() {
+        // Uses the drops verified in `predictor::geode`'s tests.  The frozen
+        // tear is the ninth drop so the frozen geode must be cracked last.
+        let game_data =
+            GameData::from_content_dir(crate::gamedata::get_game_content_path().unwrap()).unwrap();
+        let state = PredictionGameState {
+            game_id: 7269403,
+            multiplayer_id: -7347405514601242418i64,
+            geodes_cracked: 1,
+            ..Default::default()
+        };
+        let plan = optimize_geodes::<HashedSeedGenerator>(
+            &game_data,
+            &state,
+            &[(GeodeType::FrozenGeode, 1), (GeodeType::OmniGeode, 9)],
+            &IndexSet::new(),
+            &GeodeGoal::Item {
+                item: items::FROZEN_TEAR,
+                within: 9,
+            },
+            10,
+        )
+        .unwrap();
+        assert_eq!(plan.goal_items, 1);
+        assert_eq!(plan.steps.len(), 9);
+        assert!(plan.steps[..8]
+            .iter()
+            .all(|step| step.ty == GeodeType::OmniGeode));
+        assert_eq!(plan.steps[8].ty, GeodeType::FrozenGeode);
+        assert_eq!(plan.steps[8].reward, DropReward::new(items::FROZEN_TEAR, 1));
+        assert_eq!(plan.steps[7].reward, DropReward::new(items::IRIDIUM_ORE, 2));
+    }
+
+    #[test]
+    fn stops_before_losing_value() {
+        let mut optimizer = Optimizer {
+            types: vec![GeodeType::Geode],
+            predictions: vec![vec![
+                prediction(item_id!("(O)80"), 100, None),
+                prediction(item_id!("(O)390"), -50, None),
+                prediction(item_id!("(O)86"), 20, None),
+            ]],
+            max_cracks: 3,
+            total: 3,
+            memo: HashMap::new(),
+        };
+        let plan = optimizer.plan(vec![3]);
+        assert_eq!(plan.steps.len(), 1);
+        assert_eq!(plan.value, 100);
+    }
+}
@@ -1 +1,5 @@
+pub mod conditions;
+pub mod crops;
+pub mod friendship;
+pub mod geodes;
 pub mod perfection;
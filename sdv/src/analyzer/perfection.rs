@@ -49,7 +49,7 @@ fn get_all_items(save: &SaveGame, include_map_items: bool) -> Vec<Item> {
         });
     }
 
-    for (name, location) in &save.locations {
+    for (name, location) in save.all_locations() {
         for (pos, object) in &location.objects {
             if let Some(chest_items) = &object.items {
                 for item in chest_items {
@@ -179,7 +179,7 @@ pub struct XnaPoint {
 }
 
 // This should, perhaps, be moved to `xnb-rs`.
-#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Serialize, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Serialize, PartialEq)]
 #[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
 #[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
 pub struct Point<T> {
@@ -233,6 +233,13 @@ pub struct XnaRectangle {
     height: i32,
 }
 
+impl XnaRectangle {
+    // Logic found in `Rectangle.Contains()`.
+    pub const fn contains(&self, x: i32, y: i32) -> bool {
+        self.x <= x && x < self.x + self.width && self.y <= y && y < self.y + self.height
+    }
+}
+
 enum BorderPhase {
     Top,
     Right,
@@ -470,7 +477,7 @@ impl TryFrom<i32> for DayOfWeek {
     }
 }
 
-#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, EnumString, PartialEq, Serialize)]
 #[repr(u32)]
 pub enum Weather {
     Sun,
@@ -522,6 +529,32 @@ pub enum QuantityModifierMode {
     Maximum,
 }
 
+#[derive(Clone, Debug, Deserialize_repr, PartialEq, Serialize_repr, XnbType)]
+#[repr(i32)]
+pub enum PlantableRuleContext {
+    Ground = 0,
+    GardenPot,
+    Any,
+}
+
+#[derive(Clone, Debug, Deserialize_repr, PartialEq, Serialize_repr, XnbType)]
+#[repr(i32)]
+pub enum PlantableResult {
+    Allow = 0,
+    Deny,
+    Default,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, XnbType)]
+#[xnb_name("StardewValley.GameData.PlantableRule")]
+pub struct PlantableRule {
+    pub id: String,
+    pub condition: Option<String>,
+    pub planted_in: PlantableRuleContext,
+    pub result: PlantableResult,
+    pub denied_message: Option<String>,
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize, XnbType)]
 #[xnb_name("StardewValley.GameData.GenericSpawnItemData")]
 pub struct GenericSpawnItemData {
@@ -0,0 +1,43 @@
+use indexmap::IndexMap;
+
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+use xnb::{xnb_name, XnbType};
+
+use crate::common::{PlantableRule, Season};
+
+#[derive(Clone, Debug, Deserialize_repr, PartialEq, Serialize_repr, XnbType)]
+#[repr(i32)]
+pub enum HarvestMethod {
+    Grab,
+    Scythe,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, XnbType)]
+#[xnb_name("StardewValley.GameData.Crops.CropData")]
+pub struct CropData {
+    #[serde(skip)]
+    pub id: String,
+    pub seasons: Vec<Season>,
+    pub days_in_phase: Vec<i32>,
+    pub regrow_days: i32,
+    pub is_raised: bool,
+    pub is_paddy_crop: bool,
+    pub needs_watering: bool,
+    pub harvest_item_id: String,
+    pub harvest_min_stack: i32,
+    pub harvest_max_stack: i32,
+    pub harvest_min_quality: i32,
+    pub harvest_max_quality: Option<i32>,
+    pub harvest_max_increase_per_farming_level: f32,
+    pub extra_harvest_chance: f64,
+    pub harvest_method: HarvestMethod,
+    pub plantable_location_rules: Option<Vec<PlantableRule>>,
+    pub tint_colors: Option<Vec<String>>,
+    pub texture: Option<String>,
+    pub sprite_index: i32,
+    pub count_for_monoculture: bool,
+    pub count_for_polyculture: bool,
+    pub custom_fields: Option<IndexMap<String, String>>,
+}
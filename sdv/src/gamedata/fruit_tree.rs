@@ -0,0 +1,31 @@
+use indexmap::IndexMap;
+
+use serde::{Deserialize, Serialize};
+
+use xnb::{xnb_name, XnbType};
+
+use crate::common::{GenericSpawnItemDataWithCondition, PlantableRule, Season};
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, XnbType)]
+#[xnb_name("StardewValley.GameData.FruitTrees.FruitTreeFruitData")]
+pub struct FruitTreeFruitData {
+    #[serde(flatten)]
+    pub parent: GenericSpawnItemDataWithCondition,
+
+    pub season: Option<Season>,
+    pub chance: f32,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, XnbType)]
+#[xnb_name("StardewValley.GameData.FruitTrees.FruitTreeData")]
+pub struct FruitTreeData {
+    #[serde(skip)]
+    pub id: String,
+    pub display_name: Option<String>,
+    pub seasons: Vec<Season>,
+    pub fruit: Vec<FruitTreeFruitData>,
+    pub texture: String,
+    pub texture_sprite_row: i32,
+    pub plantable_location_rules: Option<Vec<PlantableRule>>,
+    pub custom_fields: Option<IndexMap<String, String>>,
+}
@@ -0,0 +1,99 @@
+use anyhow::{anyhow, Context, Result};
+use indexmap::IndexMap;
+use nom::IResult;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufReader, Read},
+    path::Path,
+};
+
+use super::{decimal, field, field_value};
+
+/// Entry from `Data/Furniture`.
+///
+/// Only the fields needed for prediction are parsed.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct FurnitureData {
+    #[serde(skip)]
+    pub id: String,
+    pub name: String,
+    pub ty: String,
+    pub price: i32,
+    pub display_name: Option<String>,
+    pub off_limits_for_random_sale: bool,
+}
+
+impl FurnitureData {
+    pub fn load<P: AsRef<Path>>(file: P) -> Result<IndexMap<String, Self>> {
+        let f = File::open(file).context("Can't open furniture file")?;
+        let mut r = BufReader::new(f);
+        let mut data: Vec<u8> = Vec::new();
+        r.read_to_end(&mut data)?;
+
+        let entries: IndexMap<String, String> = xnb::from_bytes(&data)?;
+        let mut furniture = IndexMap::new();
+        for (k, v) in &entries {
+            let (_, mut entry) =
+                Self::parse(v).map_err(|e| anyhow!("Error parsing furniture \"{}\": {}", v, e))?;
+            entry.id = k.clone();
+            furniture.insert(k.clone(), entry);
+        }
+
+        Ok(furniture)
+    }
+
+    fn parse(i: &str) -> IResult<&str, Self> {
+        let (i, name) = field(i)?;
+        let (i, ty) = field(i)?;
+        let (i, _tilesheet_size) = field(i)?;
+        let (i, _bounding_box_size) = field(i)?;
+        let (i, _rotations) = field(i)?;
+        let (i, price) = field_value(decimal)(i)?;
+        let (i, _placement_restriction) = field(i)?;
+        let (i, display_name) = field(i)?;
+        let (i, _sprite_index) = field(i)?;
+        let (i, _texture) = field(i)?;
+        let (i, off_limits_for_random_sale) = field(i)?;
+
+        Ok((
+            i,
+            Self {
+                id: String::new(),
+                name: name.to_string(),
+                ty: ty.to_string(),
+                price,
+                display_name: (!display_name.is_empty()).then(|| display_name.to_string()),
+                off_limits_for_random_sale: off_limits_for_random_sale.eq_ignore_ascii_case("true"),
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn furniture() {
+        let (_, furniture) = FurnitureData::parse(
+            "Oak Chair/chair/-1/-1/4/350/-1/[LocalizedText Strings\\Furniture:OakChair]",
+        )
+        .unwrap();
+        assert_eq!(furniture.name, "Oak Chair");
+        assert_eq!(furniture.ty, "chair");
+        assert_eq!(furniture.price, 350);
+        assert_eq!(
+            furniture.display_name.as_deref(),
+            Some("[LocalizedText Strings\\Furniture:OakChair]")
+        );
+        assert!(!furniture.off_limits_for_random_sale);
+
+        let (_, furniture) = FurnitureData::parse(
+            "Sun Table/table/2 2/2 1/1/1000/-1/[LocalizedText Strings\\Furniture:SunTable]/1680/TileSheets\\joja_furniture/true",
+        )
+        .unwrap();
+        assert_eq!(furniture.price, 1000);
+        assert!(furniture.off_limits_for_random_sale);
+    }
+}
@@ -53,7 +53,7 @@ pub struct LocationContextData {
     pub allow_rain_totem: bool,
     pub rain_totem_affects_context: Option<String>,
     pub weather_condidtions: Vec<WeatherCondition>,
-    pub copy_weather_from_location: bool,
+    pub copy_weather_from_location: Option<String>,
     pub revive_locations: Vec<ReviveLocation>,
     pub max_pass_out_cost: i32,
     pub pass_out_mail: Option<Vec<PassOutMailData>>,
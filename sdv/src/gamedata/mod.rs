@@ -22,8 +22,11 @@ use xnb::XnbType;
 pub mod big_craftable;
 pub mod bundle;
 pub mod character;
+pub mod crop;
 pub mod festival;
 pub mod fish;
+pub mod fruit_tree;
+pub mod furniture;
 pub mod garbage;
 pub mod locale;
 pub mod location;
@@ -31,15 +34,17 @@ pub mod location_context;
 pub mod npc_gift_tastes;
 pub mod object;
 pub mod recipe;
+pub mod shop;
 // Needs to be updated for Serde
 // pub mod map;
 // pub mod texture;
 
 pub use self::{
-    big_craftable::BigCraftableData, bundle::Bundle, character::CharacterData,
-    festival::PassiveFestivalData, fish::Fish, garbage::GarbageCanData, locale::Locale,
-    location::LocationData, location_context::LocationContextData, npc_gift_tastes::NpcGiftTastes,
-    object::ObjectData, recipe::Recipe,
+    big_craftable::BigCraftableData, bundle::Bundle, character::CharacterData, crop::CropData,
+    festival::PassiveFestivalData, fish::Fish, fruit_tree::FruitTreeData, furniture::FurnitureData,
+    garbage::GarbageCanData, locale::Locale, location::LocationData,
+    location_context::LocationContextData, npc_gift_tastes::NpcGiftTastes, object::ObjectData,
+    recipe::Recipe, shop::ShopData,
 };
 
 use crate::FromJsonReader;
@@ -202,13 +207,17 @@ pub struct GameDataRaw {
     pub characters: IndexMap<String, CharacterData>,
     pub cooking_recipies: IndexMap<String, Recipe>,
     pub crafting_recipies: IndexMap<String, Recipe>,
+    pub crops: IndexMap<String, CropData>,
     pub fish: IndexMap<String, Fish>,
+    pub fruit_trees: IndexMap<String, FruitTreeData>,
+    pub furniture: IndexMap<String, FurnitureData>,
     pub garbage_cans: GarbageCanData,
     pub locations: IndexMap<String, LocationData>,
     pub location_contexts: IndexMap<String, LocationContextData>,
     pub npc_gift_tastes: IndexMap<String, NpcGiftTastes>,
     pub objects: IndexMap<String, ObjectData>,
     pub passive_festivals: IndexMap<String, PassiveFestivalData>,
+    pub shops: IndexMap<String, ShopData>,
 }
 
 impl From<&GameData> for GameDataRaw {
@@ -219,13 +228,17 @@ impl From<&GameData> for GameDataRaw {
             characters: data.characters.clone(),
             cooking_recipies: data.cooking_recipies.clone(),
             crafting_recipies: data.crafting_recipies.clone(),
+            crops: data.crops.clone(),
             fish: data.fish.clone(),
+            fruit_trees: data.fruit_trees.clone(),
+            furniture: data.furniture.clone(),
             garbage_cans: data.garbage_cans.clone(),
             locations: data.locations.clone(),
             location_contexts: data.location_contexts.clone(),
             npc_gift_tastes: data.npc_gift_tastes.clone(),
             objects: data.objects.clone(),
             passive_festivals: data.passive_festivals.clone(),
+            shops: data.shops.clone(),
         }
     }
 }
@@ -237,13 +250,17 @@ pub struct GameData {
     pub characters: IndexMap<String, CharacterData>,
     pub cooking_recipies: IndexMap<String, Recipe>,
     pub crafting_recipies: IndexMap<String, Recipe>,
+    pub crops: IndexMap<String, CropData>,
     pub fish: IndexMap<String, Fish>,
+    pub fruit_trees: IndexMap<String, FruitTreeData>,
+    pub furniture: IndexMap<String, FurnitureData>,
     pub garbage_cans: GarbageCanData,
     pub locations: IndexMap<String, LocationData>,
     pub location_contexts: IndexMap<String, LocationContextData>,
     pub npc_gift_tastes: IndexMap<String, NpcGiftTastes>,
     pub objects: IndexMap<String, ObjectData>,
     pub passive_festivals: IndexMap<String, PassiveFestivalData>,
+    pub shops: IndexMap<String, ShopData>,
     object_name_map: HashMap<String, String>,
     object_id_map: HashMap<ItemId, String>,
     content_dir: Option<PathBuf>,
@@ -264,6 +281,21 @@ impl GameData {
             .iter_mut()
             .for_each(|(id, object)| object.id = id.clone());
 
+        // Populate crop IDs.
+        raw.crops
+            .iter_mut()
+            .for_each(|(id, crop)| crop.id = id.clone());
+
+        // Populate fruit_tree IDs.
+        raw.fruit_trees
+            .iter_mut()
+            .for_each(|(id, tree)| tree.id = id.clone());
+
+        // Populate furniture IDs.
+        raw.furniture
+            .iter_mut()
+            .for_each(|(id, furniture)| furniture.id = id.clone());
+
         // Populate location_context IDs.
         raw.location_contexts
             .iter_mut()
@@ -279,6 +311,11 @@ impl GameData {
             .iter_mut()
             .for_each(|(id, festival)| festival.id = id.clone());
 
+        // Populate shop IDs.
+        raw.shops
+            .iter_mut()
+            .for_each(|(id, shop)| shop.id = id.clone());
+
         // Calculate object_name_map.
         let object_name_map = raw
             .objects
@@ -299,13 +336,17 @@ impl GameData {
             characters: raw.characters,
             cooking_recipies: raw.cooking_recipies,
             crafting_recipies: raw.crafting_recipies,
+            crops: raw.crops,
             fish: raw.fish,
+            fruit_trees: raw.fruit_trees,
+            furniture: raw.furniture,
             garbage_cans: raw.garbage_cans,
             locations: raw.locations,
             location_contexts: raw.location_contexts,
             npc_gift_tastes: raw.npc_gift_tastes,
             passive_festivals: raw.passive_festivals,
             objects: raw.objects,
+            shops: raw.shops,
             object_name_map,
             object_id_map,
             content_dir: None,
@@ -333,10 +374,18 @@ impl GameData {
         crafting_recipies_file.push("CraftingRecipes.xnb");
         let crafting_recipies = Recipe::load_crafting(&crafting_recipies_file)?;
 
+        let crops = load_xnb_object(&game_content_dir, "Data/Crops.xnb")?;
+
         let mut fish_file = data_dir.clone();
         fish_file.push("Fish.xnb");
         let fish = Fish::load(&fish_file)?;
 
+        let mut furniture_file = data_dir.clone();
+        furniture_file.push("Furniture.xnb");
+        let furniture = FurnitureData::load(&furniture_file)?;
+
+        let fruit_trees = load_xnb_object(&game_content_dir, "Data/FruitTrees.xnb")?;
+
         let garbage_cans = load_xnb_object(&game_content_dir, "Data/GarbageCans.xnb")?;
         let locations = load_xnb_object(&game_content_dir, "Data/Locations.xnb")?;
         let location_contexts = load_xnb_object(&game_content_dir, "Data/LocationContexts.xnb")?;
@@ -347,6 +396,7 @@ impl GameData {
         let npc_gift_tastes = NpcGiftTastes::load(&npc_gift_tastes_file)?;
 
         let passive_festivals = load_xnb_object(&game_content_dir, "Data/PassiveFestivals.xnb")?;
+        let shops = load_xnb_object(&game_content_dir, "Data/Shops.xnb")?;
 
         let mut game_data = Self::from_game_data_raw(GameDataRaw {
             big_craftables,
@@ -354,13 +404,17 @@ impl GameData {
             characters,
             cooking_recipies,
             crafting_recipies,
+            crops,
             fish,
+            fruit_trees,
+            furniture,
             garbage_cans,
             locations,
             location_contexts,
             npc_gift_tastes,
             objects,
             passive_festivals,
+            shops,
         });
         game_data.content_dir = Some(game_content_dir.clone());
 
@@ -0,0 +1,116 @@
+use indexmap::IndexMap;
+
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+use xnb::{xnb_name, XnbType};
+
+use crate::common::{
+    GenericSpawnItemDataWithCondition, QuantityModifier, QuantityModifierMode, XnaRectangle,
+};
+
+#[derive(Clone, Debug, Deserialize_repr, PartialEq, Serialize_repr, XnbType)]
+#[repr(i32)]
+pub enum StackSizeVisibility {
+    Hide,
+    Show,
+    ShowIfMultiple,
+}
+
+#[derive(Clone, Debug, Deserialize_repr, Eq, PartialEq, Serialize_repr, XnbType)]
+#[repr(i32)]
+pub enum LimitedStockMode {
+    Global,
+    Player,
+    None,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, XnbType)]
+#[xnb_name("StardewValley.GameData.Shops.ShopDialogueData")]
+pub struct ShopDialogueData {
+    pub id: String,
+    pub condition: Option<String>,
+    pub dialogue: Option<Vec<String>>,
+    pub random_dialogue: Option<Vec<String>>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, XnbType)]
+#[xnb_name("StardewValley.GameData.Shops.ShopOwnerData")]
+pub struct ShopOwnerData {
+    pub id: Option<String>,
+    pub name: String,
+    pub condition: Option<String>,
+    pub portrait: Option<String>,
+    pub dialogues: Option<Vec<ShopDialogueData>>,
+    pub randomize_dialogue_on_open: bool,
+    pub closed_message: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, XnbType)]
+#[xnb_name("StardewValley.GameData.Shops.ShopThemeData")]
+pub struct ShopThemeData {
+    pub condition: Option<String>,
+    pub window_border_texture: Option<String>,
+    pub window_border_source_rect: Option<XnaRectangle>,
+    pub portrait_background_texture: Option<String>,
+    pub portrait_background_source_rect: Option<XnaRectangle>,
+    pub dialogue_background_texture: Option<String>,
+    pub dialogue_background_source_rect: Option<XnaRectangle>,
+    pub dialogue_color: Option<String>,
+    pub dialogue_shadow_color: Option<String>,
+    pub item_row_background_texture: Option<String>,
+    pub item_row_background_source_rect: Option<XnaRectangle>,
+    pub item_row_background_hover_color: Option<String>,
+    pub item_row_text_color: Option<String>,
+    pub item_icon_background_texture: Option<String>,
+    pub item_icon_background_source_rect: Option<XnaRectangle>,
+    pub scroll_up_texture: Option<String>,
+    pub scroll_up_source_rect: Option<XnaRectangle>,
+    pub scroll_down_texture: Option<String>,
+    pub scroll_down_source_rect: Option<XnaRectangle>,
+    pub scroll_bar_front_texture: Option<String>,
+    pub scroll_bar_front_source_rect: Option<XnaRectangle>,
+    pub scroll_bar_back_texture: Option<String>,
+    pub scroll_bar_back_source_rect: Option<XnaRectangle>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, XnbType)]
+#[xnb_name("StardewValley.GameData.Shops.ShopItemData")]
+pub struct ShopItemData {
+    #[serde(flatten)]
+    pub parent: GenericSpawnItemDataWithCondition,
+
+    pub trade_item_id: Option<String>,
+    pub trade_item_amount: i32,
+    pub price: i32,
+    pub apply_profit_margins: Option<bool>,
+    pub available_stock: i32,
+    pub available_stock_limit: LimitedStockMode,
+    pub avoid_repeat: bool,
+    pub use_object_data_price: bool,
+    pub ignore_shop_price_modifiers: bool,
+    pub price_modifiers: Option<Vec<QuantityModifier>>,
+    pub price_modifier_mode: QuantityModifierMode,
+    pub available_stock_modifiers: Option<Vec<QuantityModifier>>,
+    pub available_stock_modifier_mode: QuantityModifierMode,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, XnbType)]
+#[xnb_name("StardewValley.GameData.Shops.ShopData")]
+pub struct ShopData {
+    #[serde(skip)]
+    pub id: String,
+    pub currency: i32,
+    pub stack_size_visibility: Option<StackSizeVisibility>,
+    pub open_sound: Option<String>,
+    pub purchase_sound: Option<String>,
+    pub purchase_repeat_sound: Option<String>,
+    pub apply_profit_margins: Option<bool>,
+    pub price_modifiers: Option<Vec<QuantityModifier>>,
+    pub price_modifier_mode: QuantityModifierMode,
+    pub owners: Option<Vec<ShopOwnerData>>,
+    pub visual_theme: Option<Vec<ShopThemeData>>,
+    pub salable_item_tags: Option<Vec<String>>,
+    pub items: Option<Vec<ShopItemData>>,
+    pub custom_fields: Option<IndexMap<String, String>>,
+}
@@ -2,6 +2,7 @@ pub mod analyzer;
 pub mod common;
 pub mod gamedata;
 pub mod predictor;
+pub mod query;
 pub mod rng;
 pub mod save;
 
@@ -0,0 +1,341 @@
+use std::convert::TryFrom;
+
+use anyhow::{anyhow, Result};
+
+use crate::{
+    common::ItemId,
+    gamedata::location::ArtifactSpotDropData,
+    item_id,
+    rng::{Rng, SeedGenerator},
+    GameData,
+};
+
+use super::{Drop, DropReward, PredictionGameState};
+
+// The game stops handing out lost books once all of them have been found.
+const LOST_BOOK_COUNT: u32 = 21;
+
+#[derive(Clone, Debug)]
+enum ArtifactSpotItem {
+    Item,
+    // `RANDOM_ARTIFACT_FOR_DIG_SPOT`
+    RandomArtifact,
+    // `LOST_BOOK_OR_ITEM <fallback>`
+    LostBookOrItem(Option<ItemId>),
+}
+
+#[derive(Clone, Debug)]
+struct ArtifactSpotDrop {
+    drop: Drop,
+    item: ArtifactSpotItem,
+    chance: f64,
+    continue_on_drop: bool,
+}
+
+impl TryFrom<&ArtifactSpotDropData> for ArtifactSpotDrop {
+    type Error = anyhow::Error;
+
+    fn try_from(data: &ArtifactSpotDropData) -> std::result::Result<Self, Self::Error> {
+        // Item queries with arguments can't be parsed as item IDs.  The query
+        // is split out here and `Drop` is only used for the condition and stack
+        // size.
+        let query = data.parent.parent.item_id.as_deref().unwrap_or_default();
+        let mut args = query.split_whitespace();
+        let item = match args.next() {
+            Some("RANDOM_ARTIFACT_FOR_DIG_SPOT") => ArtifactSpotItem::RandomArtifact,
+            Some("LOST_BOOK_OR_ITEM") => ArtifactSpotItem::LostBookOrItem(
+                args.next().map(|id| id.parse::<ItemId>()).transpose()?,
+            ),
+            _ => ArtifactSpotItem::Item,
+        };
+
+        let mut parent = data.parent.clone();
+        if !matches!(item, ArtifactSpotItem::Item) {
+            parent.parent.item_id = query.split_whitespace().next().map(str::to_string);
+        }
+
+        Ok(Self {
+            drop: Drop::try_from(&parent)?,
+            item,
+            chance: data.chance,
+            continue_on_drop: data.continue_on_drop,
+        })
+    }
+}
+
+/// Cached artifact spot data for a single location.
+#[derive(Clone, Debug)]
+pub struct ArtifactSpot {
+    drops: Vec<ArtifactSpotDrop>,
+    // Objects with an artifact spot chance for this location in data order.
+    artifacts: Vec<(ItemId, f64)>,
+}
+
+impl ArtifactSpot {
+    pub fn new(location: &str, game_data: &GameData) -> Result<Self> {
+        let default = game_data
+            .locations
+            .get("Default")
+            .ok_or_else(|| anyhow!("Default location data not found"))?;
+        let data = game_data
+            .locations
+            .get(location)
+            .ok_or_else(|| anyhow!("location {} not found", location))?;
+
+        // Logic found in `GameLocation.digUpArtifactSpot()`.  The sort is
+        // stable so drops with the same precedence keep their data order.
+        let mut drops: Vec<&ArtifactSpotDropData> = default
+            .artifact_spots
+            .iter()
+            .chain(data.artifact_spots.iter())
+            .flatten()
+            .collect();
+        drops.sort_by_key(|drop| drop.precedence);
+        let drops = drops
+            .into_iter()
+            .map(ArtifactSpotDrop::try_from)
+            .collect::<Result<Vec<_>>>()?;
+
+        let artifacts = game_data
+            .objects
+            .iter()
+            .filter_map(|(id, object)| {
+                let chance = object.artifact_spot_chances.as_ref()?.get(location)?;
+                Some((id, *chance as f64))
+            })
+            .map(|(id, chance)| Ok((format!("(O){id}").parse::<ItemId>()?, chance)))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { drops, artifacts })
+    }
+
+    fn resolve_item<G: SeedGenerator>(
+        &self,
+        item: &ArtifactSpotItem,
+        drop: &Drop,
+        state: &PredictionGameState,
+        rng: &mut Rng,
+    ) -> Result<Option<DropReward>> {
+        let item = match item {
+            ArtifactSpotItem::Item => return drop.try_resolve::<G>(state, rng).map(Some),
+            // Logic found in `ItemQueryResolver.RANDOM_ARTIFACT_FOR_DIG_SPOT()`.
+            ArtifactSpotItem::RandomArtifact => {
+                let Some((item, _)) = self
+                    .artifacts
+                    .iter()
+                    .find(|(_, chance)| rng.next_weighted_bool(*chance))
+                else {
+                    return Ok(None);
+                };
+                item.clone()
+            }
+            ArtifactSpotItem::LostBookOrItem(fallback) => {
+                if state.lost_books_found < LOST_BOOK_COUNT {
+                    item_id!("(O)102")
+                } else {
+                    let Some(fallback) = fallback else {
+                        return Ok(None);
+                    };
+                    fallback.clone()
+                }
+            }
+        };
+        drop.try_resolve_item::<G>(item, state, rng).map(Some)
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ArtifactSpotPrediction {
+    pub drops: Vec<DropReward>,
+
+    /// Set when a drop was found whose random number generator pulls are not
+    /// modeled.  Any further drops can not be predicted.
+    pub truncated: bool,
+}
+
+/// Predict the items dug up from the artifact spot at (`x`, `y`).
+///
+/// The generous enchantment is not taken into account.
+pub fn predict_artifact_spot<G: SeedGenerator>(
+    spot: &ArtifactSpot,
+    state: &PredictionGameState,
+    x: i32,
+    y: i32,
+) -> Result<ArtifactSpotPrediction> {
+    // Logic found in `GameLocation.digUpArtifactSpot()`.
+    let mut rng = state.create_day_save_random::<G>((x * 2000) as f64, y as f64, 0.0);
+    let mut prediction = ArtifactSpotPrediction::default();
+
+    if state.has_seen_qi_plane && rng.next_double() < 0.05 + state.daily_luck / 2.0 {
+        prediction.drops.push(DropReward::new(
+            item_id!("(O)MysteryBox"),
+            rng.next_range(1, 3)? as u32,
+        ));
+    }
+
+    // Logic found in `Utility.trySpawnRareObject()` with a chance modifier
+    // of 9.
+    let chance_modifier = 9.0;
+    if state.has_farming_mastery
+        && rng.next_double() < 0.001 * chance_modifier * (1.0 + state.daily_luck)
+    {
+        prediction
+            .drops
+            .push(DropReward::new(item_id!("(O)GoldenMysteryBox"), 1));
+    }
+    if state.days_played > 2 && rng.next_double() < 0.002 * chance_modifier {
+        // Cosmetic items are picked with a variable number of rng pulls.
+        prediction.truncated = true;
+        return Ok(prediction);
+    }
+    if state.days_played > 2 && rng.next_double() < 0.0006 * chance_modifier {
+        let book = format!("(O)SkillBook_{}", rng.next_max(5)).parse::<ItemId>()?;
+        prediction.drops.push(DropReward::new(book, 1));
+    }
+
+    for drop in &spot.drops {
+        // The order of these conditionals is important as they contain rng
+        // pulls.
+        if !rng.next_weighted_bool(drop.chance)
+            || !drop
+                .drop
+                .evaluate_condition::<G, _>(state, Some(&mut rng))?
+                .passes_with_luck(state.daily_luck)
+        {
+            continue;
+        }
+
+        if let Some(reward) = spot.resolve_item::<G>(&drop.item, &drop.drop, state, &mut rng)? {
+            prediction.drops.push(reward);
+        }
+
+        if !drop.continue_on_drop {
+            break;
+        }
+    }
+
+    Ok(prediction)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{predictor::DropItems, rng::HashedSeedGenerator};
+
+    use super::*;
+
+    fn lost_book_spot() -> ArtifactSpot {
+        ArtifactSpot {
+            drops: vec![ArtifactSpotDrop {
+                drop: Drop {
+                    condition: None,
+                    min_stack: -1,
+                    max_stack: -1,
+                    drop: DropItems::Item(item_id!("LOST_BOOK_OR_ITEM")),
+                },
+                item: ArtifactSpotItem::LostBookOrItem(Some(item_id!("(O)770"))),
+                chance: 1.0,
+                continue_on_drop: false,
+            }],
+            artifacts: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn lost_books_until_all_are_found() {
+        let spot = lost_book_spot();
+        let state = PredictionGameState {
+            game_id: 254546202,
+            days_played: 1,
+            ..Default::default()
+        };
+        let prediction =
+            predict_artifact_spot::<HashedSeedGenerator>(&spot, &state, 10, 20).unwrap();
+        assert_eq!(
+            prediction.drops,
+            vec![DropReward::new(item_id!("(O)102"), 1)]
+        );
+        assert!(!prediction.truncated);
+
+        let state = PredictionGameState {
+            lost_books_found: LOST_BOOK_COUNT,
+            ..state
+        };
+        let prediction =
+            predict_artifact_spot::<HashedSeedGenerator>(&spot, &state, 10, 20).unwrap();
+        assert_eq!(
+            prediction.drops,
+            vec![DropReward::new(item_id!("(O)770"), 1)]
+        );
+    }
+
+    fn clay_and_stone_spot() -> ArtifactSpot {
+        let drop = |item, max_stack, chance, continue_on_drop| ArtifactSpotDrop {
+            drop: Drop {
+                condition: None,
+                min_stack: 1,
+                max_stack,
+                drop: DropItems::Item(item),
+            },
+            item: ArtifactSpotItem::Item,
+            chance,
+            continue_on_drop,
+        };
+        ArtifactSpot {
+            drops: vec![
+                drop(item_id!("(O)330"), 3, 0.5, true),
+                drop(item_id!("(O)390"), 1, 0.75, false),
+            ],
+            artifacts: Vec::new(),
+        }
+    }
+
+    // Expected values were computed with a separate Python port of the
+    // game's seeding, `System.Random` and `GameLocation.digUpArtifactSpot()`.
+    #[test]
+    fn artifact_spots_are_predicted() {
+        let spot = clay_and_stone_spot();
+        let state = PredictionGameState {
+            game_id: 254546202,
+            days_played: 10,
+            has_seen_qi_plane: true,
+            ..Default::default()
+        };
+        let dig = |state: &PredictionGameState, x, y| {
+            predict_artifact_spot::<HashedSeedGenerator>(&spot, state, x, y).unwrap()
+        };
+
+        assert_eq!(
+            dig(&state, 3, 0).drops,
+            vec![
+                DropReward::new(item_id!("(O)MysteryBox"), 2),
+                DropReward::new(item_id!("(O)330"), 1),
+                DropReward::new(item_id!("(O)390"), 1),
+            ]
+        );
+        assert_eq!(
+            dig(&state, 0, 0).drops,
+            vec![
+                DropReward::new(item_id!("(O)330"), 3),
+                DropReward::new(item_id!("(O)390"), 1),
+            ]
+        );
+        assert_eq!(
+            dig(&state, 0, 1).drops,
+            vec![DropReward::new(item_id!("(O)390"), 1)]
+        );
+        assert_eq!(
+            dig(&state, 5, 9),
+            ArtifactSpotPrediction {
+                drops: Vec::new(),
+                truncated: true,
+            }
+        );
+
+        // Without the mystery box roll every later pull shifts.
+        let state = PredictionGameState {
+            has_seen_qi_plane: false,
+            ..state
+        };
+        assert_eq!(dig(&state, 3, 0).drops, vec![]);
+    }
+}
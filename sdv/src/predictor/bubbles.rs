@@ -3,14 +3,42 @@ use log::debug;
 use xnb::xtile::Map;
 
 use crate::{
-    common::{Point, Rect, TimeSpan},
+    common::{ObjectCategory, Point, Rect, TimeSpan},
     rng::{Rng, SeedGenerator},
+    save::{Location, SaveGame},
+    GameData,
 };
 
+use super::{
+    fishing::{predict_fishing, Bait, FishChance, Fishing, FishingState},
+    PredictionGameState,
+};
+
+// Logic found in `GameLocation.performTenMinuteUpdate()`.
+//
+// TODO: Verify the frenzy chance and whether other conditions apply.
+const FRENZY_CHANCE: f64 = 0.1;
+
 #[derive(Clone, Debug)]
 pub struct Bubbles {
     pub location: Point<i32>,
     pub span: TimeSpan,
+
+    /// Distance from the bubbles to land.  Used as the water depth when
+    /// fishing in them.
+    pub distance_to_land: i32,
+
+    /// Set when the bubbles are a fish frenzy.  The game only starts the
+    /// frenzy if the fish it picks is a non-legendary fish.  `span` assumes
+    /// that it does.
+    pub frenzy: bool,
+}
+
+/// Bubbles predicted for a named location.
+#[derive(Clone, Debug)]
+pub struct LocationBubbles {
+    pub location: String,
+    pub bubbles: Bubbles,
 }
 
 fn time_to_minutes(time: i32) -> i32 {
@@ -32,21 +60,17 @@ fn is_open_water(map: &Map, x: i32, y: i32) -> bool {
         return false;
     }
 
-    let Some(layer) = map.get_layer("Buildings") else {
-        return true;
-    };
-
-    let Some(tile) = layer.get_tile(x, y) else {
-        return true;
-    };
-
-    let Some((index, sheet)) = tile.sheet_and_index() else {
-        return true;
-    };
-
-    sheet == "outdoors" && [628, 629, 734, 759].contains(&index)
+    let building = map
+        .get_layer("Buildings")
+        .and_then(|layer| layer.get_tile(x, y))
+        .and_then(|tile| tile.sheet_and_index());
+    if let Some((index, sheet)) = building {
+        if !(sheet == "outdoors" && [628, 629, 734, 759].contains(&index)) {
+            return false;
+        }
+    }
 
-    // At this point the game checks for objects existing (like crab pots?)
+    true
 }
 
 fn pt_in_bounds(map: &Map, x: i32, y: i32) -> bool {
@@ -76,16 +100,52 @@ fn distance_to_land(map: &Map, x: i32, y: i32) -> i32 {
     }
 }
 
+/// Predict the bubbles in `map` over the course of a day.
+///
+/// `location` is the save's state for the map's location.  When given, tiles
+/// occupied by objects are taken into account.
 pub fn calculate_bubbles<G: SeedGenerator>(
     map: &Map,
+    location: Option<&Location>,
     days_played: u32,
     game_id: u32,
+) -> Result<Vec<Bubbles>> {
+    let map_size = &map.layers[0].size;
+    simulate_bubbles::<G>(
+        (map_size.w, map_size.h),
+        location,
+        days_played,
+        game_id,
+        |p| {
+            if !is_open_water(map, p.x, p.y)
+                || map
+                    .get_tile_property(p.x, p.y, "NoFishing", "Back")
+                    .is_some()
+            {
+                return None;
+            }
+            Some(distance_to_land(map, p.x, p.y))
+        },
+    )
+}
+
+// Runs the ten minute updates of a day for a map of `map_size`.
+// `fishable_distance_to_land` returns the distance to land of a tile bubbles
+// can spawn on or `None` if the map doesn't allow them there.
+fn simulate_bubbles<G: SeedGenerator>(
+    map_size: (i32, i32),
+    location: Option<&Location>,
+    days_played: u32,
+    game_id: u32,
+    fishable_distance_to_land: impl Fn(Point<i32>) -> Option<i32>,
 ) -> Result<Vec<Bubbles>> {
     let mut fish_splash_point_time = 0;
     let mut fish_splash_point: Option<Point<i32>> = None;
+    let mut fish_splash_point_distance_to_land = 0;
+    let mut frenzy = false;
     let mut bubbles = Vec::new();
 
-    let map_size = &map.layers[0].size;
+    let (width, height) = map_size;
 
     for time_of_day in (610..2600).step_by(10) {
         if time_of_day % 100 >= 60 {
@@ -96,48 +156,43 @@ pub fn calculate_bubbles<G: SeedGenerator>(
             days_played,
             game_id,
             time_of_day as f64,
-            map_size.w as f64,
+            width as f64,
             0 as f64,
         ));
 
         let splash_point_druation_so_far =
             minutes_between_times(fish_splash_point_time, time_of_day);
-        // frenzy = fishFrenzyFish.Value != null && !fishFrenzyFish.Value.Equals("");
         debug!("{time_of_day}");
         if fish_splash_point.is_none() && r.next_bool() {
             for _tries in 0..2 {
                 let p = Point {
-                    x: r.next_range(0, map_size.w)?,
-                    y: r.next_range(0, map_size.h)?,
+                    x: r.next_range(0, width)?,
+                    y: r.next_range(0, height)?,
                 };
-                debug!(
-                    "{p:?} {} {:?}",
-                    is_open_water(map, p.x, p.y),
-                    map.get_tile_property(p.x, p.y, "NoFishing", "Back")
-                );
-                if !is_open_water(map, p.x, p.y)
-                    || map
-                        .get_tile_property(p.x, p.y, "NoFishing", "Back")
-                        .is_some()
-                {
+
+                // Objects placed in the water, like crab pots, block bubbles.
+                if location.is_some_and(|location| location.objects.contains_key(&p)) {
                     continue;
                 }
-
-                let to_land = distance_to_land(map, p.x, p.y);
-                debug!(" to land {to_land}");
+                let Some(to_land) = fishable_distance_to_land(p) else {
+                    continue;
+                };
+                debug!("{p:?} to land {to_land}");
                 if to_land <= 1 || to_land >= 5 {
                     continue;
                 }
 
-                // TODO frienzy calc
-                let _ = r.next_double();
+                // The frenzy fish itself is picked with the unseeded
+                // `Game1.random`.  See `predict_frenzy_fish()`.
+                frenzy = r.next_double() < FRENZY_CHANCE;
                 fish_splash_point = Some(p);
                 fish_splash_point_time = time_of_day;
+                fish_splash_point_distance_to_land = to_land;
                 break;
             }
         } else if fish_splash_point.is_some()
             && r.next_double() < 0.1 + ((splash_point_druation_so_far as f32 / 1800f32) as f64)
-            && splash_point_druation_so_far > (if false /* frenzy */ { 120 } else { 60})
+            && splash_point_druation_so_far > (if frenzy { 120 } else { 60 })
         {
             bubbles.push(Bubbles {
                 location: fish_splash_point.expect("garuntted to exist"),
@@ -145,12 +200,195 @@ pub fn calculate_bubbles<G: SeedGenerator>(
                     start: fish_splash_point_time,
                     end: time_of_day,
                 },
+                distance_to_land: fish_splash_point_distance_to_land,
+                frenzy,
             });
 
             fish_splash_point = None;
             fish_splash_point_time = 0;
+            frenzy = false;
         }
     }
 
     Ok(bubbles)
 }
+
+/// Predict the chance of each fish being picked for a frenzy in `bubbles`.
+///
+/// The game picks the fish with a regular fishing roll at the bubbles and
+/// only starts the frenzy if it lands on a non-legendary fish.  The chances
+/// of those fish are returned.  Any remaining chance is the frenzy not
+/// starting.  `fishing_state` describes the player; the bobber tile, water
+/// depth, time and bait are taken from the bubbles.
+pub fn predict_frenzy_fish<G: SeedGenerator>(
+    fishing: &Fishing,
+    game_data: &GameData,
+    state: &PredictionGameState,
+    fishing_state: &FishingState,
+    bubbles: &Bubbles,
+) -> Result<Vec<FishChance>> {
+    let fishing_state = FishingState {
+        bobber_tile: bubbles.location,
+        water_depth: bubbles.distance_to_land,
+        time_of_day: bubbles.span.start,
+        bait: Bait::None,
+        ..fishing_state.clone()
+    };
+    let prediction = predict_fishing::<G>(fishing, state, &fishing_state)?;
+
+    Ok(prediction
+        .fish
+        .into_iter()
+        .filter(|fish| {
+            game_data.get_object_by_id(&fish.item).is_ok_and(|object| {
+                object.category == ObjectCategory::Fish
+                    && !object
+                        .context_tags
+                        .iter()
+                        .flatten()
+                        .any(|tag| tag == "fish_legendary")
+            })
+        })
+        .collect())
+}
+
+/// Predict the bubbles in every fishable location for a day.
+///
+/// Maps are loaded from the game content directory.  Locations whose map
+/// can't be loaded are skipped.  The farm is skipped as bubbles only appear
+/// on the riverland farm.  When `save` is given, tiles occupied by objects
+/// are taken into account.
+///
+/// The result is sorted by start time.
+pub fn predict_bubbles_for_day<G: SeedGenerator>(
+    game_data: &GameData,
+    save: Option<&SaveGame>,
+    days_played: u32,
+    game_id: u32,
+) -> Result<Vec<LocationBubbles>> {
+    let mut schedule = Vec::new();
+    for (name, data) in &game_data.locations {
+        if name == "Default"
+            || name == "Farm"
+            || data.fish.as_ref().is_none_or(|fish| fish.is_empty())
+        {
+            continue;
+        }
+
+        let map_path = data
+            .create_on_load
+            .as_ref()
+            .map(|create| create.map_path.replace('\\', "/"))
+            .unwrap_or_else(|| format!("Maps/{name}"));
+        let map = match game_data.load_map(format!("{map_path}.xnb")) {
+            Ok(map) => map,
+            Err(e) => {
+                debug!("skipping {name}: {e}");
+                continue;
+            }
+        };
+
+        let location = save.and_then(|save| save.locations.get(name));
+        for bubbles in calculate_bubbles::<G>(&map, location, days_played, game_id)? {
+            schedule.push(LocationBubbles {
+                location: name.clone(),
+                bubbles,
+            });
+        }
+    }
+
+    // Stable so bubbles starting at the same time keep location order.
+    schedule.sort_by_key(|entry| entry.bubbles.span.start);
+    Ok(schedule)
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+
+    use crate::{rng::HashedSeedGenerator, save::Object};
+
+    use super::*;
+
+    const GAME_ID: u32 = 254546202;
+
+    // Bubbles in a 10x10 map where every tile is water three tiles from land.
+    fn open_water_bubbles(location: Option<&Location>) -> Vec<(Point<i32>, i32, i32, bool)> {
+        simulate_bubbles::<HashedSeedGenerator>((10, 10), location, 1, GAME_ID, |_| Some(3))
+            .unwrap()
+            .into_iter()
+            .map(|b| (b.location, b.span.start, b.span.end, b.frenzy))
+            .collect()
+    }
+
+    // Expected values were computed with a separate Python port of the
+    // game's seeding, `System.Random` and
+    // `GameLocation.performTenMinuteUpdate()`.
+    #[test]
+    fn frenzies_last_at_least_two_hours() {
+        assert_eq!(
+            open_water_bubbles(None),
+            vec![
+                (Point { x: 7, y: 0 }, 620, 840, true),
+                (Point { x: 1, y: 4 }, 900, 1120, false),
+                (Point { x: 7, y: 6 }, 1130, 1310, false),
+                (Point { x: 5, y: 2 }, 1320, 1530, true),
+                (Point { x: 5, y: 1 }, 1610, 1920, false),
+                (Point { x: 9, y: 1 }, 1930, 2150, false),
+                (Point { x: 5, y: 1 }, 2210, 2330, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn objects_block_bubbles() {
+        let location = Location {
+            name: "Town".to_string(),
+            unique_name: None,
+            bundles: None,
+            objects: IndexMap::from([(Point { x: 7, y: 0 }, Object::default())]),
+            terrain_features: IndexMap::new(),
+            buildings: Vec::new(),
+            animals: IndexMap::new(),
+        };
+        assert_eq!(
+            open_water_bubbles(Some(&location)),
+            vec![
+                (Point { x: 0, y: 0 }, 620, 740, false),
+                (Point { x: 5, y: 1 }, 750, 920, false),
+                (Point { x: 2, y: 3 }, 940, 1120, false),
+                (Point { x: 7, y: 6 }, 1130, 1310, false),
+                (Point { x: 5, y: 2 }, 1320, 1530, true),
+                (Point { x: 5, y: 1 }, 1610, 1920, false),
+                (Point { x: 9, y: 1 }, 1930, 2150, false),
+                (Point { x: 5, y: 1 }, 2210, 2330, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn day_schedule_is_sorted_by_start_time() {
+        let game_data =
+            GameData::from_content_dir(crate::gamedata::get_game_content_path().unwrap()).unwrap();
+        let schedule =
+            predict_bubbles_for_day::<HashedSeedGenerator>(&game_data, None, 1, GAME_ID).unwrap();
+
+        let locations: Vec<&String> = game_data.locations.keys().collect();
+        let location_index = |name: &String| locations.iter().position(|l| *l == name);
+        assert!(schedule
+            .iter()
+            .any(|entry| entry.location != schedule[0].location));
+        for pair in schedule.windows(2) {
+            let (a, b) = (&pair[0], &pair[1]);
+            assert!(
+                a.bubbles.span.start <= b.bubbles.span.start,
+                "{:?} {:?}",
+                a,
+                b
+            );
+            if a.bubbles.span.start == b.bubbles.span.start {
+                assert!(location_index(&a.location) <= location_index(&b.location));
+            }
+        }
+    }
+}
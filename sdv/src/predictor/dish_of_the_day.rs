@@ -0,0 +1,90 @@
+use anyhow::Result;
+
+use crate::{
+    common::ItemId,
+    rng::{Rng, SeedGenerator},
+};
+
+use super::{luck::new_day_random, DropReward, PredictionGameState};
+
+// Dishes the Saloon already sells along with the unused object 217.
+//
+// Logic found in `Utility.IsForbiddenDishOfTheDay()`.
+const FORBIDDEN_DISHES: [i32; 7] = [196, 206, 216, 217, 224, 346, 395];
+
+// Roll the dish of the day from the `Game1.random` created at the start of the
+// day.
+pub(crate) fn roll_dish_of_the_day(rng: &mut Rng) -> Result<DropReward> {
+    // Logic found in `Game1._newDayAfterFade()`.
+    let dish = loop {
+        let dish = rng.next_range(194, 240)?;
+        if !FORBIDDEN_DISHES.contains(&dish) {
+            break dish;
+        }
+    };
+    let max_quantity = if rng.next_double() < 0.08 { 14 } else { 4 };
+    let quantity = rng.next_range(1, max_quantity)?;
+
+    Ok(DropReward::new(
+        format!("(O){dish}").parse::<ItemId>()?,
+        quantity as u32,
+    ))
+}
+
+/// Predict the Saloon's dish of the day on `state.days_played` along with the
+/// number Gus has in stock.
+///
+/// Like the daily luck, the dish is seeded with `state.steps_taken`.  Unlike
+/// the luck it is the first roll of the day so 1.6 saves are supported too.
+pub fn predict_dish_of_the_day<G: SeedGenerator>(
+    state: &PredictionGameState,
+) -> Result<DropReward> {
+    roll_dish_of_the_day(&mut new_day_random::<G>(state))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{item_id, rng::HashedSeedGenerator, save::load_test_save};
+
+    use super::*;
+
+    #[test]
+    fn dish_of_the_day_matches_saves() {
+        for (name, dish, quantity) in [
+            ("ChimkinHill_370550379", item_id!("(O)238"), 1),
+            ("testrain_327653833", item_id!("(O)215"), 3),
+        ] {
+            let save = load_test_save(name);
+            let (state, _) = PredictionGameState::from_save(&save);
+            let (saved_dish, saved_quantity) = save.dish_of_the_day.clone().unwrap();
+            assert_eq!(format!("(O){saved_dish}").parse::<ItemId>().unwrap(), dish);
+            assert_eq!(saved_quantity, quantity as i32);
+            assert_eq!(
+                predict_dish_of_the_day::<HashedSeedGenerator>(&state).unwrap(),
+                DropReward::new(dish, quantity),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn dish_of_the_day_is_never_forbidden() {
+        let mut state = PredictionGameState {
+            game_id: 254546202,
+            ..Default::default()
+        };
+        for days_played in 1..(28 * 4 * 2) {
+            state.days_played = days_played;
+            let dish = predict_dish_of_the_day::<HashedSeedGenerator>(&state).unwrap();
+            assert!(
+                FORBIDDEN_DISHES
+                    .iter()
+                    .all(|id| dish.item != format!("(O){id}").parse::<ItemId>().unwrap()),
+                "{:?}",
+                dish
+            );
+            assert!((1..14).contains(&dish.quantity), "{:?}", dish);
+        }
+    }
+}
@@ -0,0 +1,952 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+
+use crate::{
+    common::{
+        ItemId, ModificationType, Point, QuantityModifier, QuantityModifierMode, Season,
+        XnaRectangle,
+    },
+    gamedata::{fish::Weather, location::SpawnFishData, Fish},
+    generate_seed, item_id,
+    query::GameStateQuery,
+    rng::{Rng, SeedGenerator},
+    GameData,
+};
+
+use super::{DropReward, PredictionGameState};
+
+// Logic found in `FishingRod.baseChanceForTreasure`.
+const BASE_CHANCE_FOR_TREASURE: f64 = 0.15;
+
+/// Bait attached to the fishing rod.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum Bait {
+    #[default]
+    None,
+    Magic,
+    Magnet,
+    /// Specific bait targeting a fish.
+    Targeted(ItemId),
+    Other,
+}
+
+/// Player, rod and bobber state for fishing prediction.
+#[derive(Clone, Debug, Default)]
+pub struct FishingState {
+    pub bobber_tile: Point<i32>,
+    pub player_tile: Point<i32>,
+
+    /// Distance from the bobber to land as calculated by
+    /// `FishingRod.distanceToLand()`.
+    pub water_depth: i32,
+    pub fishing_level: i32,
+    pub luck_level: i32,
+    pub time_of_day: i32,
+    pub is_raining: bool,
+    pub bait: Bait,
+    pub has_curiosity_lure: bool,
+
+    /// The Treasure Hunter tackle is attached.
+    pub has_treasure_hunter: bool,
+    pub is_pirate: bool,
+    pub has_fishing_mastery: bool,
+
+    /// Number of each fish the player has caught.  Used for catch limits.
+    pub fish_caught: HashMap<ItemId, u32>,
+
+    /// The `PreciseFishCaught` stat.
+    pub precise_fish_caught: u32,
+
+    pub knows_wild_bait_recipe: bool,
+    pub has_found_artifacts: bool,
+    pub has_found_neptunes_glaive: bool,
+    pub has_found_broken_trident: bool,
+    pub has_farm_eternal_mail: bool,
+}
+
+#[derive(Clone, Debug)]
+struct FishSpawn {
+    items: Vec<ItemId>,
+    condition: Option<GameStateQuery>,
+    chance_modifiers: Vec<(Option<GameStateQuery>, QuantityModifier)>,
+    data: SpawnFishData,
+}
+
+impl FishSpawn {
+    fn new(data: &SpawnFishData) -> Result<Self> {
+        let parse_condition = |condition: &Option<String>| {
+            condition
+                .as_ref()
+                .map(|condition| GameStateQuery::parse(condition))
+                .transpose()
+        };
+
+        // Item queries with arguments can't be parsed as item IDs so only the
+        // query name is kept.
+        let items = match &data.parent.parent.random_item_id {
+            Some(ids) if !ids.is_empty() => ids.iter().map(String::as_str).collect(),
+            _ => vec![data
+                .parent
+                .parent
+                .item_id
+                .as_deref()
+                .ok_or_else(|| anyhow!("no item id for fish {}", data.parent.parent.id))?],
+        };
+        let items = items
+            .into_iter()
+            .map(|id| id.split_whitespace().next().unwrap_or_default().parse())
+            .collect::<Result<Vec<ItemId>>>()?;
+
+        let chance_modifiers = data
+            .chance_modifiers
+            .iter()
+            .flatten()
+            .map(|modifier| Ok((parse_condition(&modifier.condition)?, modifier.clone())))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            items,
+            condition: parse_condition(&data.parent.condition)?,
+            chance_modifiers,
+            data: data.clone(),
+        })
+    }
+}
+
+/// Cached fishing data for a single location.
+#[derive(Clone, Debug)]
+pub struct Fishing {
+    spawns: Vec<FishSpawn>,
+    fish_areas: Vec<(String, XnaRectangle)>,
+    fish: HashMap<ItemId, Fish>,
+}
+
+impl Fishing {
+    pub fn new(location: &str, game_data: &GameData) -> Result<Self> {
+        let default = game_data
+            .locations
+            .get("Default")
+            .ok_or_else(|| anyhow!("Default location data not found"))?;
+        let data = game_data
+            .locations
+            .get(location)
+            .ok_or_else(|| anyhow!("location {} not found", location))?;
+
+        // Logic found in `GameLocation.GetFishFromLocationData()`.  The game
+        // orders spawns with the same precedence randomly so only the
+        // precedence order is kept here.
+        let mut spawns = default
+            .fish
+            .iter()
+            .chain(data.fish.iter())
+            .flatten()
+            .map(FishSpawn::new)
+            .collect::<Result<Vec<_>>>()?;
+        spawns.sort_by_key(|spawn| spawn.data.precedence);
+
+        let fish_areas = data
+            .fish_areas
+            .iter()
+            .flatten()
+            .filter_map(|(id, area)| Some((id.clone(), area.position?)))
+            .collect();
+
+        let fish = game_data
+            .fish
+            .iter()
+            .filter(|(_, fish)| fish.is_line_fish())
+            .map(|(id, fish)| Ok((format!("(O){id}").parse::<ItemId>()?, fish.clone())))
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        Ok(Self {
+            spawns,
+            fish_areas,
+            fish,
+        })
+    }
+
+    // Logic found in `GameLocation.TryGetFishAreaForTile()`.
+    fn fish_area(&self, tile: &Point<i32>) -> Option<&str> {
+        self.fish_areas
+            .iter()
+            .find(|(_, area)| area.contains(tile.x, tile.y))
+            .map(|(id, _)| id.as_str())
+    }
+}
+
+/// Chance of catching a single item.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FishChance {
+    pub item: ItemId,
+    pub chance: f64,
+
+    /// Set when the chance depends on a condition that could not be
+    /// evaluated.  The condition is assumed to pass.
+    pub uncertain: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FishingPrediction {
+    /// Catchable items sorted by descending chance.
+    pub fish: Vec<FishChance>,
+
+    /// Chance that nothing from the location data is caught and the game
+    /// falls back to trash.
+    pub trash_chance: f64,
+
+    pub treasure_chance: f64,
+    pub golden_treasure_chance: f64,
+}
+
+// Returns the probability of a condition passing and whether it could be
+// evaluated.
+fn condition_chance<G: SeedGenerator>(
+    condition: &Option<GameStateQuery>,
+    state: &PredictionGameState,
+) -> (f64, bool) {
+    let Some(condition) = condition else {
+        return (1.0, true);
+    };
+
+    // Fish conditions are checked against `Game1.random` so they are evaluated
+    // without a seeded rng.
+    match condition.evaluate::<G, _>(state, None) {
+        Ok(result) if result.passes_with_luck(state.daily_luck) => (result.chance, true),
+        Ok(_) => (0.0, true),
+        Err(_) => (1.0, false),
+    }
+}
+
+// Logic found in `Utility.ApplyQuantityModifiers()`.  Random amounts are
+// replaced by their average.
+fn apply_quantity_modifiers<G: SeedGenerator>(
+    value: f32,
+    modifiers: &[(Option<GameStateQuery>, QuantityModifier)],
+    mode: &QuantityModifierMode,
+    state: &PredictionGameState,
+) -> (f32, bool) {
+    let mut certain = true;
+    let mut new_value: Option<f32> = None;
+    for (condition, modifier) in modifiers {
+        let amount = match &modifier.random_amount {
+            Some(amounts) if !amounts.is_empty() => {
+                amounts.iter().sum::<f32>() / amounts.len() as f32
+            }
+            _ => modifier.amount,
+        };
+
+        let (chance, known) = condition_chance::<G>(condition, state);
+        certain &= known;
+        if chance <= 0.0 {
+            continue;
+        }
+
+        // Logic found in `QuantityModifier.Apply()`.
+        let apply = |value: f32| match modifier.modification {
+            ModificationType::Add => value + amount,
+            ModificationType::Subtract => value - amount,
+            ModificationType::Multiply => value * amount,
+            ModificationType::Divide => value / amount,
+            ModificationType::Set => amount,
+        };
+        new_value = Some(match mode {
+            QuantityModifierMode::Minimum => {
+                let applied = apply(value);
+                new_value.map_or(applied, |v| v.min(applied))
+            }
+            QuantityModifierMode::Maximum => {
+                let applied = apply(value);
+                new_value.map_or(applied, |v| v.max(applied))
+            }
+            QuantityModifierMode::Stack => apply(new_value.unwrap_or(value)),
+        });
+    }
+    (new_value.unwrap_or(value), certain)
+}
+
+// Chance for an item to pass `GameLocation.CheckGenericFishRequirements()`.
+fn fish_requirements_chance<G: SeedGenerator>(
+    fish: Option<&Fish>,
+    spawn: &FishSpawn,
+    state: &PredictionGameState,
+    fishing: &FishingState,
+    is_targeted: bool,
+) -> (f64, bool) {
+    let Some(Fish::Line {
+        times,
+        weather,
+        min_depth,
+        spawn_mult,
+        depth_mult,
+        min_level,
+        ..
+    }) = fish
+    else {
+        return (1.0, true);
+    };
+
+    let using_magic_bait = fishing.bait == Bait::Magic;
+    if !spawn.data.ignore_fish_data_requirements {
+        if !using_magic_bait {
+            let in_time = times
+                .iter()
+                .any(|span| fishing.time_of_day >= span.start && fishing.time_of_day < span.end);
+            let in_weather = match weather {
+                Weather::Rainy => fishing.is_raining,
+                Weather::Sunny => !fishing.is_raining,
+                Weather::Both => true,
+            };
+            if !in_time || !in_weather {
+                return (0.0, true);
+            }
+        }
+        if fishing.fishing_level < *min_level {
+            return (0.0, true);
+        }
+    }
+
+    let drop_off_amount = depth_mult * spawn_mult;
+    let mut chance = *spawn_mult;
+    chance -= 0.max(min_depth - fishing.water_depth) as f32 * drop_off_amount;
+    chance += fishing.fishing_level as f32 / 50.0;
+    chance = chance.min(0.9);
+    if (chance as f64) < 0.25 && fishing.has_curiosity_lure {
+        if spawn.data.curiousity_lure_buff > -1.0 {
+            chance += spawn.data.curiousity_lure_buff;
+        } else {
+            let max = 0.25f32;
+            let min = 0.08f32;
+            chance = (max - min) / max * chance + (max - min) / 2.0;
+        }
+    }
+    if is_targeted {
+        chance *= 1.66;
+    }
+    if spawn.data.apply_daily_luck {
+        chance += state.daily_luck as f32;
+    }
+
+    let (chance, certain) = apply_quantity_modifiers::<G>(
+        chance,
+        &spawn.chance_modifiers,
+        &spawn.data.chance_modifier_mode,
+        state,
+    );
+    ((chance as f64).clamp(0.0, 1.0), certain)
+}
+
+// Logic found in `SpawnFishData.GetChance()`.
+fn spawn_chance<G: SeedGenerator>(
+    spawn: &FishSpawn,
+    state: &PredictionGameState,
+    fishing: &FishingState,
+    is_targeted: bool,
+) -> (f32, bool) {
+    let data = &spawn.data;
+    let mut chance = data.chance;
+    if fishing.has_curiosity_lure && data.curiousity_lure_buff > 0.0 {
+        chance += data.curiousity_lure_buff;
+    }
+    if data.apply_daily_luck {
+        chance += state.daily_luck as f32;
+    }
+    let mut certain = true;
+    if !spawn.chance_modifiers.is_empty() {
+        (chance, certain) = apply_quantity_modifiers::<G>(
+            chance,
+            &spawn.chance_modifiers,
+            &data.chance_modifier_mode,
+            state,
+        );
+    }
+    if is_targeted {
+        chance = chance * data.specific_bait_multiplier + data.specific_bait_buff;
+    }
+    (
+        chance + data.chance_boost_per_luck_level * fishing.luck_level as f32,
+        certain,
+    )
+}
+
+// Given independent success chances for items visited in a uniformly random
+// order, returns the chance that each item is the first to succeed.
+//
+// If every item is given a uniformly random position `t` in [0, 1), item `j`
+// comes before item `i` with probability `t`.  So the chance that `i` is
+// first is `p_i * integral(0, 1, product(j != i, 1 - p_j * t) dt)`.
+fn first_success_chances(chances: &[f64]) -> Vec<f64> {
+    (0..chances.len())
+        .map(|i| {
+            let mut poly = vec![1.0];
+            for (j, chance) in chances.iter().enumerate() {
+                if i == j {
+                    continue;
+                }
+                let mut next = vec![0.0; poly.len() + 1];
+                for (k, coefficient) in poly.iter().enumerate() {
+                    next[k] += coefficient;
+                    next[k + 1] -= coefficient * chance;
+                }
+                poly = next;
+            }
+            let integral: f64 = poly
+                .iter()
+                .enumerate()
+                .map(|(k, coefficient)| coefficient / (k + 1) as f64)
+                .sum();
+            chances[i] * integral
+        })
+        .collect()
+}
+
+/// Predict the chance of catching each fish at a location.
+///
+/// The fish bite is rolled with the game's unseeded `Game1.random` so the
+/// result is a probability distribution rather than a single fish.
+///
+/// Magic bait ignores season, time and weather requirements and gives the
+/// spawns a second pass if nothing bites.  Specific bait only boosts the
+/// targeted fish's chances; the game's extra rolls for it aren't modeled.
+pub fn predict_fishing<G: SeedGenerator>(
+    fishing: &Fishing,
+    state: &PredictionGameState,
+    fishing_state: &FishingState,
+) -> Result<FishingPrediction> {
+    // Logic found in `GameLocation.GetFishFromLocationData()`.
+    let season = state.season();
+    let using_magic_bait = fishing_state.bait == Bait::Magic;
+    let fish_area = fishing.fish_area(&fishing_state.bobber_tile);
+    let targeted_fish = match &fishing_state.bait {
+        Bait::Targeted(fish) => Some(fish),
+        _ => None,
+    };
+
+    // Spawns that can be caught along with their chance of succeeding and the
+    // chance of each of their items.
+    let mut candidates: Vec<(i32, f64, Vec<FishChance>)> = Vec::new();
+    for spawn in &fishing.spawns {
+        let data = &spawn.data;
+        let bobber = &fishing_state.bobber_tile;
+        let player = &fishing_state.player_tile;
+        if data
+            .fish_area_id
+            .as_ref()
+            .is_some_and(|id| Some(id.as_str()) != fish_area)
+            || data
+                .season
+                .as_ref()
+                .is_some_and(|s| !using_magic_bait && *s != season)
+            || data
+                .player_position
+                .is_some_and(|area| !area.contains(player.x, player.y))
+            || data
+                .bobber_position
+                .is_some_and(|area| !area.contains(bobber.x, bobber.y))
+            || fishing_state.fishing_level < data.min_fishing_level
+            || fishing_state.water_depth < data.min_distance_from_shore
+            || (data.max_distance_from_shore > -1
+                && fishing_state.water_depth > data.max_distance_from_shore)
+            || (data.require_magic_bait && !using_magic_bait)
+        {
+            continue;
+        }
+
+        let is_targeted =
+            targeted_fish.is_some_and(|fish| spawn.items.len() == 1 && spawn.items[0] == *fish);
+        let (chance, chance_certain) = spawn_chance::<G>(spawn, state, fishing_state, is_targeted);
+        let chance = if data.use_fish_caught_seeded_random {
+            let mut rng = Rng::new(generate_seed!(
+                G,
+                state.game_id,
+                fishing_state.precise_fish_caught * 859
+            ));
+            if rng.next_weighted_bool(chance as f64) {
+                1.0
+            } else {
+                0.0
+            }
+        } else {
+            (chance as f64).clamp(0.0, 1.0)
+        };
+        let (condition, condition_certain) = condition_chance::<G>(&spawn.condition, state);
+
+        let mut items = Vec::new();
+        for item in &spawn.items {
+            let caught = fishing_state.fish_caught.get(item).copied().unwrap_or(0);
+            if data.catch_limit > -1 && caught as i32 >= data.catch_limit {
+                continue;
+            }
+            let (requirements, requirements_certain) = fish_requirements_chance::<G>(
+                fishing.fish.get(item),
+                spawn,
+                state,
+                fishing_state,
+                targeted_fish == Some(item),
+            );
+            items.push(FishChance {
+                item: item.clone(),
+                chance: chance * condition * requirements / spawn.items.len() as f64,
+                uncertain: !(chance_certain && condition_certain && requirements_certain),
+            });
+        }
+
+        let success: f64 = items.iter().map(|item| item.chance).sum();
+        if success > 0.0 {
+            candidates.push((data.precedence, success, items));
+        }
+    }
+
+    // Spawns are visited in precedence order with ties in a random order.
+    let mut fish: Vec<FishChance> = Vec::new();
+    let mut miss_chance = 1.0;
+    for group in candidates.chunk_by(|a, b| a.0 == b.0) {
+        let successes: Vec<f64> = group.iter().map(|(_, success, _)| *success).collect();
+        let firsts = first_success_chances(&successes);
+        for ((_, success, items), first) in group.iter().zip(firsts) {
+            for item in items {
+                let chance = miss_chance * first * item.chance / success;
+                match fish.iter_mut().find(|f| f.item == item.item) {
+                    Some(f) => {
+                        f.chance += chance;
+                        f.uncertain |= item.uncertain;
+                    }
+                    None => fish.push(FishChance {
+                        chance,
+                        ..item.clone()
+                    }),
+                }
+            }
+        }
+        miss_chance *= successes
+            .iter()
+            .map(|success| 1.0 - success)
+            .product::<f64>();
+    }
+
+    // Magic bait gets a second pass through the spawns if nothing was caught.
+    if using_magic_bait {
+        for f in &mut fish {
+            f.chance *= 1.0 + miss_chance;
+        }
+        miss_chance *= miss_chance;
+    }
+
+    // Spawns after one that always succeeds can never be reached.
+    fish.retain(|f| f.chance > 0.0);
+    fish.sort_by(|a, b| b.chance.total_cmp(&a.chance));
+
+    let treasure_chance = treasure_chance(state, fishing_state);
+    let golden_treasure_chance = if fishing_state.has_fishing_mastery {
+        treasure_chance * (0.25 + state.daily_luck).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    Ok(FishingPrediction {
+        fish,
+        trash_chance: miss_chance,
+        treasure_chance,
+        golden_treasure_chance,
+    })
+}
+
+/// Chance that a treasure chest appears while reeling in a fish.
+pub fn treasure_chance(state: &PredictionGameState, fishing: &FishingState) -> f64 {
+    // Logic found in `FishingRod.startMinigameEndFunction()`.
+    if fishing.fish_caught.len() <= 1 {
+        return 0.0;
+    }
+
+    let mut chance =
+        BASE_CHANCE_FOR_TREASURE + fishing.luck_level as f64 * 0.005 + state.daily_luck / 2.0;
+    if fishing.bait == Bait::Magnet {
+        chance += BASE_CHANCE_FOR_TREASURE;
+    }
+    if fishing.has_treasure_hunter {
+        chance += BASE_CHANCE_FOR_TREASURE / 3.0;
+    }
+    if fishing.is_pirate {
+        chance += BASE_CHANCE_FOR_TREASURE;
+    }
+    chance.clamp(0.0, 1.0)
+}
+
+fn object(id: i32, quantity: i32) -> Result<DropReward> {
+    Ok(DropReward::new(
+        format!("(O){id}").parse::<ItemId>()?,
+        quantity as u32,
+    ))
+}
+
+fn double_last(treasures: &mut [DropReward]) {
+    if let Some(last) = treasures.last_mut() {
+        last.quantity *= 2;
+    }
+}
+
+/// Roll the contents of a fishing treasure chest.
+///
+/// The game rolls with the unseeded `Game1.random` so this is useful for
+/// sampling the distribution of contents.
+///
+/// TODO: Golden treasure chests and items added in 1.6 are not modeled.
+pub fn roll_treasure_chest(
+    rng: &mut Rng,
+    state: &PredictionGameState,
+    fishing: &FishingState,
+    is_beach: bool,
+) -> Result<Vec<DropReward>> {
+    // Logic found in `FishingRod.openTreasureMenuEndFunction()`.
+    let distance = fishing.water_depth;
+    let luck_level = fishing.luck_level as f64;
+    let mut treasures: Vec<DropReward> = Vec::new();
+    let mut chance = 1.0f32;
+    while rng.next_double() <= chance as f64 {
+        chance *= 0.4;
+        if state.season() == Season::Spring && !is_beach && rng.next_double() < 0.1 {
+            let quantity = rng.next_range(2, 6)? + if rng.next_double() < 0.25 { 5 } else { 0 };
+            treasures.push(object(273, quantity)?);
+        }
+
+        match rng.next_max(4) {
+            0 => {
+                if distance >= 5 && rng.next_double() < 0.03 {
+                    treasures.push(object(386, rng.next_range(1, 3)?)?);
+                } else {
+                    let mut possibles = Vec::new();
+                    if distance >= 4 {
+                        possibles.push(384);
+                    }
+                    for (id, min_distance) in [(380, 3), (378, 0), (388, 0), (390, 0)] {
+                        if distance >= min_distance
+                            && (possibles.is_empty() || rng.next_double() < 0.6)
+                        {
+                            possibles.push(id);
+                        }
+                    }
+                    possibles.push(382);
+                    let id = *rng.chooose_from(&possibles);
+                    let quantity = rng.next_range(2, 7)?
+                        * if rng.next_double() < 0.05 + luck_level * 0.015 {
+                            2
+                        } else {
+                            1
+                        };
+                    treasures.push(object(id, quantity)?);
+                    if rng.next_double() < 0.05 + luck_level * 0.03 {
+                        double_last(&mut treasures);
+                    }
+                }
+            }
+            1 => {
+                if distance >= 4 && rng.next_double() < 0.1 && fishing.fishing_level >= 6 {
+                    treasures.push(object(687, 1)?);
+                } else if rng.next_double() < 0.25 && fishing.knows_wild_bait_recipe {
+                    let quantity = 5 + if rng.next_double() < 0.25 { 5 } else { 0 };
+                    treasures.push(object(774, quantity)?);
+                } else if fishing.fishing_level >= 6 {
+                    treasures.push(object(685, 1)?);
+                } else {
+                    treasures.push(object(685, 10)?);
+                }
+            }
+            2 => {
+                if rng.next_double() < 0.1 && (1..21).contains(&state.lost_books_found) {
+                    treasures.push(object(102, 1)?);
+                } else if fishing.has_found_artifacts {
+                    if rng.next_double() < 0.25 && fishing.fishing_level > 1 {
+                        treasures.push(object(rng.next_range(585, 588)?, 1)?);
+                    } else if rng.next_double() < 0.5 && fishing.fishing_level > 1 {
+                        treasures.push(object(rng.next_range(103, 120)?, 1)?);
+                    } else {
+                        treasures.push(object(535, 1)?);
+                    }
+                } else {
+                    treasures.push(object(382, rng.next_range(1, 3)?)?);
+                }
+            }
+            _ => roll_treasure_loot(rng, state, fishing, &mut treasures)?,
+        }
+    }
+
+    if treasures.is_empty() {
+        treasures.push(object(685, rng.next_range(1, 4)? * 5)?);
+    }
+
+    Ok(treasures)
+}
+
+fn roll_treasure_loot(
+    rng: &mut Rng,
+    state: &PredictionGameState,
+    fishing: &FishingState,
+    treasures: &mut Vec<DropReward>,
+) -> Result<()> {
+    let distance = fishing.water_depth;
+    let luck_level = fishing.luck_level as f64;
+    match rng.next_max(3) {
+        0 => {
+            let id = if distance >= 4 {
+                537 + if rng.next_double() < 0.4 {
+                    rng.next_range(-2, 0)?
+                } else {
+                    0
+                }
+            } else if distance >= 3 {
+                536 + if rng.next_double() < 0.4 { -1 } else { 0 }
+            } else {
+                535
+            };
+            treasures.push(object(id, rng.next_range(1, 4)?)?);
+            if rng.next_double() < 0.05 + luck_level * 0.03 {
+                double_last(treasures);
+            }
+        }
+        1 => {
+            if fishing.fishing_level < 2 {
+                treasures.push(object(382, rng.next_range(1, 4)?)?);
+                return Ok(());
+            }
+            let (rare, common_a, common_b) = match distance {
+                4.. => (82, 64, 60),
+                3 => (84, 70, 62),
+                _ => (86, 66, 68),
+            };
+            let id = if rng.next_double() < 0.3 {
+                rare
+            } else if rng.next_double() < 0.5 {
+                common_a
+            } else {
+                common_b
+            };
+            treasures.push(object(id, rng.next_range(1, 3)?)?);
+            if rng.next_double() < 0.028 * (distance as f32 / 5.0) as f64 {
+                treasures.push(object(72, 1)?);
+            }
+            if rng.next_double() < 0.05 {
+                double_last(treasures);
+            }
+        }
+        _ => {
+            if fishing.fishing_level < 2 {
+                treasures.push(object(770, rng.next_range(1, 4)?)?);
+                return Ok(());
+            }
+            let luck_modifier = ((1.0 + state.daily_luck as f32) * (distance as f32 / 5.0)) as f64;
+            if rng.next_double() < 0.05 * luck_modifier && !fishing.has_found_neptunes_glaive {
+                treasures.push(DropReward::new(item_id!("(W)14"), 1));
+            }
+            if rng.next_double() < 0.05 * luck_modifier && !fishing.has_found_broken_trident {
+                treasures.push(DropReward::new(item_id!("(W)51"), 1));
+            }
+            if rng.next_double() < 0.07 * luck_modifier {
+                let upgrade = |rng: &mut Rng| rng.next_double() < (luck_level as f32 / 11.0) as f64;
+                let id = match rng.next_max(3) {
+                    0 => 516 + upgrade(rng) as i32,
+                    1 => 518 + upgrade(rng) as i32,
+                    _ => rng.next_range(529, 535)?,
+                };
+                treasures.push(object(id, 1)?);
+            }
+            if rng.next_double() < 0.02 * luck_modifier {
+                treasures.push(object(166, 1)?);
+            }
+            if fishing.fishing_level > 5 && rng.next_double() < 0.001 * luck_modifier {
+                treasures.push(object(74, 1)?);
+            }
+            if rng.next_double() < 0.01 * luck_modifier {
+                treasures.push(object(127, 1)?);
+            }
+            if rng.next_double() < 0.01 * luck_modifier {
+                treasures.push(object(126, 1)?);
+            }
+            if rng.next_double() < 0.01 * luck_modifier {
+                treasures.push(object(527, 1)?);
+            }
+            if rng.next_double() < 0.01 * luck_modifier {
+                let boots = format!("(B){}", rng.next_range(504, 514)?).parse::<ItemId>()?;
+                treasures.push(DropReward::new(boots, 1));
+            }
+            if fishing.has_farm_eternal_mail && rng.next_double() < 0.01 * luck_modifier {
+                treasures.push(object(928, 1)?);
+            }
+            if treasures.len() == 1 {
+                treasures.push(object(72, 1)?);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::{GenericSpawnItemData, GenericSpawnItemDataWithCondition},
+        rng::HashedSeedGenerator,
+    };
+
+    use super::*;
+
+    fn spawn(id: &str, chance: f32, precedence: i32) -> SpawnFishData {
+        SpawnFishData {
+            parent: GenericSpawnItemDataWithCondition {
+                parent: GenericSpawnItemData {
+                    id: id.to_string(),
+                    item_id: Some(id.to_string()),
+                    random_item_id: None,
+                    max_items: None,
+                    min_stack: -1,
+                    max_stack: -1,
+                    quality: -1,
+                    internal_name: None,
+                    display_name: None,
+                    tool_upgrade_level: -1,
+                    is_recipe: false,
+                    stack_modifiers: None,
+                    stack_modifier_mode: QuantityModifierMode::Stack,
+                    quality_modifiers: None,
+                    quality_modifier_mode: QuantityModifierMode::Stack,
+                    mod_data: None,
+                    per_item_condition: None,
+                },
+                condition: None,
+            },
+            chance,
+            season: None,
+            fish_area_id: None,
+            bobber_position: None,
+            player_position: None,
+            min_fishing_level: 0,
+            min_distance_from_shore: 0,
+            max_distance_from_shore: -1,
+            apply_daily_luck: false,
+            curiousity_lure_buff: 0.0,
+            specific_bait_buff: 0.0,
+            specific_bait_multiplier: 1.66,
+            catch_limit: -1,
+            can_use_training_rod: None,
+            is_boss_fish: false,
+            set_flag_on_catch: None,
+            require_magic_bait: false,
+            precedence,
+            ignore_fish_data_requirements: false,
+            can_be_inherited: true,
+            chance_modifiers: None,
+            chance_modifier_mode: QuantityModifierMode::Stack,
+            chance_boost_per_luck_level: 0.0,
+            use_fish_caught_seeded_random: false,
+        }
+    }
+
+    fn fishing(spawns: &[SpawnFishData]) -> Fishing {
+        Fishing {
+            spawns: spawns
+                .iter()
+                .map(|data| FishSpawn::new(data).unwrap())
+                .collect(),
+            fish_areas: Vec::new(),
+            fish: HashMap::new(),
+        }
+    }
+
+    #[track_caller]
+    fn assert_fish(prediction: &FishingPrediction, expected: &[(&str, f64)], trash: f64) {
+        let fish: Vec<(String, f64)> = prediction
+            .fish
+            .iter()
+            .map(|fish| (fish.item.to_string(), fish.chance))
+            .collect();
+        assert_eq!(fish.len(), expected.len(), "{:?}", fish);
+        for ((item, chance), (expected_item, expected_chance)) in fish.iter().zip(expected) {
+            assert_eq!(item, expected_item);
+            assert!((chance - expected_chance).abs() < 1e-6, "{:?}", fish);
+        }
+        assert!(
+            (prediction.trash_chance - trash).abs() < 1e-6,
+            "{}",
+            prediction.trash_chance
+        );
+    }
+
+    #[test]
+    fn fish_are_predicted_in_precedence_order() {
+        let mut summer_only = spawn("(O)128", 1.0, -10);
+        summer_only.season = Some(Season::Summer);
+        let mut fall_only = spawn("(O)130", 1.0, 0);
+        fall_only.parent.condition = Some("SEASON fall".to_string());
+        let fishing = fishing(&[
+            summer_only,
+            spawn("(O)129", 0.5, 0),
+            fall_only,
+            spawn("(O)131", 0.5, 10),
+        ]);
+        let state = PredictionGameState {
+            days_played: 1,
+            ..Default::default()
+        };
+        let mut fishing_state = FishingState::default();
+
+        // The summer fish is out of season and the fall condition fails.  The
+        // second spawn is only reached when the first one misses.
+        let prediction =
+            predict_fishing::<HashedSeedGenerator>(&fishing, &state, &fishing_state).unwrap();
+        assert_fish(&prediction, &[("(O)129", 0.5), ("(O)131", 0.25)], 0.25);
+
+        // Magic bait ignores the season and takes priority.
+        fishing_state.bait = Bait::Magic;
+        let prediction =
+            predict_fishing::<HashedSeedGenerator>(&fishing, &state, &fishing_state).unwrap();
+        assert_fish(&prediction, &[("(O)128", 1.0)], 0.0);
+    }
+
+    #[test]
+    fn caught_fish_respect_catch_limits() {
+        let mut legendary = spawn("(O)159", 1.0, -10);
+        legendary.catch_limit = 1;
+        let fishing = fishing(&[legendary, spawn("(O)129", 1.0, 0)]);
+        let state = PredictionGameState {
+            days_played: 1,
+            ..Default::default()
+        };
+        let mut fishing_state = FishingState::default();
+        let prediction =
+            predict_fishing::<HashedSeedGenerator>(&fishing, &state, &fishing_state).unwrap();
+        assert_fish(&prediction, &[("(O)159", 1.0)], 0.0);
+
+        fishing_state.fish_caught.insert(item_id!("(O)159"), 1);
+        let prediction =
+            predict_fishing::<HashedSeedGenerator>(&fishing, &state, &fishing_state).unwrap();
+        assert_fish(&prediction, &[("(O)129", 1.0)], 0.0);
+    }
+
+    #[test]
+    fn first_success_in_random_order() {
+        let chances = first_success_chances(&[1.0, 1.0]);
+        assert!((chances[0] - 0.5).abs() < 1e-9, "{:?}", chances);
+        assert!((chances[1] - 0.5).abs() < 1e-9, "{:?}", chances);
+
+        let chances = first_success_chances(&[1.0, 0.5]);
+        assert!((chances[0] - 0.75).abs() < 1e-9, "{:?}", chances);
+        assert!((chances[1] - 0.25).abs() < 1e-9, "{:?}", chances);
+
+        // Whatever the order, something is caught unless every roll fails.
+        let chances = first_success_chances(&[0.3, 0.6, 0.1, 0.8]);
+        let total: f64 = chances.iter().sum();
+        assert!((total - (1.0 - 0.7 * 0.4 * 0.9 * 0.2)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn treasure_needs_two_fish_caught() {
+        let state = PredictionGameState::default();
+        let mut fishing = FishingState::default();
+        assert_eq!(treasure_chance(&state, &fishing), 0.0);
+
+        fishing.fish_caught.insert(item_id!("(O)128"), 1);
+        fishing.fish_caught.insert(item_id!("(O)129"), 1);
+        assert!((treasure_chance(&state, &fishing) - BASE_CHANCE_FOR_TREASURE).abs() < 1e-9);
+
+        fishing.is_pirate = true;
+        assert!((treasure_chance(&state, &fishing) - 0.3).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,316 @@
+use std::{collections::HashSet, convert::TryFrom};
+
+use anyhow::{anyhow, Result};
+use xnb::xtile::Map;
+
+use crate::{
+    common::{ItemId, Point, Season},
+    generate_seed, item_id,
+    rng::{Rng, SeedGenerator},
+    save::Location,
+    GameData,
+};
+
+use super::{Drop, DropReward, PredictionGameState};
+
+// Number of random tiles tried for each spawn.
+const SPAWN_ATTEMPTS: usize = 11;
+
+#[derive(Clone, Debug)]
+struct ForageDrop {
+    drop: Drop,
+    chance: f64,
+    season: Option<Season>,
+}
+
+/// Cached forage data for a single location.
+#[derive(Clone, Debug)]
+pub struct Forage {
+    drops: Vec<ForageDrop>,
+    min_daily_spawn: i32,
+    max_daily_spawn: i32,
+    max_spawned_at_once: i32,
+}
+
+impl Forage {
+    pub fn new(location: &str, game_data: &GameData) -> Result<Self> {
+        let default = game_data
+            .locations
+            .get("Default")
+            .ok_or_else(|| anyhow!("Default location data not found"))?;
+        let data = game_data
+            .locations
+            .get(location)
+            .ok_or_else(|| anyhow!("location {} not found", location))?;
+
+        let drops = default
+            .forage
+            .iter()
+            .chain(data.forage.iter())
+            .map(|forage| {
+                Ok(ForageDrop {
+                    drop: Drop::try_from(&forage.parent)?,
+                    chance: forage.chance,
+                    season: forage.season.clone(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            drops,
+            min_daily_spawn: data.min_daily_forage_spawn,
+            max_daily_spawn: data.max_daily_forage_spawn,
+            max_spawned_at_once: data.max_spawned_forage_at_once,
+        })
+    }
+}
+
+/// Pick a basic forage item for `season`.
+///
+/// Resolves the `RANDOM_BASE_SEASON_ITEM` item query.  `seed_addition` is
+/// pulled from the query's rng.
+pub fn random_base_season_item<G: SeedGenerator>(
+    state: &PredictionGameState,
+    season: Season,
+    seed_addition: i32,
+) -> ItemId {
+    // Logic found in `Utility.getRandomBasicSeasonalForageItem()`.
+    let mut rng = Rng::new(generate_seed!(
+        G,
+        state.game_id,
+        state.days_played,
+        seed_addition
+    ));
+    let items = match season {
+        Season::Spring => [
+            item_id!("(O)16"),
+            item_id!("(O)18"),
+            item_id!("(O)20"),
+            item_id!("(O)22"),
+        ]
+        .as_slice(),
+        Season::Summer => [item_id!("(O)396"), item_id!("(O)398"), item_id!("(O)402")].as_slice(),
+        Season::Fall => [
+            item_id!("(O)404"),
+            item_id!("(O)406"),
+            item_id!("(O)408"),
+            item_id!("(O)410"),
+        ]
+        .as_slice(),
+        Season::Winter => [
+            item_id!("(O)412"),
+            item_id!("(O)414"),
+            item_id!("(O)416"),
+            item_id!("(O)418"),
+        ]
+        .as_slice(),
+    };
+    rng.chooose_from(items).clone()
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ForageSpawn {
+    pub tile: Point<i32>,
+    pub reward: DropReward,
+}
+
+fn has_tile(map: &Map, x: i32, y: i32, layer: &str) -> bool {
+    map.get_layer(layer)
+        .and_then(|layer| layer.get_tile(x, y))
+        .and_then(|tile| tile.sheet_and_index())
+        .is_some()
+}
+
+// Whether forage can be placed at (`x`, `y`) based on the map alone.
+//
+// Logic found in `GameLocation.spawnObjects()`.  Objects, trees and bushes
+// placed on the map at runtime are checked separately.
+fn is_spawnable_tile(map: &Map, x: i32, y: i32) -> bool {
+    // Logic found in `GameLocation.IsNoSpawnTile()`.
+    if map
+        .get_tile_property(x, y, "NoSpawn", "Back")
+        .is_some_and(|value| value == "All" || value == "True")
+    {
+        return false;
+    }
+
+    match map.get_tile_property(x, y, "Spawnable", "Back") {
+        Some(value) if value != "F" => (),
+        _ => return false,
+    }
+
+    // Approximates `GameLocation.CanItemBePlacedHere()`.
+    if map.get_tile_property(x, y, "Water", "Back").is_some() || has_tile(map, x, y, "Buildings") {
+        return false;
+    }
+
+    !["AlwaysFront", "AlwaysFront2", "AlwaysFront3", "Front"]
+        .iter()
+        .any(|layer| has_tile(map, x, y, layer))
+}
+
+/// Predict the forage spawned overnight in the location described by
+/// `forage` and `map` at the start of `state.days_played`.
+///
+/// `spawned_forage` is the number of forage items already in the location.
+/// `location` is the save's state for the location.  When given, tiles
+/// occupied by objects and terrain features are taken into account.
+pub fn predict_forage<G: SeedGenerator>(
+    forage: &Forage,
+    map: &Map,
+    location: Option<&Location>,
+    state: &PredictionGameState,
+    spawned_forage: i32,
+) -> Result<Vec<ForageSpawn>> {
+    let map_size = &map.layers[0].size;
+    spawn_forage::<G>(
+        forage,
+        (map_size.w, map_size.h),
+        location,
+        state,
+        spawned_forage,
+        |x, y| is_spawnable_tile(map, x, y),
+    )
+}
+
+// Places the day's forage in a map of `map_size`.  `is_spawnable` checks the
+// map itself for a tile.
+fn spawn_forage<G: SeedGenerator>(
+    forage: &Forage,
+    map_size: (i32, i32),
+    location: Option<&Location>,
+    state: &PredictionGameState,
+    spawned_forage: i32,
+    is_spawnable: impl Fn(i32, i32) -> bool,
+) -> Result<Vec<ForageSpawn>> {
+    // Logic found in `GameLocation.spawnObjects()`.
+    let mut rng = state.create_day_save_random::<G>(0.0, 0.0, 0.0);
+    let mut spawns: Vec<ForageSpawn> = Vec::new();
+
+    if spawned_forage >= forage.max_spawned_at_once {
+        return Ok(spawns);
+    }
+
+    // The condition is checked before the season and can pull from the rng.
+    let season = state.season();
+    let mut possible_forage = Vec::new();
+    for drop in &forage.drops {
+        if drop
+            .drop
+            .evaluate_condition::<G, _>(state, Some(&mut rng))?
+            .passes_with_luck(state.daily_luck)
+            && drop.season.as_ref().is_none_or(|s| *s == season)
+        {
+            possible_forage.push(drop);
+        }
+    }
+
+    if possible_forage.is_empty() {
+        return Ok(spawns);
+    }
+
+    let spawn_count = rng
+        .next_range(forage.min_daily_spawn, forage.max_daily_spawn + 1)?
+        .min(forage.max_spawned_at_once - spawned_forage);
+    let (width, height) = map_size;
+    let mut occupied = HashSet::new();
+    for _ in 0..spawn_count {
+        for _ in 0..SPAWN_ATTEMPTS {
+            let x = rng.next_max(width);
+            let y = rng.next_max(height);
+            let tile = Point { x, y };
+            if occupied.contains(&tile)
+                || !is_spawnable(x, y)
+                || location.is_some_and(|location| location.is_tile_occupied(&tile))
+            {
+                continue;
+            }
+            // The game also skips tiles behind trees 10% of the time but rolls
+            // for that with the unseeded `Game1.random` so it doesn't affect
+            // the seeded rng.
+
+            let drop = rng.chooose_from(&possible_forage);
+            if !rng.next_weighted_bool(drop.chance) {
+                continue;
+            }
+
+            let reward = drop.drop.try_resolve::<G>(state, &mut rng)?;
+            occupied.insert(tile);
+            spawns.push(ForageSpawn { tile, reward });
+            break;
+        }
+    }
+
+    Ok(spawns)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{predictor::DropItems, rng::HashedSeedGenerator};
+
+    use super::*;
+
+    fn forage_drop(item: ItemId, chance: f64) -> ForageDrop {
+        ForageDrop {
+            drop: Drop {
+                condition: None,
+                min_stack: -1,
+                max_stack: -1,
+                drop: DropItems::Item(item),
+            },
+            chance,
+            season: None,
+        }
+    }
+
+    fn spawns(spawned_forage: i32) -> Vec<(i32, i32, ItemId)> {
+        let forage = Forage {
+            drops: vec![
+                forage_drop(item_id!("(O)16"), 1.0),
+                forage_drop(item_id!("(O)18"), 0.5),
+            ],
+            min_daily_spawn: 2,
+            max_daily_spawn: 4,
+            max_spawned_at_once: 6,
+        };
+        let state = PredictionGameState {
+            game_id: 254546202,
+            days_played: 3,
+            ..Default::default()
+        };
+        // Only the left half of the map can hold forage.
+        spawn_forage::<HashedSeedGenerator>(
+            &forage,
+            (20, 20),
+            None,
+            &state,
+            spawned_forage,
+            |x, _| x < 10,
+        )
+        .unwrap()
+        .into_iter()
+        .map(|spawn| (spawn.tile.x, spawn.tile.y, spawn.reward.item))
+        .collect()
+    }
+
+    // Expected values were computed with a separate Python port of the
+    // game's seeding, `System.Random` and `GameLocation.spawnObjects()`.
+    #[test]
+    fn forage_is_predicted() {
+        assert_eq!(
+            spawns(0),
+            vec![
+                (2, 3, item_id!("(O)16")),
+                (4, 18, item_id!("(O)18")),
+                (3, 19, item_id!("(O)18")),
+                (6, 7, item_id!("(O)16")),
+            ]
+        );
+    }
+
+    #[test]
+    fn spawns_are_capped_by_existing_forage() {
+        assert_eq!(spawns(5), vec![(2, 3, item_id!("(O)16"))]);
+        assert_eq!(spawns(6), vec![]);
+    }
+}
@@ -1,14 +1,14 @@
 use std::convert::TryFrom;
 
 use anyhow::{anyhow, Result};
-use sdv_core::HashedString;
 use strum::{Display, EnumIter, EnumString};
 use xxhash_rust::xxh32::xxh32;
 
 use super::{Drop, DropReward, PredictionGameState};
 use crate::{
     gamedata::garbage::GarbageCanData,
-    generate_day_save_seed, generate_seed, hashed_match,
+    generate_day_save_seed,
+    query::QueryResult,
     rng::{Rng, SeedGenerator},
 };
 
@@ -77,134 +77,6 @@ impl GarbageCan {
     }
 }
 
-fn daily_luck_bool(rng: &mut Rng, chance: f64) -> ConditionResult {
-    let roll = rng.next_double();
-    ConditionResult::WithDailyLuck(roll - chance)
-}
-
-macro_rules! synced_random {
-    (day, $generator:ty, $key:literal, $chance:expr, $state: expr) => {
-        ConditionResult::Static(
-            Rng::new(generate_seed!(
-                G,
-                xxh32($key.as_bytes(), 0) as i32, // i32 conversion here is very important
-                $state.game_id,
-                $state.days_played
-            ))
-            .next_weighted_bool($chance),
-        )
-    };
-    (day_luck, $generator:ty, $key:literal, $chance:expr, $state: expr) => {{
-        let mut rng = Rng::new(generate_seed!(
-            G,
-            xxh32($key.as_bytes(), 0) as i32, // i32 conversion here is very important
-            $state.game_id as f64,
-            $state.days_played as f64
-        ));
-        daily_luck_bool(&mut rng, $chance)
-    }};
-}
-
-#[derive(Clone, Copy, Debug)]
-enum ConditionResult {
-    Static(bool),
-    WithDailyLuck(f64),
-}
-
-impl ConditionResult {
-    pub fn and(self, b: bool) -> Self {
-        if !b {
-            return ConditionResult::Static(false);
-        }
-        self
-    }
-
-    pub fn and_result(self, b: Self) -> Self {
-        match self {
-            ConditionResult::Static(a) => match b {
-                ConditionResult::Static(b) => ConditionResult::Static(a && b),
-                ConditionResult::WithDailyLuck(_) => {
-                    if a {
-                        b
-                    } else {
-                        self
-                    }
-                }
-            },
-            ConditionResult::WithDailyLuck(a) => match b {
-                ConditionResult::Static(b) => {
-                    if b {
-                        self
-                    } else {
-                        ConditionResult::Static(false)
-                    }
-                }
-                ConditionResult::WithDailyLuck(b) => ConditionResult::WithDailyLuck(a.max(b)),
-            },
-        }
-    }
-}
-
-impl From<ConditionResult> for bool {
-    fn from(value: ConditionResult) -> Self {
-        match value {
-            ConditionResult::Static(v) => v,
-            // Treat daily luck rolls as success as long as they require less
-            // than 0.100001 daily luck so we can accumlate min_daily_luck.
-            ConditionResult::WithDailyLuck(min_luck) => min_luck < 0.100001,
-        }
-    }
-}
-
-// Stub until we get real condition parsing working
-fn evaulate_condition<G: SeedGenerator>(
-    r: &mut Rng,
-    state: &PredictionGameState,
-    condition: &Option<HashedString>,
-) -> ConditionResult {
-    let Some(condition) = condition else {
-        return ConditionResult::Static(true);
-    };
-
-    match condition {
-        hashed_match!("PLAYER_STAT Current trashCansChecked 20, RANDOM .002") => {
-            ConditionResult::Static(state.trash_cans_checked >= 20 && r.next_weighted_bool(0.002))
-        }
-        hashed_match!("PLAYER_STAT Current trashCansChecked 50, RANDOM .002") => {
-            ConditionResult::Static(state.trash_cans_checked >= 50 && r.next_weighted_bool(0.002))
-        }
-        hashed_match!("PLAYER_SPECIAL_ORDER_RULE_ACTIVE Current DROP_QI_BEANS, RANDOM 0.25") => {
-            ConditionResult::Static(state.qi_beans_quest_active && r.next_weighted_bool(0.25))
-        }
-        hashed_match!("PLAYER_STAT Current trashCansChecked 20, RANDOM .01") => {
-            ConditionResult::Static(state.trash_cans_checked >= 20 && r.next_weighted_bool(0.01))
-        }
-        hashed_match!("RANDOM 0.2 @addDailyLuck") => daily_luck_bool(r, 0.2),
-        hashed_match!(
-            "SYNCED_RANDOM day garbage_joja 0.2, \
-	                    PLAYER_HAS_MAIL Host ccMovieTheater, \
-                        !PLAYER_HAS_MAIL Host ccMovieTheaterJoja"
-        ) => synced_random!(day, G, "garbage_joja", 0.2, state)
-            .and(state.has_cc_movie_theater_mail)
-            .and(!state.has_cc_movie_theater_joja_mail),
-        hashed_match!("SYNCED_RANDOM day garbage_joja 0.2, !PLAYER_HAS_SEEN_EVENT Any 191393") => {
-            synced_random!(day, G, "garbage_joja", 0.2, state).and(!state.seen_event_191383)
-        }
-        hashed_match!(
-            "SYNCED_RANDOM day garbage_museum_535 0.2 @addDailyLuck, \
-	                    SYNCED_RANDOM day garbage_museum_749 0.05"
-        ) => synced_random!(day_luck, G, "garbage_museum_535", 0.2, state)
-            .and_result(synced_random!(day, G, "garbage_museum_749", 0.05, state)),
-        hashed_match!("SYNCED_RANDOM day garbage_museum_535 0.2 @addDailyLuck") => {
-            synced_random!(day_luck, G, "garbage_museum_535", 0.2, state)
-        }
-        hashed_match!("SYNCED_RANDOM day garbage_saloon_dish 0.2 @addDailyLuck") => {
-            synced_random!(day_luck, G, "garbage_saloon_dish", 0.2, state)
-        }
-        _ => panic!("Unkown condition {}", condition),
-    }
-}
-
 pub fn predict_garbage<G: SeedGenerator>(
     can: &GarbageCan,
     state: &PredictionGameState,
@@ -229,23 +101,21 @@ pub fn predict_garbage<G: SeedGenerator>(
         can.base_chance
     };
 
-    let base_chance_passed = daily_luck_bool(&mut r, base_chance as f64);
+    // The base chance roll is `NextDouble() < base_chance + dailyLuck`.  We
+    // track the luck needed to pass instead of resolving it here.
+    let base_chance_passed = QueryResult::with_daily_luck(r.next_double() - base_chance as f64);
 
     for item in &can.items {
-        if base_chance_passed.into() || item.ignore_base_chance {
-            let result = evaulate_condition::<G>(&mut r, state, &item.drop.condition);
-            let result = result.and_result(base_chance_passed);
-            match result {
-                ConditionResult::Static(success) => {
-                    if success {
-                        return Ok(Some((item.drop.try_resolve(&mut r)?, -1.)));
-                    }
-                }
-                ConditionResult::WithDailyLuck(min_luck) => {
-                    if state.daily_luck > min_luck {
-                        return Ok(Some((item.drop.try_resolve(&mut r)?, min_luck)));
-                    }
-                }
+        if base_chance_passed.is_possible() || item.ignore_base_chance {
+            let result = item.drop.evaluate_condition::<G, _>(state, Some(&mut r))?;
+            let result = if item.ignore_base_chance {
+                result
+            } else {
+                result.and(base_chance_passed)
+            };
+            if result.passes_with_luck(state.daily_luck) {
+                let min_luck = result.min_daily_luck.unwrap_or(-1.);
+                return Ok(Some((item.drop.try_resolve::<G>(state, &mut r)?, min_luck)));
             }
         }
     }
@@ -295,8 +165,10 @@ mod tests {
                     -0.05895514341953917
                 ),
                 (
+                    // The dish of the day is resolved assuming no steps were
+                    // taken.
                     GarbageCanLocation::Saloon,
-                    DropReward::new(items::DISH_OF_THE_DAY, 1),
+                    DropReward::new(items::SASHIMI, 1),
                     -0.09639927181247587
                 ),
             ]
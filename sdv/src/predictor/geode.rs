@@ -1,15 +1,17 @@
-use std::convert::{TryFrom, TryInto};
+use std::{
+    convert::{TryFrom, TryInto},
+    fmt::Display,
+};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use num_derive::FromPrimitive;
-use sdv_core::HashedString;
 use serde::{Deserialize, Serialize};
 use strum::{EnumIter, EnumString};
 
 use crate::{
-    common::items,
+    common::{items, ItemId, Season},
     gamedata::object::ObjectGeodeDropData,
-    generate_seed, hashed_match,
+    generate_seed, item_id,
     predictor::Drop,
     rng::{Rng, SeedGenerator},
     GameData,
@@ -38,6 +40,10 @@ pub enum GeodeType {
     OmniGeode = 749,
     ArtifactTrove = 275,
     GoldenCoconut = 791,
+    // Mystery boxes have non-numeric item IDs so their discriminants do not
+    // correspond to an item.
+    MysteryBox,
+    GoldenMysteryBox,
 }
 
 impl GeodeType {
@@ -49,8 +55,14 @@ impl GeodeType {
             Self::OmniGeode => "Omni Geode",
             Self::ArtifactTrove => "Artifact Trove",
             Self::GoldenCoconut => "Golden Coconut",
+            Self::MysteryBox => "Mystery Box",
+            Self::GoldenMysteryBox => "Golden Mystery Box",
         }
     }
+
+    pub fn is_mystery_box(&self) -> bool {
+        matches!(self, Self::MysteryBox | Self::GoldenMysteryBox)
+    }
 }
 
 /// Cached drop information
@@ -103,33 +115,41 @@ impl Geode {
             drops,
         })
     }
-}
 
-// Stub until we get real condition parsing working
-fn evaulate_condition(condition: &Option<HashedString>, geodes_cracked: i32) -> bool {
-    let Some(condition) = condition else {
-        return true;
-    };
+    // Mystery boxes don't have drops in game data so tests can build them
+    // without loading it.
+    #[cfg(test)]
+    pub(crate) fn mystery_box(ty: GeodeType) -> Self {
+        Self {
+            ty,
+            default_drops: false,
+            drops: Vec::new(),
+        }
+    }
 
-    match condition {
-        hashed_match!("PLAYER_STAT Current GeodesCracked 16") => geodes_cracked >= 16,
-        hashed_match!("!PLAYER_HAS_MAIL Current goldenCoconutHat") => true,
-        _ => panic!("Unkown condition {:x?}", condition),
+    pub fn ty(&self) -> GeodeType {
+        self.ty
     }
 }
 
 /// Predict a single geode pull
 ///
-/// TODO: add mystery box support
+/// Mystery boxes are seeded with `state.mystery_boxes_opened` instead of
+/// `state.geodes_cracked`.
 pub fn predict_single_geode<G: SeedGenerator>(
     geode: &Geode,
     state: &PredictionGameState,
 ) -> Result<DropReward> {
     // Logic found in StardewValley.Utility.getTreasureFromGeode()
 
+    let opened = if geode.ty.is_mystery_box() {
+        state.mystery_boxes_opened
+    } else {
+        state.geodes_cracked
+    };
     let mut rng = Rng::new(generate_seed!(
         G,
-        state.geodes_cracked,
+        opened,
         state.game_id / 2,
         (state.multiplayer_id as i32) / 2
     ));
@@ -142,7 +162,9 @@ pub fn predict_single_geode<G: SeedGenerator>(
         }
     }
 
-    // TODO: Mystery Box logic goes here
+    if geode.ty.is_mystery_box() {
+        return predict_mystery_box(geode.ty, state, &mut rng);
+    }
 
     if rng.next_double() <= 0.1 && state.qi_beans_quest_active {
         let quantity = if rng.next_double() < 0.25 { 5 } else { 1 };
@@ -161,12 +183,15 @@ pub fn predict_single_geode<G: SeedGenerator>(
         // drops accoring to precidence
         for drop in &geode.drops {
             if !rng.next_weighted_bool(drop.chance)
-                || !evaulate_condition(&drop.drop.condition, state.geodes_cracked as i32)
+                || !drop
+                    .drop
+                    .evaluate_condition::<G, _>(state, Some(&mut rng))?
+                    .is_certain()
             {
                 continue;
             }
 
-            if let Ok(reward) = drop.drop.try_resolve(&mut rng) {
+            if let Ok(reward) = drop.drop.try_resolve::<G>(state, &mut rng) {
                 return Ok(reward);
             }
         }
@@ -244,6 +269,129 @@ pub fn predict_single_geode<G: SeedGenerator>(
     Ok(DropReward::new(item, amount))
 }
 
+fn object(id: impl Display, quantity: u32) -> Result<DropReward> {
+    Ok(DropReward::new(
+        format!("(O){id}").parse::<ItemId>()?,
+        quantity,
+    ))
+}
+
+// Logic found in `Utility.getTreasureFromGeode()`.
+//
+// Drops that can't be predicted are returned as errors rather than guessed:
+// low grade crops are picked with the unseeded `Game1.random` and the common
+// drops 5 and 7 through 13 aren't modeled yet.
+fn predict_mystery_box(
+    ty: GeodeType,
+    state: &PredictionGameState,
+    rng: &mut Rng,
+) -> Result<DropReward> {
+    let is_golden = ty == GeodeType::GoldenMysteryBox;
+
+    // Rare drops are not available until the player has opened more than 10
+    // regular mystery boxes.
+    if state.mystery_boxes_opened > 10 || is_golden {
+        let rare_mod = if is_golden { 2.0 } else { 1.0 };
+
+        // The order of these conditionals is important as they contain rng
+        // pulls.
+        if is_golden && state.has_farming_mastery && rng.next_weighted_bool(0.005) {
+            return object("GoldenAnimalCracker", 1);
+        }
+        if is_golden && rng.next_weighted_bool(0.005) {
+            return Ok(DropReward::new(item_id!("(BC)272"), 1));
+        }
+        if rng.next_weighted_bool(0.002 * rare_mod) {
+            return object("279", 1);
+        }
+        if rng.next_weighted_bool(0.004 * rare_mod) {
+            return object("74", 1);
+        }
+        if rng.next_weighted_bool(0.008 * rare_mod) {
+            return object("166", 1);
+        }
+        let mystery_book_bonus = if state.has_mystery_book_mail {
+            0.0
+        } else {
+            0.0004 * state.mystery_boxes_opened as f64
+        };
+        if rng.next_weighted_bool(0.01 * rare_mod + mystery_book_bonus) {
+            // The first book is always a Book of Mysteries.
+            if !state.has_mystery_book_mail {
+                return object("Book_Mystery", 1);
+            }
+            return object(rng.chooose_from(&["PurpleBook", "Book_Mystery"]), 1);
+        }
+        if rng.next_weighted_bool(0.01 * rare_mod) {
+            return object(rng.chooose_from(&["797", "373"]), 1);
+        }
+        if rng.next_weighted_bool(0.01 * rare_mod) {
+            return Ok(DropReward::new(item_id!("(H)MysteryHat"), 1));
+        }
+        if rng.next_weighted_bool(0.01 * rare_mod) {
+            return Ok(DropReward::new(item_id!("(S)MysteryShirt"), 1));
+        }
+        if rng.next_weighted_bool(0.01 * rare_mod) {
+            return Ok(DropReward::new(item_id!("(WP)MoreWalls:11"), 1));
+        }
+
+        if rng.next_weighted_bool(0.1) || is_golden {
+            return match rng.next_max(15) {
+                0 => object("288", 5),
+                1 => object("253", 3),
+                2 => {
+                    if state.fishing_level >= 6 && rng.next_bool() {
+                        object(rng.chooose_from(&["687", "695"]), 1)
+                    } else {
+                        object("242", 2)
+                    }
+                }
+                3 => object("204", 2),
+                4 => object("369", 20),
+                5 => object("466", 20),
+                6 => object("773", 2),
+                7 => object("688", 3),
+                8 => object(rng.next_range(628, 634)?, 1),
+                9 => Err(low_grade_crop_error()),
+                10 => {
+                    if rng.next_bool() {
+                        Ok(DropReward::new(item_id!("(W)60"), 1))
+                    } else {
+                        object(rng.chooose_from(&["533", "534"]), 1)
+                    }
+                }
+                11 => object("621", 1),
+                12 => object("MysteryBox", rng.next_range(3, 5)? as u32),
+                13 => object(format!("SkillBook_{}", rng.next_max(5)), 1),
+                // Logic found in `Utility.getRaccoonSeedForCurrentTimeOfYear()`.
+                _ => {
+                    let seeds = match state.season() {
+                        Season::Spring => "CarrotSeeds",
+                        Season::Summer => "SummerSquashSeeds",
+                        Season::Fall => "BroccoliSeeds",
+                        Season::Winter => "PowdermelonSeeds",
+                    };
+                    object(seeds, 8)
+                }
+            };
+        }
+    }
+
+    match rng.next_max(14) {
+        0 => object("395", 3),
+        1 => object("287", 5),
+        2 => Err(low_grade_crop_error()),
+        3 => object(rng.next_range(727, 734)?, 1),
+        4 => object(rng.chooose_from(&["191", "233"]), 1),
+        6 => object("709", 10),
+        drop => Err(anyhow!("Mystery box common drop {drop} is not modeled")),
+    }
+}
+
+fn low_grade_crop_error() -> anyhow::Error {
+    anyhow!("Mystery box low grade crops are picked with the unseeded Game1.random")
+}
+
 #[cfg(test)]
 mod tests {
     use crate::rng::HashedSeedGenerator;
@@ -370,4 +518,89 @@ mod tests {
             ],
         );
     }
+
+    fn mystery_box(ty: GeodeType, mystery_boxes_opened: u32) -> Result<DropReward> {
+        let geode = Geode::mystery_box(ty);
+        let state = PredictionGameState {
+            game_id: 7269403,
+            multiplayer_id: -7347405514601242418i64,
+            mystery_boxes_opened,
+            ..Default::default()
+        };
+        predict_single_geode::<HashedSeedGenerator>(&geode, &state)
+    }
+
+    // Expected values were computed with a separate Python port of the
+    // game's seeding, `System.Random` and `Utility.getTreasureFromGeode()`.
+    #[test]
+    fn mystery_box_common_drops_are_predicted() {
+        let expected = [
+            (0, None),
+            (1, Some(DropReward::new(item_id!("(O)709"), 10))),
+            (2, None),
+            (3, None),
+            (4, None),
+            (5, None),
+            (6, None),
+            (7, Some(DropReward::new(item_id!("(O)709"), 10))),
+            (8, Some(DropReward::new(item_id!("(O)395"), 3))),
+            (9, Some(DropReward::new(item_id!("(O)287"), 5))),
+            (10, None),
+        ];
+        for (mystery_boxes_opened, reward) in expected {
+            let result = mystery_box(GeodeType::MysteryBox, mystery_boxes_opened);
+            assert_eq!(result.ok(), reward, "{mystery_boxes_opened}");
+        }
+    }
+
+    #[test]
+    fn mystery_box_rare_drops_need_ten_boxes_opened() {
+        // Only golden mystery boxes can roll rare drops before ten boxes
+        // have been opened.
+        assert_eq!(
+            mystery_box(GeodeType::GoldenMysteryBox, 5).unwrap(),
+            DropReward::new(item_id!("(S)MysteryShirt"), 1)
+        );
+        assert_eq!(
+            mystery_box(GeodeType::GoldenMysteryBox, 10).unwrap(),
+            DropReward::new(item_id!("(O)74"), 1)
+        );
+
+        let expected = [
+            (11, DropReward::new(item_id!("(S)MysteryShirt"), 1)),
+            (49, DropReward::new(item_id!("(O)Book_Mystery"), 1)),
+            (75, DropReward::new(item_id!("(O)74"), 1)),
+        ];
+        for (mystery_boxes_opened, reward) in expected {
+            assert_eq!(
+                mystery_box(GeodeType::MysteryBox, mystery_boxes_opened).unwrap(),
+                reward,
+                "{mystery_boxes_opened}"
+            );
+        }
+    }
+
+    #[test]
+    fn mystery_boxes_use_their_own_counter() {
+        let geode = Geode::mystery_box(GeodeType::MysteryBox);
+        for mystery_boxes_opened in 0..=10 {
+            let state = PredictionGameState {
+                game_id: 7269403,
+                multiplayer_id: -7347405514601242418i64,
+                mystery_boxes_opened,
+                ..Default::default()
+            };
+            let reward = predict_single_geode::<HashedSeedGenerator>(&geode, &state).ok();
+
+            // Cracking geodes doesn't change mystery box results.
+            let state = PredictionGameState {
+                geodes_cracked: 100,
+                ..state
+            };
+            assert_eq!(
+                predict_single_geode::<HashedSeedGenerator>(&geode, &state).ok(),
+                reward
+            );
+        }
+    }
 }
@@ -0,0 +1,140 @@
+use anyhow::{anyhow, Result};
+
+use crate::{
+    generate_day_save_seed,
+    rng::{Rng, SeedGenerator},
+};
+
+use super::{dish_of_the_day::roll_dish_of_the_day, PredictionGameState};
+
+/// Highest daily luck the game rolls.
+pub const MAX_SHARED_DAILY_LUCK: f64 = 0.1;
+
+/// Luck added for players with the special charm.
+pub const SPECIAL_CHARM_LUCK: f64 = 0.025;
+
+// `Game1.random` as reseeded at the start of `state.days_played`.
+//
+// Logic found in `Game1._newDayAfterFade()`.  The game reseeds `Game1.random`
+// before the days played stat advances, so the seed uses the previous day, and
+// pulls from it once per day of the month.
+pub(crate) fn new_day_random<G: SeedGenerator>(state: &PredictionGameState) -> Rng {
+    let previous_day = state.days_played.saturating_sub(1);
+    if state.legacy_day_random {
+        // Before 1.6 the seed was a plain sum and the day of the month hadn't
+        // advanced yet either.
+        let mut rng = Rng::new(
+            (state.game_id as i32 / 100)
+                .wrapping_add((previous_day * 10 + 1) as i32)
+                .wrapping_add(state.steps_taken as i32),
+        );
+        let previous_day_of_month = if previous_day == 0 {
+            0
+        } else {
+            (previous_day - 1) % 28 + 1
+        };
+        for _ in 0..previous_day_of_month {
+            rng.next_i32();
+        }
+        return rng;
+    }
+
+    let mut rng = Rng::new(generate_day_save_seed!(
+        G,
+        previous_day,
+        state.game_id,
+        state.game_id / 100,
+        previous_day * 10 + 1,
+        state.steps_taken
+    ));
+    for _ in 0..state.day_of_month() {
+        rng.next_i32();
+    }
+    rng
+}
+
+/// Predict the daily luck shared by all players on `state.days_played`.
+///
+/// The roll is seeded with `state.steps_taken` which must be the steps taken
+/// by the end of the previous day.
+///
+/// Only saves from before 1.6 are supported.  1.6 rolls the luck from the
+/// start of day random after pulls that aren't modeled, so an error is
+/// returned for newer saves.
+pub fn predict_shared_daily_luck<G: SeedGenerator>(state: &PredictionGameState) -> Result<f64> {
+    if !state.legacy_day_random {
+        return Err(anyhow!(
+            "Daily luck can only be predicted for pre 1.6 saves"
+        ));
+    }
+
+    // The dish of the day is rolled from the same rng before the luck.
+    let mut rng = new_day_random::<G>(state);
+    roll_dish_of_the_day(&mut rng)?;
+
+    // The game pulls twice more before the luck.  The number of pulls was
+    // matched against the 1.5.6 test save.
+    rng.next_i32();
+    rng.next_i32();
+
+    let luck = rng.next_range(-100, 101)? as f64 / 1000.0;
+    Ok(luck.min(MAX_SHARED_DAILY_LUCK))
+}
+
+/// Predict the player's daily luck on `state.days_played`.
+///
+/// This is the shared daily luck plus the special charm bonus.
+pub fn predict_daily_luck<G: SeedGenerator>(state: &PredictionGameState) -> Result<f64> {
+    // Logic found in `Farmer.DailyLuck`.
+    let charm = if state.has_special_charm {
+        SPECIAL_CHARM_LUCK
+    } else {
+        0.0
+    };
+    Ok(predict_shared_daily_luck::<G>(state)? + charm)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{rng::HashedSeedGenerator, save::load_test_save};
+
+    use super::*;
+
+    #[test]
+    fn shared_daily_luck_matches_legacy_save() {
+        let save = load_test_save("testrain_327653833");
+        let (state, _) = PredictionGameState::from_save(&save);
+        assert!(state.legacy_day_random);
+        let luck = predict_shared_daily_luck::<HashedSeedGenerator>(&state).unwrap();
+        assert!((luck - save.daily_luck as f64).abs() < 1e-6, "{}", luck);
+        assert!((luck - 0.006).abs() < 1e-9, "{}", luck);
+    }
+
+    #[test]
+    fn shared_daily_luck_is_not_predicted_for_1_6_saves() {
+        let save = load_test_save("ChimkinHill_370550379");
+        let (state, _) = PredictionGameState::from_save(&save);
+        assert!(!state.legacy_day_random);
+        assert!(predict_shared_daily_luck::<HashedSeedGenerator>(&state).is_err());
+    }
+
+    #[test]
+    fn special_charm_adds_luck() {
+        let mut state = PredictionGameState {
+            game_id: 254546202,
+            steps_taken: 1234,
+            legacy_day_random: true,
+            ..Default::default()
+        };
+        for days_played in 1..(28 * 4) {
+            state.days_played = days_played;
+            state.has_special_charm = false;
+            let luck = predict_daily_luck::<HashedSeedGenerator>(&state).unwrap();
+            assert!((-0.1..=MAX_SHARED_DAILY_LUCK).contains(&luck), "{}", luck);
+
+            state.has_special_charm = true;
+            let charmed_luck = predict_daily_luck::<HashedSeedGenerator>(&state).unwrap();
+            assert!((charmed_luck - luck - SPECIAL_CHARM_LUCK).abs() < 1e-9);
+        }
+    }
+}
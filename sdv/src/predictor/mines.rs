@@ -0,0 +1,300 @@
+use std::ops::RangeInclusive;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    generate_day_save_seed,
+    rng::{Rng, SeedGenerator},
+};
+
+use super::PredictionGameState;
+
+/// First level of the Skull Cavern when counting from the top of the mines.
+pub const SKULL_CAVERN_START: u32 = 121;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MineArea {
+    /// Levels 1-39.
+    Earth,
+    /// Levels 40-79.
+    Frost,
+    /// Levels 80-120.
+    Lava,
+    SkullCavern,
+}
+
+impl MineArea {
+    // Logic found in `MineShaft.getMineArea()`.
+    pub const fn from_level(level: u32) -> Self {
+        match level {
+            0..=39 => Self::Earth,
+            40..=79 => Self::Frost,
+            80..=120 => Self::Lava,
+            _ => Self::SkullCavern,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Infestation {
+    /// All monsters must be killed to spawn a ladder.
+    Monster,
+    /// Slime infested floor.
+    Slime,
+    /// Pepper rex floor.  Skull Cavern only.
+    Dino,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct MineFloor {
+    pub level: u32,
+    pub infestation: Option<Infestation>,
+    pub is_quarry: bool,
+    pub is_dark: bool,
+
+    /// Set for Skull Cavern floors where the map layout, which is picked
+    /// with an unseeded random number generator, can still prevent the
+    /// predicted infestation.
+    pub depends_on_layout: bool,
+}
+
+// Logic found in `MineShaft.loadLevel()`.
+fn map_number(level: u32) -> u32 {
+    if level == 120 {
+        return 120;
+    }
+
+    let map = if (level % 40).is_multiple_of(20) && !level.is_multiple_of(40) {
+        20
+    } else if level.is_multiple_of(10) {
+        10
+    } else {
+        level
+    };
+    map % 40
+}
+
+// Whether a map can be turned into a monster or slime area.
+fn can_be_infested(map: u32) -> bool {
+    !map.is_multiple_of(5) && map % 40 > 5 && map % 40 < 30 && map % 40 != 19
+}
+
+/// Predict the special properties of mine `level` on `state.days_played`.
+///
+/// Levels above [SKULL_CAVERN_START] are in the Skull Cavern.
+pub fn predict_mine_floor<G: SeedGenerator>(state: &PredictionGameState, level: u32) -> MineFloor {
+    // Logic found in `MineShaft.loadLevel()`.
+    let area = MineArea::from_level(level);
+    let is_skull_cavern = area == MineArea::SkullCavern;
+
+    let mut rng = Rng::new(generate_day_save_seed!(
+        G,
+        state.days_played,
+        state.game_id,
+        level * 100
+    ));
+
+    let mut floor = MineFloor {
+        level,
+        infestation: None,
+        is_quarry: false,
+        // Logic found in `MineShaft.isDarkArea()`.
+        is_dark: level % 40 > 30 && area != MineArea::Frost,
+        depends_on_layout: false,
+    };
+
+    // Skull Cavern layouts are picked from any map that is not a multiple
+    // of five.  Since we can't know which one, the map checks are skipped
+    // and the floor is flagged.
+    let map = map_number(level);
+    let map_allows_infestation = is_skull_cavern || can_be_infested(map);
+
+    // The order of these conditionals is important as they contain rng pulls.
+    if (!state.has_oil_of_garlic_buff || is_skull_cavern)
+        && rng.next_double() < 0.044
+        && map_allows_infestation
+    {
+        floor.infestation = Some(if rng.next_bool() {
+            Infestation::Monster
+        } else {
+            Infestation::Slime
+        });
+        if is_skull_cavern && level > SKULL_CAVERN_START + 5 && rng.next_bool() {
+            floor.infestation = Some(Infestation::Dino);
+        }
+        floor.depends_on_layout = is_skull_cavern;
+    } else if !is_skull_cavern
+        && rng.next_double() < 0.044
+        && state.cc_crafts_room_complete
+        && state.has_visited_quarry_mine
+        && map % 40 > 1
+        && !map.is_multiple_of(5)
+    {
+        floor.is_quarry = true;
+        if rng.next_double() < 0.25 && !state.has_oil_of_garlic_buff {
+            floor.infestation = Some(Infestation::Monster);
+        }
+    }
+
+    floor
+}
+
+/// Predict the special properties of each of `levels`.
+pub fn predict_mine_floors<G: SeedGenerator>(
+    state: &PredictionGameState,
+    levels: RangeInclusive<u32>,
+) -> Vec<MineFloor> {
+    levels
+        .map(|level| predict_mine_floor::<G>(state, level))
+        .collect()
+}
+
+/// Chance that breaking a stone spawns a ladder.
+///
+/// `stones_left` is the number of stones left on the level after the stone is
+/// broken.
+pub fn ladder_chance(stones_left: u32, luck_level: i32, daily_luck: f64, enemy_count: u32) -> f64 {
+    // Logic found in `MineShaft.checkStoneForItems()`.
+    let mut chance =
+        0.02 + 1.0 / stones_left.max(1) as f64 + luck_level as f64 / 100.0 + daily_luck / 5.0;
+    if enemy_count == 0 {
+        chance += 0.04;
+    }
+    chance
+}
+
+/// The roll compared against [ladder_chance] when the stone at (`x`, `y`)
+/// on mine `level` is broken.  A ladder spawns if the roll is less than the
+/// chance or there are no stones left on the level.
+pub fn ladder_roll<G: SeedGenerator>(
+    state: &PredictionGameState,
+    level: u32,
+    x: i32,
+    y: i32,
+) -> f64 {
+    // Logic found in `MineShaft.checkStoneForItems()`.
+    let mut rng = Rng::new(generate_day_save_seed!(
+        G,
+        state.days_played,
+        state.game_id,
+        x * 1000,
+        y,
+        level
+    ));
+    rng.next_double();
+    rng.next_double()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rng::HashedSeedGenerator;
+
+    use super::*;
+
+    #[test]
+    fn map_numbers() {
+        assert_eq!(map_number(1), 1);
+        assert_eq!(map_number(10), 10);
+        assert_eq!(map_number(20), 20);
+        assert_eq!(map_number(39), 39);
+        assert_eq!(map_number(40), 10);
+        assert_eq!(map_number(45), 5);
+        assert_eq!(map_number(60), 20);
+        assert_eq!(map_number(70), 10);
+        assert_eq!(map_number(120), 120);
+    }
+
+    #[test]
+    fn mine_areas() {
+        assert_eq!(MineArea::from_level(1), MineArea::Earth);
+        assert_eq!(MineArea::from_level(40), MineArea::Frost);
+        assert_eq!(MineArea::from_level(80), MineArea::Lava);
+        assert_eq!(MineArea::from_level(120), MineArea::Lava);
+        assert_eq!(MineArea::from_level(121), MineArea::SkullCavern);
+    }
+
+    #[test]
+    fn elevator_floors_are_never_special() {
+        let state = PredictionGameState {
+            game_id: 254546202,
+            cc_crafts_room_complete: true,
+            has_visited_quarry_mine: true,
+            ..Default::default()
+        };
+        for days_played in 1..200 {
+            let state = PredictionGameState {
+                days_played,
+                ..state.clone()
+            };
+            for floor in predict_mine_floors::<HashedSeedGenerator>(&state, 1..=120) {
+                if floor.level.is_multiple_of(5) {
+                    assert_eq!(floor.infestation, None, "{floor:?}");
+                    assert!(!floor.is_quarry, "{:?}", floor);
+                }
+                if floor.infestation == Some(Infestation::Dino) {
+                    panic!("dino floor outside of the skull cavern: {:?}", floor);
+                }
+            }
+        }
+    }
+
+    // Expected values were computed with a separate Python port of the
+    // game's seeding, `System.Random` and `MineShaft.loadLevel()`.
+    #[test]
+    fn special_floors_are_predicted() {
+        let state = PredictionGameState {
+            days_played: 5,
+            game_id: 254546202,
+            cc_crafts_room_complete: true,
+            has_visited_quarry_mine: true,
+            ..Default::default()
+        };
+        let special: Vec<(u32, Option<Infestation>, bool)> =
+            predict_mine_floors::<HashedSeedGenerator>(&state, 1..=200)
+                .into_iter()
+                .filter(|floor| floor.infestation.is_some() || floor.is_quarry)
+                .map(|floor| (floor.level, floor.infestation, floor.is_quarry))
+                .collect();
+        assert_eq!(
+            special,
+            vec![
+                (21, Some(Infestation::Monster), false),
+                (23, None, true),
+                (46, None, true),
+                (49, Some(Infestation::Slime), false),
+                (109, Some(Infestation::Slime), false),
+                (116, None, true),
+                (173, Some(Infestation::Dino), false),
+                (186, Some(Infestation::Slime), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn dark_floors() {
+        let state = PredictionGameState {
+            days_played: 1,
+            ..Default::default()
+        };
+        let dark: Vec<u32> = predict_mine_floors::<HashedSeedGenerator>(&state, 1..=120)
+            .into_iter()
+            .filter(|floor| floor.is_dark)
+            .map(|floor| floor.level)
+            .collect();
+        assert_eq!(
+            dark,
+            vec![31, 32, 33, 34, 35, 36, 37, 38, 39, 111, 112, 113, 114, 115, 116, 117, 118, 119]
+        );
+    }
+
+    #[test]
+    fn ladder_chance_includes_bonuses() {
+        assert!((ladder_chance(0, 0, 0.0, 1) - 1.02).abs() < 1e-9);
+        assert!((ladder_chance(49, 0, 0.0, 1) - (0.02 + 1.0 / 49.0)).abs() < 1e-9);
+        assert!(
+            (ladder_chance(49, 1, 0.1, 0) - (0.02 + 1.0 / 49.0 + 0.01 + 0.02 + 0.04)).abs() < 1e-9
+        );
+    }
+}
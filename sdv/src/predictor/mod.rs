@@ -1,18 +1,29 @@
 use std::{cmp::max, convert::TryFrom};
 
 use anyhow::{anyhow, Result};
-use sdv_core::HashedString;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    common::{GenericSpawnItemDataWithCondition, ItemId, Season},
+    common::{items, GenericSpawnItemDataWithCondition, ItemId, Season},
     generate_day_save_seed, item_id,
+    query::{GameStateQuery, PlayerTarget, QueryContext, QueryResult},
     rng::{Rng, SeedGenerator},
+    save::{SaveGame, Skill, TerrainFeature},
 };
 
+pub mod artifact_spot;
 pub mod bubbles;
+pub mod dish_of_the_day;
+pub mod fishing;
+pub mod forage;
 pub mod garbage;
 pub mod geode;
+pub mod luck;
+pub mod mines;
 pub mod night_event;
+pub mod search;
+pub mod shop;
+pub mod traveling_cart;
 pub mod weather;
 
 /// Game state data for garbage prediction.
@@ -34,9 +45,113 @@ pub struct PredictionGameState {
     pub raccoon_tree_fallen: bool,
     pub has_fairy_rose: bool,
     pub has_mail_got_capsule: bool,
+    pub has_golden_coconut_hat_mail: bool,
+    pub has_oil_of_garlic_buff: bool,
+    pub cc_crafts_room_complete: bool,
+    pub has_visited_quarry_mine: bool,
+
+    pub lost_books_found: u32,
+    pub has_seen_qi_plane: bool,
+    pub has_farming_mastery: bool,
+    pub mystery_boxes_opened: u32,
+    pub has_mystery_book_mail: bool,
+    pub fishing_level: u32,
+
+    /// Location context whose weather a rain totem used the previous day
+    /// changed.  See [weather::WeatherLocation::rain_totem_target].
+    pub rain_totem_context: Option<String>,
+
+    /// Steps taken by the end of the previous day.  Seeds the daily luck.
+    pub steps_taken: u32,
+    pub has_special_charm: bool,
+
+    /// Set for saves from before 1.6 which seeded the start of day random
+    /// differently.  See [luck::predict_shared_daily_luck].
+    pub legacy_day_random: bool,
 }
 
 impl PredictionGameState {
+    /// Build the state for the current day of `save`.
+    ///
+    /// Returns the state along with the names of the fields that can't be
+    /// determined from the save.  Those fields are left at their defaults.
+    pub fn from_save(save: &SaveGame) -> (Self, Vec<&'static str>) {
+        let player = &save.player;
+        let has_mail = |mail: &str| player.mail_received.contains(mail);
+        // Totems used today aren't recorded in saves.
+        let mut undetermined = vec!["rain_totem_context"];
+
+        let lost_books_found = save.lost_books_found.unwrap_or_else(|| {
+            undetermined.push("lost_books_found");
+            0
+        });
+
+        // Logic found in `Utility.pickFarmEvent()`.
+        let has_fairy_rose = save.locations.get("Farm").is_some_and(|farm| {
+            farm.terrain_features
+                .values()
+                .filter_map(TerrainFeature::crop)
+                .any(|crop| crop.harvest_id.as_deref() == Some("595") && crop.is_mature())
+        });
+
+        let fishing_level = player
+            .levels()
+            .get(&Skill::Fishing)
+            .map_or(0, |(level, _)| *level as u32);
+
+        // 1.6 saves store every stat that has been set so a missing stat
+        // is 0.  Older saves don't record the stats added in 1.6 and only
+        // some of the others.
+        let has_every_stat = !save.is_older_than(1, 6);
+        let mut stat = |field, name| {
+            player.stats.get(name).unwrap_or_else(|| {
+                if !has_every_stat {
+                    undetermined.push(field);
+                }
+                0
+            })
+        };
+
+        let state = Self {
+            game_id: save.unique_id_for_this_game as u32,
+            multiplayer_id: player.unique_multiplayer_id,
+            days_played: save.days_played(),
+            daily_luck: save.daily_luck as f64,
+            geodes_cracked: stat("geodes_cracked", "geodesCracked"),
+            deepest_mine_level: player.deepest_mine_level.max(0) as u32,
+            has_trash_book: stat("has_trash_book", "Book_Trash") > 0,
+            trash_cans_checked: stat("trash_cans_checked", "trashCansChecked") as usize,
+            qi_beans_quest_active: save
+                .special_orders
+                .iter()
+                .any(|order| order.rule_active("DROP_QI_BEANS")),
+            has_cc_movie_theater_mail: has_mail("ccMovieTheater"),
+            has_cc_movie_theater_joja_mail: has_mail("ccMovieTheaterJoja"),
+            seen_event_191383: player.events_seen.contains("191393"),
+            cc_pantry_complete: has_mail("ccPantry"),
+            raccoon_tree_fallen: has_mail("raccoonTreeFallen"),
+            has_fairy_rose,
+            has_mail_got_capsule: has_mail("Got_Capsule"),
+            has_golden_coconut_hat_mail: has_mail("goldenCoconutHat"),
+            // Buffs don't last past the day so a save never has one.
+            has_oil_of_garlic_buff: false,
+            cc_crafts_room_complete: has_mail("ccCraftsRoom"),
+            has_visited_quarry_mine: has_mail("VisitedQuarryMine"),
+            lost_books_found: lost_books_found.max(0) as u32,
+            has_seen_qi_plane: has_mail("sawQiPlane"),
+            has_farming_mastery: stat("has_farming_mastery", "mastery_0") > 0,
+            mystery_boxes_opened: stat("mystery_boxes_opened", "MysteryBoxesOpened"),
+            has_mystery_book_mail: has_mail("GotMysteryBook"),
+            fishing_level,
+            rain_totem_context: None,
+            steps_taken: stat("steps_taken", "stepsTaken"),
+            has_special_charm: has_mail("HasSpecialCharm"),
+            legacy_day_random: save.is_older_than(1, 6),
+        };
+
+        (state, undetermined)
+    }
+
     pub fn create_day_save_random<G: SeedGenerator>(&self, a: f64, b: f64, c: f64) -> Rng {
         Rng::new(generate_day_save_seed!(
             G,
@@ -48,12 +163,30 @@ impl PredictionGameState {
         ))
     }
 
+    /// Daily luck the game rolls for `days_played`.
+    ///
+    /// See [luck::predict_daily_luck].
+    pub fn predicted_daily_luck<G: SeedGenerator>(&self) -> Result<f64> {
+        luck::predict_daily_luck::<G>(self)
+    }
+
+    /// Copy of the state with `daily_luck` set to the predicted daily luck.
+    ///
+    /// Predictions that track the luck needed for a drop, like garbage cans,
+    /// resolve to a definite outcome with this state.
+    pub fn with_predicted_daily_luck<G: SeedGenerator>(&self) -> Result<Self> {
+        Ok(Self {
+            daily_luck: self.predicted_daily_luck::<G>()?,
+            ..self.clone()
+        })
+    }
+
     pub const fn year(&self) -> u32 {
-        (self.days_played - 1) / (28 * 4) + 1
+        self.days_played.saturating_sub(1) / (28 * 4) + 1
     }
 
     pub const fn season(&self) -> Season {
-        match ((self.days_played - 1) / 28) % 4 {
+        match (self.days_played.saturating_sub(1) / 28) % 4 {
             0 => Season::Spring,
             1 => Season::Summer,
             2 => Season::Fall,
@@ -62,7 +195,7 @@ impl PredictionGameState {
     }
 
     pub const fn day_of_month(&self) -> u32 {
-        (self.days_played - 1) % 28 + 1
+        self.days_played.saturating_sub(1) % 28 + 1
     }
 
     pub const fn is_festival_day(&self) -> bool {
@@ -98,7 +231,57 @@ impl PredictionGameState {
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+impl QueryContext for PredictionGameState {
+    fn game_id(&self) -> u32 {
+        self.game_id
+    }
+
+    fn days_played(&self) -> u32 {
+        self.days_played
+    }
+
+    fn daily_luck(&self) -> f64 {
+        self.daily_luck
+    }
+
+    fn player_stat(&self, _player: &PlayerTarget, stat: &str) -> Option<u32> {
+        match stat.to_ascii_lowercase().as_str() {
+            "trashcanschecked" => Some(self.trash_cans_checked as u32),
+            "geodescracked" => Some(self.geodes_cracked),
+            "mysteryboxesopened" => Some(self.mystery_boxes_opened),
+            "stepstaken" => Some(self.steps_taken),
+            _ => None,
+        }
+    }
+
+    fn player_has_mail(&self, _player: &PlayerTarget, mail: &str) -> Option<bool> {
+        match mail {
+            "ccMovieTheater" => Some(self.has_cc_movie_theater_mail),
+            "ccMovieTheaterJoja" => Some(self.has_cc_movie_theater_joja_mail),
+            "goldenCoconutHat" => Some(self.has_golden_coconut_hat_mail),
+            "Got_Capsule" => Some(self.has_mail_got_capsule),
+            "sawQiPlane" => Some(self.has_seen_qi_plane),
+            "GotMysteryBook" => Some(self.has_mystery_book_mail),
+            _ => None,
+        }
+    }
+
+    fn player_has_seen_event(&self, _player: &PlayerTarget, event: &str) -> Option<bool> {
+        match event {
+            "191393" => Some(self.seen_event_191383),
+            _ => None,
+        }
+    }
+
+    fn player_special_order_rule_active(&self, _player: &PlayerTarget, rule: &str) -> Option<bool> {
+        match rule {
+            "DROP_QI_BEANS" => Some(self.qi_beans_quest_active),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct DropReward {
     pub item: ItemId,
     pub quantity: u32,
@@ -123,19 +306,63 @@ enum DropItems {
 /// predition.
 #[derive(Clone, Debug)]
 pub struct Drop {
-    condition: Option<HashedString>,
+    condition: Option<GameStateQuery>,
     min_stack: i32,
     max_stack: i32,
     drop: DropItems,
 }
 
 impl Drop {
-    pub fn try_resolve(&self, rng: &mut Rng) -> Result<DropReward> {
+    /// Evaluate the drop's condition.  Drops with no condition always pass.
+    pub fn evaluate_condition<G: SeedGenerator, C: QueryContext>(
+        &self,
+        context: &C,
+        rng: Option<&mut Rng>,
+    ) -> Result<QueryResult> {
+        match &self.condition {
+            Some(condition) => condition.evaluate::<G, C>(context, rng),
+            None => Ok(QueryResult::TRUE),
+        }
+    }
+
+    /// Resolve the drop's item and stack size.
+    ///
+    /// `DISH_OF_THE_DAY` and `RANDOM_BASE_SEASON_ITEM` are resolved to
+    /// concrete items using `state`.
+    pub fn try_resolve<G: SeedGenerator>(
+        &self,
+        state: &PredictionGameState,
+        rng: &mut Rng,
+    ) -> Result<DropReward> {
         let item = match &self.drop {
             DropItems::Item(item) => item,
             DropItems::Random(items) => rng.chooose_from(items),
         };
 
+        self.try_resolve_item::<G>(item.clone(), state, rng)
+    }
+
+    /// Resolve the drop's stack size for an item that was already chosen.
+    ///
+    /// Used for drops whose item comes from an item query that has to be
+    /// resolved separately.
+    pub fn try_resolve_item<G: SeedGenerator>(
+        &self,
+        item: ItemId,
+        state: &PredictionGameState,
+        rng: &mut Rng,
+    ) -> Result<DropReward> {
+        // Item queries are resolved before their item fields are applied.
+        // Logic found in `ItemQueryResolver.TryResolve()`.
+        let item = if item == item_id!("RANDOM_BASE_SEASON_ITEM") {
+            let seed_addition = rng.next_i32();
+            forage::random_base_season_item::<G>(state, state.season(), seed_addition)
+        } else if item == items::DISH_OF_THE_DAY {
+            dish_of_the_day::predict_dish_of_the_day::<G>(state)?.item
+        } else {
+            item
+        };
+
         // This is the quanity logic from ItemQueryResolve.ApplyItemfields.
         let min_stack_size = self.min_stack;
         let max_stack_size = self.max_stack;
@@ -154,11 +381,7 @@ impl Drop {
         } else {
             1
         };
-        if *item == item_id!("RANDOM_BASE_SEASON_ITEM") {
-            let _ = rng.next_double();
-        }
-
-        Ok(DropReward::new(item.clone(), stack_size as u32))
+        Ok(DropReward::new(item, stack_size as u32))
     }
 }
 
@@ -168,7 +391,11 @@ impl TryFrom<&GenericSpawnItemDataWithCondition> for Drop {
     fn try_from(
         drop: &GenericSpawnItemDataWithCondition,
     ) -> std::result::Result<Self, Self::Error> {
-        let condition = drop.condition.clone();
+        let condition = drop
+            .condition
+            .as_ref()
+            .map(|condition| GameStateQuery::parse(condition))
+            .transpose()?;
         let min_stack = drop.parent.min_stack;
         let max_stack = drop.parent.max_stack;
         let drop: Result<DropItems> = {
@@ -195,7 +422,7 @@ impl TryFrom<&GenericSpawnItemDataWithCondition> for Drop {
         };
 
         Ok(Self {
-            condition: condition.map(|condition| HashedString::new(&condition)),
+            condition,
             min_stack,
             max_stack,
             drop: drop?,
@@ -203,3 +430,64 @@ impl TryFrom<&GenericSpawnItemDataWithCondition> for Drop {
     }
 }
 //pub use geode::{Geode, GeodeType};
+
+#[cfg(test)]
+mod tests {
+    use crate::save::load_test_save;
+
+    use super::*;
+
+    #[test]
+    fn state_from_save() {
+        let save = load_test_save("ChimkinHill_370550379");
+        let (state, undetermined) = PredictionGameState::from_save(&save);
+
+        assert_eq!(state.game_id, 370550379);
+        assert_eq!(state.days_played, 21);
+        assert_eq!(state.steps_taken, 19596);
+        assert_eq!(state.deepest_mine_level, 120);
+        assert_eq!(state.trash_cans_checked, 7);
+        assert_eq!(state.lost_books_found, 2);
+        assert!(!state.qi_beans_quest_active);
+        assert!(!state.cc_pantry_complete);
+
+        assert_eq!(undetermined, vec!["rain_totem_context"]);
+        assert!(!state.has_fairy_rose);
+    }
+
+    #[test]
+    fn dates_start_on_day_one() {
+        let date = |days_played| {
+            let state = PredictionGameState {
+                days_played,
+                ..Default::default()
+            };
+            (state.year(), state.season(), state.day_of_month())
+        };
+
+        assert_eq!(date(0), (1, Season::Spring, 1));
+        assert_eq!(date(1), (1, Season::Spring, 1));
+        assert_eq!(date(28 * 5 + 3), (2, Season::Summer, 3));
+    }
+
+    #[test]
+    fn stats_missing_from_old_saves_are_undetermined() {
+        // A 1.5.6 save which has no 1.6 stats and has never checked a trash
+        // can.
+        let save = load_test_save("testrain_327653833");
+        let (state, undetermined) = PredictionGameState::from_save(&save);
+
+        assert_eq!(state.steps_taken, 2);
+        assert_eq!(state.trash_cans_checked, 0);
+        assert_eq!(
+            undetermined,
+            vec![
+                "rain_totem_context",
+                "has_trash_book",
+                "trash_cans_checked",
+                "has_farming_mastery",
+                "mystery_boxes_opened",
+            ]
+        );
+    }
+}
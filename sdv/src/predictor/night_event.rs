@@ -1,3 +1,5 @@
+use std::{marker::PhantomData, ops::Range};
+
 use serde::{Deserialize, Serialize};
 
 use crate::{
@@ -14,14 +16,22 @@ pub enum NightEvent {
     RacoonStump,
     Fairy,
     Witch,
+    /// Sound in the night that leaves a meteorite on the farm.
     Meteorite,
+    /// Sound in the night that leaves a stone owl on the farm.
     Owl,
+    /// Sound in the night where a UFO leaves a strange capsule on the farm.
     Capsule,
+    /// Sound in the night the day before the railroad opens.
+    Earthquake,
     None,
 }
 
+// `Game1.stats.DaysPlayed` of the morning after the earthquake (Summer 3).
+const EARTHQUAKE_DAYS_PLAYED: u32 = 31;
+
 const fn season(days_played: u32) -> Season {
-    match ((days_played - 1) / 28) % 4 {
+    match (days_played.saturating_sub(1) / 28) % 4 {
         0 => Season::Spring,
         1 => Season::Summer,
         2 => Season::Fall,
@@ -30,20 +40,48 @@ const fn season(days_played: u32) -> Season {
 }
 
 const fn day_of_month(days_played: u32) -> u32 {
-    (days_played - 1) % 28 + 1
+    days_played.saturating_sub(1) % 28 + 1
 }
 
 const fn year(days_played: u32) -> u32 {
-    (days_played - 1) / (28 * 4) + 1
+    days_played.saturating_sub(1) / (28 * 4) + 1
 }
 
-/// Predict a night event based on game state.
-pub fn predict_night_event<G: SeedGenerator>(state: &mut PredictionGameState) -> NightEvent {
+/// Predict the night event at the end of `state.days_played`.
+///
+/// Returns the event along with the state for the next night.  One-shot
+/// flags like `has_mail_got_capsule` are set in the returned state and
+/// `has_fairy_rose` is cleared.
+///
+/// Events that take precedence over the random ones such as community center
+/// world changes and weddings are not modeled.  Animal births are not either:
+/// they are picked by `Utility.pickPersonalFarmEvent()` from the player's own
+/// seed and depend on the farm's barns and coops, which aren't tracked.
+pub fn predict_night_event<G: SeedGenerator>(
+    state: &PredictionGameState,
+) -> (NightEvent, PredictionGameState) {
+    let mut next_state = state.clone();
+    next_state.has_fairy_rose = false;
+    let event = pick_night_event::<G>(state, &mut next_state);
+    (event, next_state)
+}
+
+fn pick_night_event<G: SeedGenerator>(
+    state: &PredictionGameState,
+    next_state: &mut PredictionGameState,
+) -> NightEvent {
     // Logic appears in `Utility.pickFarmEvent()`
 
     // Night events are calculated at the end of the day after the `days_played`
     // is already incremented so we need to create our seeds off of `days_played + 1`.
     let days_played = state.days_played + 1;
+
+    // The earthquake is checked before any rolls and before the raccoon
+    // stump.
+    if days_played == EARTHQUAKE_DAYS_PLAYED {
+        return NightEvent::Earthquake;
+    }
+
     let mut random = Rng::new(generate_day_save_seed!(
         G,
         days_played,
@@ -61,13 +99,12 @@ pub fn predict_night_event<G: SeedGenerator>(state: &mut PredictionGameState) ->
     // The below checks are taken from the game and are fairly self explainitory.
 
     if state.cc_pantry_complete && random.next_double() < 0.1 && !state.raccoon_tree_fallen {
-        state.raccoon_tree_fallen = true;
+        next_state.raccoon_tree_fallen = true;
         return NightEvent::RacoonStump;
     }
 
     // If a fairy rose matured tonight, there's a buf to fairy chance.
     let fairy_chance = 0.01 + if state.has_fairy_rose { 0.007 } else { 0.0 };
-    state.has_fairy_rose = false;
     if random.next_double() < fairy_chance
         && season(days_played) != Season::Winter
         && day_of_month(days_played) != 1
@@ -89,13 +126,47 @@ pub fn predict_night_event<G: SeedGenerator>(state: &mut PredictionGameState) ->
 
     if random.next_double() < 0.008 && year(days_played) > 1 && !state.has_mail_got_capsule {
         // Only one capsule event per save.
-        state.has_mail_got_capsule = true;
+        next_state.has_mail_got_capsule = true;
         return NightEvent::Capsule;
     }
 
     NightEvent::None
 }
 
+/// Iterator over the night events of a range of days.
+///
+/// Yields the `days_played` of each night along with its event.  State
+/// changed by an event is carried forward to the following nights.
+pub struct NightEvents<G: SeedGenerator> {
+    state: PredictionGameState,
+    days: Range<u32>,
+    _generator: PhantomData<G>,
+}
+
+impl<G: SeedGenerator> NightEvents<G> {
+    /// Predict the nights at the end of each day in `days`.  `state` is the
+    /// game state at the start of the first night.
+    pub fn new(state: PredictionGameState, days: Range<u32>) -> Self {
+        Self {
+            state,
+            days,
+            _generator: PhantomData,
+        }
+    }
+}
+
+impl<G: SeedGenerator> Iterator for NightEvents<G> {
+    type Item = (u32, NightEvent);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let days_played = self.days.next()?;
+        self.state.days_played = days_played;
+        let (event, state) = predict_night_event::<G>(&self.state);
+        self.state = state;
+        Some((days_played, event))
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -111,9 +182,10 @@ mod tests {
         };
         for day in 1..(2 * 4 * 28) {
             state.days_played = day;
-            let event = predict_night_event::<HashedSeedGenerator>(&mut state);
+            let (event, next_state) = predict_night_event::<HashedSeedGenerator>(&state);
             // Data verified with mouseypounds which seems to be a day off.
             let expected = match (state.year(), state.season(), state.day_of_month()) {
+                (1, Season::Summer, 2) => NightEvent::Earthquake,
                 (1, Season::Spring, 23) => NightEvent::Meteorite,
                 (1, Season::Summer, 8) => NightEvent::Meteorite,
                 (1, Season::Fall, 11) => NightEvent::Witch,
@@ -126,6 +198,73 @@ mod tests {
                 _ => NightEvent::None,
             };
             assert_eq!(event, expected, "{day}");
+            state = next_state;
         }
     }
+
+    #[test]
+    fn night_events_carry_state_forward() {
+        let state = PredictionGameState {
+            game_id: 7269403,
+            ..Default::default()
+        };
+        let events: Vec<(u32, NightEvent)> =
+            NightEvents::<HashedSeedGenerator>::new(state.clone(), 1..(2 * 4 * 28))
+                .filter(|(_, event)| *event != NightEvent::None)
+                .collect();
+        assert_eq!(
+            events,
+            vec![
+                (23, NightEvent::Meteorite),
+                (30, NightEvent::Earthquake),
+                (36, NightEvent::Meteorite),
+                (67, NightEvent::Witch),
+                (124, NightEvent::Meteorite),
+                (141, NightEvent::Witch),
+                (150, NightEvent::Witch),
+                (172, NightEvent::Capsule),
+                (174, NightEvent::Fairy),
+                (187, NightEvent::Meteorite),
+            ]
+        );
+
+        // The capsule is only found once per save.
+        let state = PredictionGameState {
+            has_mail_got_capsule: true,
+            ..state
+        };
+        assert!(
+            NightEvents::<HashedSeedGenerator>::new(state, 1..(2 * 4 * 28))
+                .all(|(_, event)| event != NightEvent::Capsule)
+        );
+    }
+
+    #[test]
+    fn earthquake_takes_precedence_over_raccoon_stump() {
+        let game_id = 254546224;
+
+        // The raccoon stump roll passes on the earthquake night for this game.
+        let mut random = Rng::new(generate_day_save_seed!(
+            HashedSeedGenerator,
+            EARTHQUAKE_DAYS_PLAYED,
+            game_id,
+            0.0,
+            0.0,
+            0.0
+        ));
+        for _ in 0..10 {
+            random.next_double();
+        }
+        assert!(random.next_double() < 0.1);
+
+        let state = PredictionGameState {
+            game_id,
+            days_played: EARTHQUAKE_DAYS_PLAYED - 1,
+            cc_pantry_complete: true,
+            ..Default::default()
+        };
+        let (event, next_state) = predict_night_event::<HashedSeedGenerator>(&state);
+        assert_eq!(event, NightEvent::Earthquake);
+        assert!(!next_state.raccoon_tree_fallen);
+    }
 }
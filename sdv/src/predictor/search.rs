@@ -0,0 +1,424 @@
+//! Parallel seed search.
+//!
+//! A [SeedSearch] scans a range of `game_id`s looking for seeds that satisfy
+//! a [Predicate] over [PredictionGameState].  The range is processed in
+//! chunks, each of which is checked in parallel.  After every chunk a
+//! [SearchCheckpoint] is handed to a progress callback.  Checkpoints can be
+//! serialized and later passed back to [SeedSearch::resume] to pick up where
+//! a search left off.
+
+use std::ops::Range;
+
+use anyhow::{anyhow, Result};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{ItemId, Weather},
+    rng::SeedGenerator,
+};
+
+use super::{
+    garbage::{predict_garbage, GarbageCan},
+    geode::{predict_single_geode, Geode},
+    night_event::{predict_night_event, NightEvent},
+    weather::{predict_weather, WeatherLocation},
+    PredictionGameState,
+};
+
+/// A test of a single candidate seed.
+///
+/// `state` has its `game_id` set to the candidate.  Predicates that need to
+/// look at other days or geode counts should modify a copy of the state.
+///
+/// Any `Fn(&PredictionGameState) -> Result<bool>` closure can be used as a
+/// predicate.
+pub trait Predicate<G: SeedGenerator>: Send + Sync {
+    fn test(&self, state: &PredictionGameState) -> Result<bool>;
+}
+
+impl<G, F> Predicate<G> for F
+where
+    G: SeedGenerator,
+    F: Fn(&PredictionGameState) -> Result<bool> + Send + Sync,
+{
+    fn test(&self, state: &PredictionGameState) -> Result<bool> {
+        self(state)
+    }
+}
+
+/// Passes if all of its predicates pass.  Evaluation stops at the first
+/// predicate that fails so cheap predicates should be listed first.
+pub struct All<G: SeedGenerator>(pub Vec<Box<dyn Predicate<G>>>);
+
+impl<G: SeedGenerator> Predicate<G> for All<G> {
+    fn test(&self, state: &PredictionGameState) -> Result<bool> {
+        for predicate in &self.0 {
+            if !predicate.test(state)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Passes if any of its predicates pass.
+pub struct Any<G: SeedGenerator>(pub Vec<Box<dyn Predicate<G>>>);
+
+impl<G: SeedGenerator> Predicate<G> for Any<G> {
+    fn test(&self, state: &PredictionGameState) -> Result<bool> {
+        for predicate in &self.0 {
+            if predicate.test(state)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// Passes if its predicate fails.
+pub struct Not<G: SeedGenerator>(pub Box<dyn Predicate<G>>);
+
+impl<G: SeedGenerator> Predicate<G> for Not<G> {
+    fn test(&self, state: &PredictionGameState) -> Result<bool> {
+        Ok(!self.0.test(state)?)
+    }
+}
+
+/// Passes if the chance of `weather` on `days_played` is at least
+/// `min_chance`.
+pub struct WeatherIs {
+    pub location: WeatherLocation,
+    pub days_played: u32,
+    pub weather: Weather,
+    pub min_chance: f64,
+}
+
+impl<G: SeedGenerator> Predicate<G> for WeatherIs {
+    fn test(&self, state: &PredictionGameState) -> Result<bool> {
+        let state = PredictionGameState {
+            days_played: self.days_played,
+            ..state.clone()
+        };
+        let prediction = predict_weather::<G>(&self.location, &state)?;
+        Ok(prediction.chance(&self.weather) >= self.min_chance)
+    }
+}
+
+/// Passes if the night of `days_played` has `event`.
+pub struct NightEventIs {
+    pub days_played: u32,
+    pub event: NightEvent,
+}
+
+impl<G: SeedGenerator> Predicate<G> for NightEventIs {
+    fn test(&self, state: &PredictionGameState) -> Result<bool> {
+        let state = PredictionGameState {
+            days_played: self.days_played,
+            ..state.clone()
+        };
+        Ok(predict_night_event::<G>(&state).0 == self.event)
+    }
+}
+
+/// Passes if the next geodes cracked produce `items` in order.
+///
+/// The first geode is predicted with the state's `geodes_cracked`, or
+/// `mystery_boxes_opened` for mystery boxes.  Items that are `None` match any
+/// drop.
+pub struct GeodeSequence {
+    pub geode: Geode,
+    pub items: Vec<Option<ItemId>>,
+}
+
+impl<G: SeedGenerator> Predicate<G> for GeodeSequence {
+    fn test(&self, state: &PredictionGameState) -> Result<bool> {
+        let mut state = state.clone();
+        for item in &self.items {
+            let reward = predict_single_geode::<G>(&self.geode, &state)?;
+            if item.as_ref().is_some_and(|item| *item != reward.item) {
+                return Ok(false);
+            }
+            if self.geode.ty().is_mystery_box() {
+                state.mystery_boxes_opened += 1;
+            } else {
+                state.geodes_cracked += 1;
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Passes if `can` contains `item` on `days_played` given the state's daily
+/// luck.
+pub struct GarbageContains {
+    pub can: GarbageCan,
+    pub days_played: u32,
+    pub item: ItemId,
+}
+
+impl<G: SeedGenerator> Predicate<G> for GarbageContains {
+    fn test(&self, state: &PredictionGameState) -> Result<bool> {
+        let state = PredictionGameState {
+            days_played: self.days_played,
+            ..state.clone()
+        };
+        Ok(predict_garbage::<G>(&self.can, &state)?
+            .is_some_and(|(reward, _)| reward.item == self.item))
+    }
+}
+
+/// Progress of a search.
+///
+/// Checkpoints are serializable so that long running searches can be
+/// persisted and resumed with [SeedSearch::resume].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SearchCheckpoint {
+    /// The full range of `game_id`s being searched.
+    pub range: Range<u32>,
+
+    /// The next `game_id` to check.
+    pub next: u32,
+
+    /// Matching `game_id`s found so far, in ascending order.
+    pub results: Vec<u32>,
+}
+
+impl SearchCheckpoint {
+    pub fn new(range: Range<u32>) -> Self {
+        Self {
+            next: range.start,
+            range,
+            results: Vec::new(),
+        }
+    }
+
+    /// Number of `game_id`s checked so far.
+    pub fn checked(&self) -> u64 {
+        (self.next - self.range.start) as u64
+    }
+
+    /// Total number of `game_id`s in the search.
+    pub fn total(&self) -> u64 {
+        self.range.len() as u64
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.next >= self.range.end
+    }
+}
+
+pub struct SeedSearch<G: SeedGenerator> {
+    /// Base state for every candidate.  `game_id` is overwritten.
+    pub state: PredictionGameState,
+    pub predicate: Box<dyn Predicate<G>>,
+
+    /// Stop once this many matches have been found.
+    pub max_results: Option<usize>,
+
+    /// Number of `game_id`s checked between checkpoints.
+    pub chunk_size: u32,
+}
+
+impl<G: SeedGenerator> SeedSearch<G> {
+    pub const DEFAULT_CHUNK_SIZE: u32 = 1 << 16;
+
+    pub fn new(state: PredictionGameState, predicate: impl Predicate<G> + 'static) -> Self {
+        Self {
+            state,
+            predicate: Box::new(predicate),
+            max_results: None,
+            chunk_size: Self::DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    /// Search `range` for matching `game_id`s.
+    pub fn run(&self, range: Range<u32>) -> Result<SearchCheckpoint> {
+        self.resume(SearchCheckpoint::new(range), |_| true)
+    }
+
+    /// Continue a search from `checkpoint`.
+    ///
+    /// `progress` is called after each chunk is processed and may return
+    /// `false` to stop the search early.  The returned checkpoint reflects
+    /// how far the search got and can be resumed again if it is not complete.
+    pub fn resume<F>(
+        &self,
+        mut checkpoint: SearchCheckpoint,
+        mut progress: F,
+    ) -> Result<SearchCheckpoint>
+    where
+        F: FnMut(&SearchCheckpoint) -> bool,
+    {
+        if self.chunk_size == 0 {
+            return Err(anyhow!("search chunk size must be non-zero"));
+        }
+
+        while !checkpoint.is_complete() && !self.has_enough_results(&checkpoint) {
+            let chunk_end = checkpoint
+                .next
+                .saturating_add(self.chunk_size)
+                .min(checkpoint.range.end);
+
+            let matches = (checkpoint.next..chunk_end)
+                .into_par_iter()
+                .map(|game_id| {
+                    let state = PredictionGameState {
+                        game_id,
+                        ..self.state.clone()
+                    };
+                    Ok(self.predicate.test(&state)?.then_some(game_id))
+                })
+                .collect::<Result<Vec<Option<u32>>>>()?;
+
+            checkpoint.results.extend(matches.into_iter().flatten());
+            checkpoint.next = chunk_end;
+
+            // Results past `max_results` are discarded and the checkpoint
+            // rewound so that resuming doesn't skip over any seeds.
+            if let Some(max_results) = self.max_results {
+                if checkpoint.results.len() > max_results {
+                    checkpoint.results.truncate(max_results);
+                    checkpoint.next = checkpoint.results[max_results - 1] + 1;
+                }
+            }
+
+            if !progress(&checkpoint) {
+                break;
+            }
+        }
+
+        Ok(checkpoint)
+    }
+
+    fn has_enough_results(&self, checkpoint: &SearchCheckpoint) -> bool {
+        self.max_results
+            .is_some_and(|max_results| checkpoint.results.len() >= max_results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{item_id, predictor::geode::GeodeType, rng::HashedSeedGenerator};
+
+    use super::*;
+
+    fn divisible_by(n: u32) -> impl Predicate<HashedSeedGenerator> {
+        move |state: &PredictionGameState| Ok(state.game_id.is_multiple_of(n))
+    }
+
+    #[test]
+    fn search_finds_matching_seeds() {
+        let mut search = SeedSearch::new(PredictionGameState::default(), divisible_by(7));
+        search.chunk_size = 10;
+        let checkpoint = search.run(0..50).unwrap();
+        assert!(checkpoint.is_complete());
+        assert_eq!(checkpoint.results, vec![0, 7, 14, 21, 28, 35, 42, 49]);
+    }
+
+    #[test]
+    fn predicates_compose() {
+        let predicate = All(vec![
+            Box::new(divisible_by(2)),
+            Box::new(Not(Box::new(Any(vec![
+                Box::new(divisible_by(3)),
+                Box::new(divisible_by(5)),
+            ])))),
+        ]);
+        let search = SeedSearch::new(PredictionGameState::default(), predicate);
+        assert_eq!(
+            search.run(0..30).unwrap().results,
+            vec![2, 4, 8, 14, 16, 22, 26, 28]
+        );
+    }
+
+    #[test]
+    fn search_stops_at_max_results() {
+        let mut search = SeedSearch::new(PredictionGameState::default(), divisible_by(3));
+        search.chunk_size = 100;
+        search.max_results = Some(4);
+        let checkpoint = search.run(1..1000).unwrap();
+        assert_eq!(checkpoint.results, vec![3, 6, 9, 12]);
+        assert_eq!(checkpoint.next, 13);
+        assert!(!checkpoint.is_complete());
+    }
+
+    #[test]
+    fn search_resumes_from_checkpoint() {
+        let mut search = SeedSearch::new(PredictionGameState::default(), divisible_by(11));
+        search.chunk_size = 16;
+
+        let mut chunks = 0;
+        let checkpoint = search
+            .resume(SearchCheckpoint::new(0..200), |_| {
+                chunks += 1;
+                chunks < 3
+            })
+            .unwrap();
+        assert_eq!(checkpoint.next, 48);
+        assert_eq!(checkpoint.checked(), 48);
+
+        let serialized = serde_json::to_string(&checkpoint).unwrap();
+        let checkpoint: SearchCheckpoint = serde_json::from_str(&serialized).unwrap();
+        let resumed = search.resume(checkpoint, |_| true).unwrap();
+        assert_eq!(resumed, search.run(0..200).unwrap());
+    }
+
+    #[test]
+    fn search_errors_are_propagated() {
+        let search = SeedSearch::<HashedSeedGenerator>::new(
+            PredictionGameState::default(),
+            |state: &PredictionGameState| {
+                if state.game_id == 5 {
+                    Err(anyhow!("bad seed"))
+                } else {
+                    Ok(false)
+                }
+            },
+        );
+        assert!(search.run(0..10).is_err());
+    }
+
+    #[test]
+    fn night_event_search_finds_known_seed() {
+        // See `night_event::tests`.
+        let search = SeedSearch::new(
+            PredictionGameState::default(),
+            All::<HashedSeedGenerator>(vec![
+                Box::new(NightEventIs {
+                    days_played: 23,
+                    event: NightEvent::Meteorite,
+                }),
+                Box::new(NightEventIs {
+                    days_played: 28 + 8,
+                    event: NightEvent::Meteorite,
+                }),
+            ]),
+        );
+        assert!(search
+            .run(7269000..7270000)
+            .unwrap()
+            .results
+            .contains(&7269403));
+    }
+
+    #[test]
+    fn geode_sequence_counts_mystery_boxes_opened() {
+        // See `geode::tests::mystery_box_common_drops_are_predicted`.
+        let sequence = GeodeSequence {
+            geode: Geode::mystery_box(GeodeType::MysteryBox),
+            items: vec![
+                Some(item_id!("(O)709")),
+                Some(item_id!("(O)395")),
+                Some(item_id!("(O)287")),
+            ],
+        };
+        let state = PredictionGameState {
+            game_id: 7269403,
+            multiplayer_id: -7347405514601242418i64,
+            mystery_boxes_opened: 7,
+            ..Default::default()
+        };
+        assert!(Predicate::<HashedSeedGenerator>::test(&sequence, &state).unwrap());
+    }
+}
@@ -0,0 +1,1006 @@
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{ItemId, ModificationType, ObjectCategory, QuantityModifier, QuantityModifierMode},
+    gamedata::shop::{LimitedStockMode, ShopData, ShopItemData},
+    query::{GameStateQuery, PlayerTarget, QueryContext, TargetItem},
+    rng::{Rng, SeedGenerator},
+    GameData,
+};
+
+use super::{Drop, DropReward, PredictionGameState};
+
+type Modifiers = Vec<(Option<GameStateQuery>, QuantityModifier)>;
+
+#[derive(Clone, Debug)]
+enum ShopItemSource {
+    // A single item or a random item picked by `Drop`.
+    Item,
+    // `RandomItemId` with `AvoidRepeat` set.  The list is shuffled and the
+    // first item not already in stock is used.
+    AvoidRepeat(Vec<ItemId>),
+    RandomItems(RandomItems),
+    // An item query other than `DISH_OF_THE_DAY`, `RANDOM_BASE_SEASON_ITEM`
+    // or a supported `RANDOM_ITEMS`.
+    Query(String),
+}
+
+// A `RANDOM_ITEMS` query for objects, big craftables or furniture.
+//
+// Logic found in `ItemQueryResolver.DefaultResolvers.RANDOM_ITEMS()`.  The
+// game orders every item of the type by `random.Next()` before filtering them
+// so the rng is pulled once per item in the type's data.
+#[derive(Clone, Debug)]
+struct RandomItems {
+    item_count: usize,
+    // Items that pass the query's ID range and flags along with their index
+    // in the type's data.
+    candidates: Vec<(usize, TargetItem, ItemId)>,
+}
+
+impl RandomItems {
+    // Returns `None` for item types that aren't supported.
+    fn new(query: &str, game_data: &GameData) -> Result<Option<Self>> {
+        let mut args = query.split_whitespace().skip(1);
+        let ty = args.next().unwrap_or_default().to_ascii_uppercase();
+
+        let mut range = Vec::new();
+        let mut is_random_sale = false;
+        let mut require_price = false;
+        for arg in args {
+            match arg.to_ascii_lowercase().as_str() {
+                "@israndomsale" => is_random_sale = true,
+                "@requireprice" => require_price = true,
+                _ => range.push(
+                    arg.parse::<i32>()
+                        .map_err(|_| anyhow!("can't parse {} argument {}", query, arg))?,
+                ),
+            }
+        }
+        let min = range.first().copied();
+        let max = range.get(1).copied();
+
+        // Each item's unqualified ID, category, object type, whether it's
+        // excluded from random sales and price.
+        let items: Vec<(&str, ObjectCategory, Option<String>, bool, i32)> = match ty.as_str() {
+            "(O)" => game_data
+                .objects
+                .iter()
+                .map(|(id, object)| {
+                    (
+                        id.as_str(),
+                        object.category,
+                        Some(format!("{:?}", object.ty)),
+                        object.exclude_from_random_sale,
+                        object.price,
+                    )
+                })
+                .collect(),
+            "(BC)" => game_data
+                .big_craftables
+                .iter()
+                .map(|(id, object)| {
+                    (
+                        id.as_str(),
+                        ObjectCategory::BigCraftable,
+                        None,
+                        false,
+                        object.price,
+                    )
+                })
+                .collect(),
+            "(F)" => game_data
+                .furniture
+                .iter()
+                .map(|(id, furniture)| {
+                    (
+                        id.as_str(),
+                        ObjectCategory::Furniture,
+                        None,
+                        furniture.off_limits_for_random_sale,
+                        furniture.price,
+                    )
+                })
+                .collect(),
+            _ => return Ok(None),
+        };
+
+        let mut candidates = Vec::new();
+        for (index, (id, category, object_type, excluded, price)) in items.iter().enumerate() {
+            if min.is_some() || max.is_some() {
+                let Ok(numeric_id) = id.parse::<i32>() else {
+                    continue;
+                };
+                if min.is_some_and(|min| numeric_id < min)
+                    || max.is_some_and(|max| numeric_id > max)
+                {
+                    continue;
+                }
+            }
+            if (is_random_sale && *excluded) || (require_price && *price <= 0) {
+                continue;
+            }
+
+            let qualified_id = format!("{ty}{id}");
+            let item = qualified_id.parse::<ItemId>()?;
+            candidates.push((
+                index,
+                TargetItem {
+                    qualified_id,
+                    category: *category as i32,
+                    object_type: object_type.clone(),
+                },
+                item,
+            ));
+        }
+
+        Ok(Some(Self {
+            item_count: items.len(),
+            candidates,
+        }))
+    }
+
+    // The candidates in the order the game's shuffle puts them.
+    fn shuffled(&self, rng: &mut Rng) -> Vec<&(usize, TargetItem, ItemId)> {
+        // `OrderBy` computes every key before sorting and is a stable sort.
+        let keys: Vec<i32> = (0..self.item_count).map(|_| rng.next_i32()).collect();
+        let mut candidates: Vec<_> = self.candidates.iter().collect();
+        candidates.sort_by_key(|(index, _, _)| keys[*index]);
+        candidates
+    }
+}
+
+// Evaluates `PerItemCondition` queries against an item.
+struct ItemQueryContext<'a> {
+    state: &'a PredictionGameState,
+    item: &'a TargetItem,
+}
+
+impl QueryContext for ItemQueryContext<'_> {
+    fn game_id(&self) -> u32 {
+        self.state.game_id()
+    }
+
+    fn days_played(&self) -> u32 {
+        self.state.days_played()
+    }
+
+    fn daily_luck(&self) -> f64 {
+        self.state.daily_luck()
+    }
+
+    fn target_item(&self) -> Option<&TargetItem> {
+        Some(self.item)
+    }
+
+    fn player_stat(&self, player: &PlayerTarget, stat: &str) -> Option<u32> {
+        self.state.player_stat(player, stat)
+    }
+
+    fn player_has_mail(&self, player: &PlayerTarget, mail: &str) -> Option<bool> {
+        self.state.player_has_mail(player, mail)
+    }
+
+    fn player_has_seen_event(&self, player: &PlayerTarget, event: &str) -> Option<bool> {
+        self.state.player_has_seen_event(player, event)
+    }
+
+    fn player_special_order_rule_active(&self, player: &PlayerTarget, rule: &str) -> Option<bool> {
+        self.state.player_special_order_rule_active(player, rule)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct ShopEntry {
+    drop: Drop,
+    source: ShopItemSource,
+    per_item_condition: Option<GameStateQuery>,
+    max_items: Option<usize>,
+    avoid_repeat: bool,
+    is_recipe: bool,
+    trade_item: Option<(ItemId, u32)>,
+    price: i32,
+    use_object_data_price: bool,
+    ignore_shop_price_modifiers: bool,
+    price_modifiers: Modifiers,
+    price_modifier_mode: QuantityModifierMode,
+    available_stock: i32,
+    available_stock_limit: LimitedStockMode,
+    available_stock_modifiers: Modifiers,
+    available_stock_modifier_mode: QuantityModifierMode,
+}
+
+fn parse_modifiers(modifiers: &Option<Vec<QuantityModifier>>) -> Result<Modifiers> {
+    modifiers
+        .iter()
+        .flatten()
+        .map(|modifier| {
+            let condition = modifier
+                .condition
+                .as_ref()
+                .map(|condition| GameStateQuery::parse(condition))
+                .transpose()?;
+            Ok((condition, modifier.clone()))
+        })
+        .collect()
+}
+
+// Item queries are all caps and either take arguments or are one of a handful
+// of argumentless queries.  `Drop` resolves the two that don't depend on
+// anything but the game state.
+fn item_query(id: &str) -> Option<&str> {
+    let name = id.split_whitespace().next()?;
+    let is_query = name.contains('_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_');
+    match name {
+        "DISH_OF_THE_DAY" | "RANDOM_BASE_SEASON_ITEM" => None,
+        _ if is_query => Some(name),
+        _ => None,
+    }
+}
+
+impl ShopEntry {
+    fn new(data: &ShopItemData, game_data: &GameData) -> Result<Self> {
+        let spawn = &data.parent.parent;
+        let random_item_id = spawn.random_item_id.as_ref().filter(|ids| !ids.is_empty());
+
+        // Like artifact spots, item queries are split out and `Drop` is only
+        // used for the condition and stack size.
+        let mut parent = data.parent.clone();
+        let source = match (
+            random_item_id,
+            spawn.item_id.as_deref().and_then(item_query),
+        ) {
+            (Some(ids), _) if data.avoid_repeat => ShopItemSource::AvoidRepeat(
+                ids.iter()
+                    .map(|id| id.parse::<ItemId>())
+                    .collect::<Result<Vec<_>>>()?,
+            ),
+            (Some(_), _) => ShopItemSource::Item,
+            (None, Some(query)) => {
+                let random_items = match query {
+                    "RANDOM_ITEMS" => {
+                        RandomItems::new(spawn.item_id.as_deref().unwrap_or_default(), game_data)?
+                    }
+                    _ => None,
+                };
+                parent.parent.item_id = Some(query.to_string());
+                random_items.map_or_else(
+                    || ShopItemSource::Query(query.to_string()),
+                    ShopItemSource::RandomItems,
+                )
+            }
+            (None, None) => ShopItemSource::Item,
+        };
+
+        // Logic found in `ShopBuilder.GetShopStock()`.  Negative trade item
+        // amounts disable the trade.
+        let trade_item = match &data.trade_item_id {
+            Some(id) if data.trade_item_amount >= 0 => {
+                Some((id.parse::<ItemId>()?, data.trade_item_amount as u32))
+            }
+            _ => None,
+        };
+
+        let per_item_condition = spawn
+            .per_item_condition
+            .as_ref()
+            .map(|condition| GameStateQuery::parse(condition))
+            .transpose()?;
+
+        Ok(Self {
+            drop: Drop::try_from(&parent)?,
+            source,
+            per_item_condition,
+            max_items: spawn.max_items.map(|max| max.max(0) as usize),
+            avoid_repeat: data.avoid_repeat,
+            is_recipe: spawn.is_recipe,
+            trade_item,
+            price: data.price,
+            use_object_data_price: data.use_object_data_price,
+            ignore_shop_price_modifiers: data.ignore_shop_price_modifiers,
+            price_modifiers: parse_modifiers(&data.price_modifiers)?,
+            price_modifier_mode: data.price_modifier_mode.clone(),
+            available_stock: data.available_stock,
+            available_stock_limit: data.available_stock_limit.clone(),
+            available_stock_modifiers: parse_modifiers(&data.available_stock_modifiers)?,
+            available_stock_modifier_mode: data.available_stock_modifier_mode.clone(),
+        })
+    }
+}
+
+/// Cached data for a single shop.
+#[derive(Clone, Debug)]
+pub struct Shop {
+    id: String,
+    entries: Vec<ShopEntry>,
+    price_modifiers: Modifiers,
+    price_modifier_mode: QuantityModifierMode,
+    // Sale prices of items that don't set their own price, as returned by
+    // `Item.salePrice()`.
+    sale_prices: HashMap<ItemId, i32>,
+    object_prices: HashMap<ItemId, i32>,
+}
+
+impl Shop {
+    pub fn new(id: &str, game_data: &GameData) -> Result<Self> {
+        let data = game_data
+            .shops
+            .get(id)
+            .ok_or_else(|| anyhow!("shop {} not found", id))?;
+        Self::from_data(data, game_data)
+    }
+
+    fn from_data(data: &ShopData, game_data: &GameData) -> Result<Self> {
+        let entries = data
+            .items
+            .iter()
+            .flatten()
+            .map(|item| ShopEntry::new(item, game_data))
+            .collect::<Result<Vec<_>>>()?;
+
+        // TODO: `Object.salePrice()` special cases a few items like seeds and
+        // ores by year and all prices are multiplied by the save's profit
+        // margin.  Neither are modeled.
+        let objects = game_data
+            .objects
+            .iter()
+            .map(|(id, object)| Ok((format!("(O){id}").parse::<ItemId>()?, object.price)))
+            .collect::<Result<HashMap<_, _>>>()?;
+        let big_craftables = game_data
+            .big_craftables
+            .iter()
+            .map(|(id, object)| Ok((format!("(BC){id}").parse::<ItemId>()?, object.price * 2)));
+        let furniture = game_data
+            .furniture
+            .iter()
+            .map(|(id, furniture)| Ok((format!("(F){id}").parse::<ItemId>()?, furniture.price)));
+        let sale_prices = objects
+            .iter()
+            .map(|(id, price)| Ok((id.clone(), price * 2)))
+            .chain(big_craftables)
+            .chain(furniture)
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        Ok(Self {
+            id: data.id.clone(),
+            entries,
+            price_modifiers: parse_modifiers(&data.price_modifiers)?,
+            price_modifier_mode: data.price_modifier_mode.clone(),
+            sale_prices,
+            object_prices: objects,
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    // Logic found in `ShopBuilder.GetBasePrice()`.
+    fn base_price(&self, entry: &ShopEntry, item: &ItemId) -> Option<i32> {
+        if entry.price >= 0 {
+            return Some(entry.price);
+        }
+        if entry.use_object_data_price {
+            if let Some(price) = self.object_prices.get(item) {
+                return Some(*price);
+            }
+        }
+        self.sale_prices.get(item).copied()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ShopStockItem {
+    pub item: ItemId,
+
+    /// Stack size of each purchase.
+    pub quantity: u32,
+
+    /// `None` when the price can't be determined from game data.
+    pub price: Option<i32>,
+
+    /// Item and amount traded for each purchase in addition to the price.
+    pub trade_item: Option<DropReward>,
+
+    /// Number of purchases available or `None` if unlimited.
+    pub stock: Option<u32>,
+
+    /// How purchases count against `stock`.
+    pub stock_limit: LimitedStockMode,
+
+    /// Set when the item's condition could not be evaluated.  The condition
+    /// is assumed to pass.
+    pub uncertain: bool,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ShopStockPrediction {
+    pub items: Vec<ShopStockItem>,
+
+    /// Set to the first item query found whose random number generator pulls
+    /// are not modeled.  Any further items can not be predicted.
+    pub unsupported_query: Option<String>,
+}
+
+// Logic found in `Utility.ApplyQuantityModifiers()`.
+fn apply_quantity_modifiers<G: SeedGenerator>(
+    value: f32,
+    modifiers: &[(Option<GameStateQuery>, QuantityModifier)],
+    mode: &QuantityModifierMode,
+    state: &PredictionGameState,
+    rng: &mut Rng,
+) -> Result<f32> {
+    let mut new_value: Option<f32> = None;
+    for (condition, modifier) in modifiers {
+        // The amount is picked before the condition is checked.
+        let amount = match &modifier.random_amount {
+            Some(amounts) if !amounts.is_empty() => *rng.chooose_from(amounts),
+            _ => modifier.amount,
+        };
+
+        if let Some(condition) = condition {
+            if !condition
+                .evaluate::<G, _>(state, Some(rng))?
+                .passes_with_luck(state.daily_luck)
+            {
+                continue;
+            }
+        }
+
+        // Logic found in `QuantityModifier.Apply()`.
+        let apply = |value: f32| match modifier.modification {
+            ModificationType::Add => value + amount,
+            ModificationType::Subtract => value - amount,
+            ModificationType::Multiply => value * amount,
+            ModificationType::Divide => value / amount,
+            ModificationType::Set => amount,
+        };
+        new_value = Some(match mode {
+            QuantityModifierMode::Minimum => {
+                let applied = apply(value);
+                new_value.map_or(applied, |v| v.min(applied))
+            }
+            QuantityModifierMode::Maximum => {
+                let applied = apply(value);
+                new_value.map_or(applied, |v| v.max(applied))
+            }
+            QuantityModifierMode::Stack => apply(new_value.unwrap_or(value)),
+        });
+    }
+    Ok(new_value.unwrap_or(value))
+}
+
+// Resolve an entry's item.  Returns `None` if every candidate item is already
+// in stock.
+fn resolve_entry<G: SeedGenerator>(
+    entry: &ShopEntry,
+    state: &PredictionGameState,
+    stocked: &HashSet<ItemId>,
+    rng: &mut Rng,
+) -> Result<Option<DropReward>> {
+    // Logic found in `ItemQueryResolver.TryResolve()`.
+    let reward = match &entry.source {
+        ShopItemSource::AvoidRepeat(items) => {
+            let mut items = items.clone();
+            rng.shuffle(&mut items);
+            let Some(item) = items.into_iter().find(|item| !stocked.contains(item)) else {
+                return Ok(None);
+            };
+            entry.drop.try_resolve_item::<G>(item, state, rng)?
+        }
+        _ => entry.drop.try_resolve::<G>(state, rng)?,
+    };
+
+    if entry.avoid_repeat && stocked.contains(&reward.item) {
+        return Ok(None);
+    }
+    Ok(Some(reward))
+}
+
+// Resolve a `RANDOM_ITEMS` entry.  Returns `None` if an item's
+// `PerItemCondition` can't be evaluated.
+fn resolve_random_items<G: SeedGenerator>(
+    entry: &ShopEntry,
+    random_items: &RandomItems,
+    state: &PredictionGameState,
+    stocked: &HashSet<ItemId>,
+    rng: &mut Rng,
+) -> Result<Option<Vec<DropReward>>> {
+    // Logic found in `ItemQueryResolver.TryResolve()`.
+    let mut items = Vec::new();
+    for (_, target, item) in random_items.shuffled(rng) {
+        if entry.max_items.is_some_and(|max| items.len() >= max) {
+            break;
+        }
+        if entry.avoid_repeat && stocked.contains(item) {
+            continue;
+        }
+        if let Some(condition) = &entry.per_item_condition {
+            let context = ItemQueryContext {
+                state,
+                item: target,
+            };
+            match condition.evaluate::<G, _>(&context, Some(rng)) {
+                Ok(result) if result.passes_with_luck(state.daily_luck) => (),
+                Ok(_) => continue,
+                Err(_) => return Ok(None),
+            }
+        }
+        items.push(item.clone());
+    }
+
+    // Item fields are applied to every item before the shop prices them.
+    let rewards = items
+        .into_iter()
+        .map(|item| entry.drop.try_resolve_item::<G>(item, state, rng))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Some(rewards))
+}
+
+// Price and stock an item.  Logic found in `ShopBuilder.GetShopStock()`.
+fn stock_item<G: SeedGenerator>(
+    shop: &Shop,
+    entry: &ShopEntry,
+    reward: DropReward,
+    uncertain: bool,
+    state: &PredictionGameState,
+    rng: &mut Rng,
+) -> Result<ShopStockItem> {
+    // Prices are modified before the stock.  Both can pull from the rng.
+    let mut price = shop
+        .base_price(entry, &reward.item)
+        .map(|price| price as f32);
+    if let Some(base) = price {
+        let mut value = base;
+        if !entry.ignore_shop_price_modifiers {
+            value = apply_quantity_modifiers::<G>(
+                value,
+                &shop.price_modifiers,
+                &shop.price_modifier_mode,
+                state,
+                rng,
+            )?;
+        }
+        value = apply_quantity_modifiers::<G>(
+            value,
+            &entry.price_modifiers,
+            &entry.price_modifier_mode,
+            state,
+            rng,
+        )?;
+        price = Some(value);
+    }
+
+    // Recipes can only be bought once so their stock isn't modified.
+    let (stock, stock_limit) = if entry.is_recipe {
+        (Some(1), LimitedStockMode::None)
+    } else if entry.available_stock >= 0 {
+        let stock = apply_quantity_modifiers::<G>(
+            entry.available_stock as f32,
+            &entry.available_stock_modifiers,
+            &entry.available_stock_modifier_mode,
+            state,
+            rng,
+        )?;
+        (
+            Some(stock.max(0.0) as u32),
+            entry.available_stock_limit.clone(),
+        )
+    } else {
+        (None, entry.available_stock_limit.clone())
+    };
+    let quantity = if entry.is_recipe {
+        reward.quantity.min(1)
+    } else {
+        reward.quantity
+    };
+
+    Ok(ShopStockItem {
+        item: reward.item,
+        quantity,
+        price: price.map(|price| price as i32),
+        trade_item: entry
+            .trade_item
+            .as_ref()
+            .map(|(item, amount)| DropReward::new(item.clone(), *amount)),
+        stock,
+        stock_limit,
+        uncertain,
+    })
+}
+
+/// Predict the stock of `shop` on `state.days_played`.
+///
+/// Items bought earlier in the day and the save's profit margin are not taken
+/// into account.
+pub fn predict_shop_stock<G: SeedGenerator>(
+    shop: &Shop,
+    state: &PredictionGameState,
+) -> Result<ShopStockPrediction> {
+    // Logic found in `ShopBuilder.GetShopStock()`.
+    let mut rng = state.create_day_save_random::<G>(0.0, 0.0, 0.0);
+    let mut prediction = ShopStockPrediction::default();
+    let mut stocked = HashSet::new();
+
+    for entry in &shop.entries {
+        // The condition is checked with the shop's rng so its `RANDOM` rolls
+        // are part of the day's stock.
+        let uncertain = match entry.drop.evaluate_condition::<G, _>(state, Some(&mut rng)) {
+            Ok(result) if result.passes_with_luck(state.daily_luck) => false,
+            Ok(_) => continue,
+            Err(_) => true,
+        };
+
+        let rewards = match &entry.source {
+            ShopItemSource::Query(query) => {
+                prediction.unsupported_query = Some(query.clone());
+                return Ok(prediction);
+            }
+            ShopItemSource::RandomItems(random_items) => {
+                match resolve_random_items::<G>(entry, random_items, state, &stocked, &mut rng)? {
+                    Some(rewards) => rewards,
+                    None => {
+                        prediction.unsupported_query = Some("RANDOM_ITEMS".to_string());
+                        return Ok(prediction);
+                    }
+                }
+            }
+            _ => resolve_entry::<G>(entry, state, &stocked, &mut rng)?
+                .into_iter()
+                .collect(),
+        };
+
+        for reward in rewards {
+            let item = stock_item::<G>(shop, entry, reward, uncertain, state, &mut rng)?;
+            stocked.insert(item.item.clone());
+            prediction.items.push(item);
+        }
+    }
+
+    Ok(prediction)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{item_id, predictor::DropItems, rng::HashedSeedGenerator, save::load_test_save};
+
+    use super::*;
+
+    fn entry(source: ShopItemSource, drop: DropItems) -> ShopEntry {
+        ShopEntry {
+            drop: Drop {
+                condition: None,
+                min_stack: -1,
+                max_stack: -1,
+                drop,
+            },
+            source,
+            per_item_condition: None,
+            max_items: None,
+            avoid_repeat: true,
+            is_recipe: false,
+            trade_item: None,
+            price: 100,
+            use_object_data_price: false,
+            ignore_shop_price_modifiers: false,
+            price_modifiers: Vec::new(),
+            price_modifier_mode: QuantityModifierMode::Stack,
+            available_stock: 1,
+            available_stock_limit: LimitedStockMode::Global,
+            available_stock_modifiers: Vec::new(),
+            available_stock_modifier_mode: QuantityModifierMode::Stack,
+        }
+    }
+
+    fn shop(entries: Vec<ShopEntry>) -> Shop {
+        Shop {
+            id: "Test".to_string(),
+            entries,
+            price_modifiers: Vec::new(),
+            price_modifier_mode: QuantityModifierMode::Stack,
+            sale_prices: HashMap::new(),
+            object_prices: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn avoid_repeat_skips_stocked_items() {
+        let items = vec![item_id!("(O)388"), item_id!("(O)390")];
+        let shop = shop(vec![
+            entry(
+                ShopItemSource::AvoidRepeat(items.clone()),
+                DropItems::Random(items.clone()),
+            ),
+            entry(
+                ShopItemSource::AvoidRepeat(items.clone()),
+                DropItems::Random(items.clone()),
+            ),
+            entry(
+                ShopItemSource::AvoidRepeat(items.clone()),
+                DropItems::Random(items.clone()),
+            ),
+        ]);
+        let state = PredictionGameState {
+            game_id: 254546202,
+            days_played: 10,
+            ..Default::default()
+        };
+
+        let prediction = predict_shop_stock::<HashedSeedGenerator>(&shop, &state).unwrap();
+        let mut stocked: Vec<ItemId> = prediction
+            .items
+            .iter()
+            .map(|item| item.item.clone())
+            .collect();
+        stocked.sort_by_key(|item| format!("{item}"));
+        assert_eq!(stocked, items);
+        assert!(prediction
+            .items
+            .iter()
+            .all(|item| item.price == Some(100) && item.stock == Some(1)));
+        assert_eq!(prediction.unsupported_query, None);
+    }
+
+    // Expected values were computed with a separate Python port of the
+    // game's seeding, `System.Random` and `Utility.Shuffle()`.
+    #[test]
+    fn avoid_repeat_stock_is_predicted() {
+        let items: Vec<ItemId> = [388, 390, 709, 330, 382]
+            .iter()
+            .map(|id| format!("(O){id}").parse().unwrap())
+            .collect();
+        let shop = shop(
+            (0..3)
+                .map(|_| {
+                    entry(
+                        ShopItemSource::AvoidRepeat(items.clone()),
+                        DropItems::Random(items.clone()),
+                    )
+                })
+                .collect(),
+        );
+        let stock = |days_played| {
+            let state = PredictionGameState {
+                game_id: 254546202,
+                days_played,
+                ..Default::default()
+            };
+            predict_shop_stock::<HashedSeedGenerator>(&shop, &state)
+                .unwrap()
+                .items
+                .into_iter()
+                .map(|item| format!("{}", item.item))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(stock(10), vec!["(O)390", "(O)709", "(O)388"]);
+        assert_eq!(stock(11), vec!["(O)390", "(O)709", "(O)330"]);
+    }
+
+    #[test]
+    fn item_queries_truncate_stock() {
+        let shop = shop(vec![
+            entry(ShopItemSource::Item, DropItems::Item(item_id!("(O)388"))),
+            entry(
+                ShopItemSource::Query("RANDOM_ITEMS".to_string()),
+                DropItems::Item(item_id!("RANDOM_ITEMS")),
+            ),
+            entry(ShopItemSource::Item, DropItems::Item(item_id!("(O)390"))),
+        ]);
+        let state = PredictionGameState {
+            game_id: 254546202,
+            days_played: 10,
+            ..Default::default()
+        };
+
+        let prediction = predict_shop_stock::<HashedSeedGenerator>(&shop, &state).unwrap();
+        assert_eq!(prediction.items.len(), 1);
+        assert_eq!(prediction.items[0].item, item_id!("(O)388"));
+        assert_eq!(
+            prediction.unsupported_query,
+            Some("RANDOM_ITEMS".to_string())
+        );
+    }
+
+    fn random_objects(ids: &[u32]) -> RandomItems {
+        RandomItems {
+            item_count: ids.len() + 2,
+            candidates: ids
+                .iter()
+                .enumerate()
+                .map(|(index, id)| {
+                    let qualified_id = format!("(O){id}");
+                    let item = qualified_id.parse::<ItemId>().unwrap();
+                    let target = TargetItem {
+                        qualified_id,
+                        category: -16,
+                        object_type: Some("Basic".to_string()),
+                    };
+                    (index, target, item)
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn random_items_skip_stocked_and_filtered_items() {
+        let mut random = entry(
+            ShopItemSource::RandomItems(random_objects(&[388, 390, 709, 330, 382])),
+            DropItems::Item(item_id!("RANDOM_ITEMS")),
+        );
+        random.max_items = Some(3);
+        random.per_item_condition = Some(GameStateQuery::parse("!ITEM_ID Target (O)330").unwrap());
+        let shop = shop(vec![
+            entry(ShopItemSource::Item, DropItems::Item(item_id!("(O)388"))),
+            random,
+        ]);
+        let state = PredictionGameState {
+            game_id: 254546202,
+            days_played: 10,
+            ..Default::default()
+        };
+
+        let prediction = predict_shop_stock::<HashedSeedGenerator>(&shop, &state).unwrap();
+        let mut stocked: Vec<String> = prediction
+            .items
+            .iter()
+            .map(|item| format!("{}", item.item))
+            .collect();
+        stocked.sort();
+        assert_eq!(stocked, vec!["(O)382", "(O)388", "(O)390", "(O)709"]);
+        assert_eq!(prediction.unsupported_query, None);
+    }
+
+    #[test]
+    fn unsupported_per_item_conditions_truncate_stock() {
+        let mut random = entry(
+            ShopItemSource::RandomItems(random_objects(&[388, 390])),
+            DropItems::Item(item_id!("RANDOM_ITEMS")),
+        );
+        random.per_item_condition =
+            Some(GameStateQuery::parse("ITEM_HAS_TAG Target book_item").unwrap());
+        let shop = shop(vec![random]);
+        let state = PredictionGameState {
+            game_id: 254546202,
+            days_played: 10,
+            ..Default::default()
+        };
+
+        let prediction = predict_shop_stock::<HashedSeedGenerator>(&shop, &state).unwrap();
+        assert!(prediction.items.is_empty());
+        assert_eq!(
+            prediction.unsupported_query,
+            Some("RANDOM_ITEMS".to_string())
+        );
+    }
+
+    fn random_modifier(id: &str, modification: ModificationType, amounts: &[f32]) -> Modifiers {
+        vec![(
+            None,
+            QuantityModifier {
+                id: id.to_string(),
+                condition: None,
+                modification,
+                amount: 1.0,
+                random_amount: Some(amounts.to_vec()),
+            },
+        )]
+    }
+
+    // The price modifier rolls before the stock modifier.  Expected values
+    // were computed by rolling both from the shop's seed in that order.
+    #[test]
+    fn price_is_rolled_before_stock() {
+        let mut item = entry(ShopItemSource::Item, DropItems::Item(item_id!("(O)388")));
+        item.price_modifiers =
+            random_modifier("Price", ModificationType::Multiply, &[1.0, 2.0, 3.0]);
+        item.available_stock = 5;
+        item.available_stock_modifiers =
+            random_modifier("Stock", ModificationType::Add, &[0.0, 10.0, 20.0]);
+        let shop = shop(vec![item]);
+        let stock = |days_played| {
+            let state = PredictionGameState {
+                game_id: 254546202,
+                days_played,
+                ..Default::default()
+            };
+            let prediction = predict_shop_stock::<HashedSeedGenerator>(&shop, &state).unwrap();
+            (prediction.items[0].price, prediction.items[0].stock)
+        };
+
+        assert_eq!(stock(10), (Some(200), Some(25)));
+        assert_eq!(stock(11), (Some(100), Some(25)));
+        assert_eq!(stock(12), (Some(100), Some(5)));
+    }
+
+    #[test]
+    fn recipes_are_stocked_once() {
+        let mut recipe = entry(ShopItemSource::Item, DropItems::Item(item_id!("(O)388")));
+        recipe.is_recipe = true;
+        recipe.drop.min_stack = 5;
+        recipe.drop.max_stack = 5;
+        recipe.available_stock = 5;
+        recipe.available_stock_modifiers = random_modifier("Stock", ModificationType::Add, &[10.0]);
+        let shop = shop(vec![recipe]);
+        let state = PredictionGameState {
+            game_id: 254546202,
+            days_played: 10,
+            ..Default::default()
+        };
+
+        let prediction = predict_shop_stock::<HashedSeedGenerator>(&shop, &state).unwrap();
+        assert_eq!(prediction.items[0].quantity, 1);
+        assert_eq!(prediction.items[0].stock, Some(1));
+        assert_eq!(prediction.items[0].stock_limit, LimitedStockMode::None);
+    }
+
+    #[test]
+    fn real_shops_sell_seeds_at_known_prices() {
+        let game_data =
+            GameData::from_content_dir(crate::gamedata::get_game_content_path().unwrap()).unwrap();
+        let state = PredictionGameState {
+            game_id: 254546202,
+            days_played: 1,
+            ..Default::default()
+        };
+        let price = |shop: &str, item: ItemId| {
+            let shop = Shop::new(shop, &game_data).unwrap();
+            let prediction = predict_shop_stock::<HashedSeedGenerator>(&shop, &state).unwrap();
+            prediction
+                .items
+                .iter()
+                .find(|stock| stock.item == item)
+                .and_then(|stock| stock.price)
+        };
+
+        // Parsnip and cauliflower seeds on Spring 1 of year 1.
+        assert_eq!(price("SeedShop", item_id!("(O)472")), Some(20));
+        assert_eq!(price("SeedShop", item_id!("(O)474")), Some(80));
+        assert_eq!(price("Joja", item_id!("(O)472")), Some(25));
+        assert_eq!(price("Joja", item_id!("(O)474")), Some(100));
+    }
+
+    #[test]
+    fn real_shops_stock_known_day() {
+        let game_data =
+            GameData::from_content_dir(crate::gamedata::get_game_content_path().unwrap()).unwrap();
+        // Spring 21 of year 1.
+        let (state, _) = PredictionGameState::from_save(&load_test_save("ChimkinHill_370550379"));
+        let stock = |shop: &str| {
+            let shop = Shop::new(shop, &game_data).unwrap();
+            predict_shop_stock::<HashedSeedGenerator>(&shop, &state).unwrap()
+        };
+
+        let seed_shop = stock("SeedShop");
+        let seed_stock = |item: ItemId| {
+            seed_shop
+                .items
+                .iter()
+                .find(|stock| stock.item == item)
+                .map(|stock| stock.stock)
+        };
+        // Spring seeds are unlimited, garlic waits for year 2 and melons for
+        // summer.
+        for seed in [472, 474, 475, 477, 427, 429] {
+            let item = format!("(O){seed}").parse().unwrap();
+            assert_eq!(seed_stock(item), Some(None), "(O){}", seed);
+        }
+        assert_eq!(seed_stock(item_id!("(O)476")), None);
+        assert_eq!(seed_stock(item_id!("(O)479")), None);
+
+        // The saloon sells the save's dish of the day.
+        assert!(stock("Saloon")
+            .items
+            .iter()
+            .any(|stock| stock.item == item_id!("(O)238")));
+    }
+}
@@ -0,0 +1,93 @@
+use std::convert::TryFrom;
+
+use anyhow::Result;
+
+use crate::{common::DayOfWeek, rng::SeedGenerator, GameData};
+
+use super::{
+    shop::{predict_shop_stock, Shop, ShopStockPrediction},
+    PredictionGameState,
+};
+
+/// Returns true if the traveling cart is in the forest on `days_played`.
+pub fn is_traveling_cart_day(days_played: u32) -> bool {
+    let day_of_month = days_played.saturating_sub(1) % 28 + 1;
+    matches!(
+        DayOfWeek::try_from(day_of_month as i32),
+        Ok(DayOfWeek::Friday | DayOfWeek::Sunday)
+    )
+}
+
+/// Cached data for traveling cart prediction.
+///
+/// The cart's stock comes from the `Traveler` shop in `Data/Shops`.
+#[derive(Clone, Debug)]
+pub struct TravelingCart {
+    shop: Shop,
+}
+
+impl TravelingCart {
+    pub fn new(game_data: &GameData) -> Result<Self> {
+        Ok(Self {
+            shop: Shop::new("Traveler", game_data)?,
+        })
+    }
+}
+
+/// Predict the traveling cart's stock for `state.days_played`.
+///
+/// No check is made that the cart is visiting on that day.  See
+/// [is_traveling_cart_day].
+pub fn predict_traveling_cart<G: SeedGenerator>(
+    cart: &TravelingCart,
+    state: &PredictionGameState,
+) -> Result<ShopStockPrediction> {
+    predict_shop_stock::<G>(&cart.shop, state)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{rng::HashedSeedGenerator, save::load_test_save};
+
+    use super::*;
+
+    #[test]
+    fn cart_visits_friday_and_sunday() {
+        let days: Vec<u32> = (1..=28).filter(|day| is_traveling_cart_day(*day)).collect();
+        assert_eq!(days, vec![5, 7, 12, 14, 19, 21, 26, 28]);
+        assert!(is_traveling_cart_day(28 * 5 + 5));
+        assert!(!is_traveling_cart_day(28 * 5 + 6));
+        assert!(!is_traveling_cart_day(0));
+    }
+
+    #[test]
+    fn cart_stocks_traveler_random_objects() {
+        let game_data =
+            GameData::from_content_dir(crate::gamedata::get_game_content_path().unwrap()).unwrap();
+        let cart = TravelingCart::new(&game_data).unwrap();
+        // Sunday the 21st of spring in year 1.
+        let (state, _) = PredictionGameState::from_save(&load_test_save("ChimkinHill_370550379"));
+        assert!(is_traveling_cart_day(state.days_played));
+
+        let stock = predict_traveling_cart::<HashedSeedGenerator>(&cart, &state).unwrap();
+        let objects: Vec<_> = stock.items.iter().take(10).collect();
+        assert_eq!(objects.len(), 10);
+
+        // `Traveler` starts with ten different random objects between IDs 2
+        // and 789 which are stocked once or five times.
+        let ids: HashSet<u32> = objects
+            .iter()
+            .map(|item| {
+                let id = format!("{}", item.item);
+                id.strip_prefix("(O)").unwrap().parse().unwrap()
+            })
+            .collect();
+        assert_eq!(ids.len(), 10);
+        assert!(ids.iter().all(|id| (2..=789).contains(id)));
+        assert!(objects
+            .iter()
+            .all(|item| item.price.is_some() && matches!(item.stock, Some(1 | 5))));
+    }
+}
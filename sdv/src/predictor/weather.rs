@@ -1,37 +1,206 @@
-use sdv_core::HashedString;
-use xxhash_rust::xxh32::xxh32;
+use std::convert::TryFrom;
+
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     common::{Season, Weather},
-    gamedata::LocationContextData,
-    generate_day_save_seed, generate_seed, hashed_match,
-    rng::{Rng, SeedGenerator},
+    gamedata::{LocationContextData, PassiveFestivalData},
+    query::{GameStateQuery, PlayerTarget, QueryContext},
+    rng::SeedGenerator,
+    GameData,
 };
 
 use super::PredictionGameState;
 
+const DEFAULT_CONTEXT: &str = "Default";
+
+/// Cached weather condition for weather prediction.
+#[derive(Clone, Debug, PartialEq)]
+struct WeatherCondition {
+    id: String,
+    weather: Weather,
+    query: Option<GameStateQuery>,
+}
+
+/// Cached passive festival that forces sunny weather in the valley.
+#[derive(Clone, Debug, PartialEq)]
+struct PassiveFestival {
+    id: String,
+    season: Season,
+    start_day: u32,
+    end_day: u32,
+    query: Option<GameStateQuery>,
+}
+
+impl TryFrom<&PassiveFestivalData> for PassiveFestival {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &PassiveFestivalData) -> std::result::Result<Self, Self::Error> {
+        let query = (!value.condition.is_empty())
+            .then(|| GameStateQuery::parse(&value.condition))
+            .transpose()?;
+        Ok(Self {
+            id: value.id.clone(),
+            season: value.season.clone(),
+            start_day: value.start_day.max(0) as u32,
+            end_day: value.end_day.max(0) as u32,
+            query,
+        })
+    }
+}
+
+// Whether the location `name` is in the `Default` location context.
+//
+// Context of the location `name` or `None` if it isn't in `Data/Locations`.
+//
+// Logic found in `GameLocation.GetLocationContextId()`.  The game reads the
+// context from the `LocationContext` property of the location's map, which
+// isn't loaded.  Instead, locations belong to the context named after them or
+// the one that revives or places passed out players in them, and to the valley
+// otherwise.
+fn location_context<'a>(name: &str, game_data: &'a GameData) -> Option<&'a str> {
+    if !game_data.locations.contains_key(name) {
+        return None;
+    }
+    let context = game_data.location_contexts.values().find(|context| {
+        context.id == name
+            || context
+                .revive_locations
+                .iter()
+                .chain(context.pass_out_locations.iter().flatten())
+                .any(|revive| revive.location == name)
+    });
+    Some(context.map_or(DEFAULT_CONTEXT, |context| context.id.as_str()))
+}
+
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct WeatherLocation {
-    conditions: Vec<Option<HashedString>>,
+    /// ID of the location context.
+    id: String,
+
+    /// ID of the location context whose weather is used.  Differs from `id`
+    /// when the weather is copied from another context.
+    context: String,
+    conditions: Vec<WeatherCondition>,
+    rain_totem_target: Option<String>,
+
+    // Only populated for the `Default` context.
+    passive_festivals: Vec<PassiveFestival>,
 }
 
-impl From<&LocationContextData> for WeatherLocation {
-    fn from(value: &LocationContextData) -> Self {
+impl WeatherLocation {
+    /// Cache the weather data for the location context `id`.
+    ///
+    /// Follows `copy_weather_from_location` and, for the valley, loads the
+    /// passive festivals that affect its weather.
+    pub fn new(id: &str, game_data: &GameData) -> Result<Self> {
+        let get_context = |id: &str| {
+            game_data
+                .location_contexts
+                .get(id)
+                .ok_or_else(|| anyhow!("location context {} not found", id))
+        };
+        let data = get_context(id)?;
+        let source = match &data.copy_weather_from_location {
+            Some(source) => get_context(source)?,
+            None => data,
+        };
+
+        let mut location = Self::try_from(source)?;
+        location.id = data.id.clone();
+        location.rain_totem_target = rain_totem_target(data);
+
+        if location.context == DEFAULT_CONTEXT {
+            // Logic found in `Game1.getWeatherModificationsForDate()`.
+            location.passive_festivals = game_data
+                .passive_festivals
+                .values()
+                .filter(|festival| {
+                    festival
+                        .map_replacements
+                        .iter()
+                        .flatten()
+                        .any(|(location, _)| {
+                            location_context(location, game_data) == Some(DEFAULT_CONTEXT)
+                        })
+                })
+                .map(PassiveFestival::try_from)
+                .collect::<Result<Vec<_>>>()?;
+        }
+
+        Ok(location)
+    }
+
+    /// Cache the weather data for every location context.
+    pub fn all(game_data: &GameData) -> Result<Vec<Self>> {
+        game_data
+            .location_contexts
+            .keys()
+            .map(|id| Self::new(id, game_data))
+            .collect()
+    }
+
+    /// ID of the location context.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Location context whose weather changes when a rain totem is used in
+    /// this context.  `None` if rain totems can't be used here.
+    pub fn rain_totem_target(&self) -> Option<&str> {
+        self.rain_totem_target.as_deref()
+    }
+}
+
+// Logic found in `Object.rainTotem()`.
+fn rain_totem_target(data: &LocationContextData) -> Option<String> {
+    data.allow_rain_totem.then(|| {
+        data.rain_totem_affects_context
+            .clone()
+            .unwrap_or_else(|| data.id.clone())
+    })
+}
+
+impl TryFrom<&LocationContextData> for WeatherLocation {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &LocationContextData) -> std::result::Result<Self, Self::Error> {
         let conditions = value
             .weather_condidtions
             .iter()
             .map(|condition| {
-                condition
+                let weather = condition.weather.parse().map_err(|_| {
+                    anyhow!(
+                        "unknown weather {} in condition {}",
+                        condition.weather,
+                        condition.id
+                    )
+                })?;
+                let query = condition
                     .condition
                     .as_ref()
-                    .map(|value| HashedString::new(value))
+                    .map(|query| GameStateQuery::parse(query))
+                    .transpose()?;
+                Ok(WeatherCondition {
+                    id: condition.id.clone(),
+                    weather,
+                    query,
+                })
             })
-            .collect();
-        Self { conditions }
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            id: value.id.clone(),
+            context: value.id.clone(),
+            conditions,
+            rain_totem_target: rain_totem_target(value),
+            passive_festivals: Vec::new(),
+        })
     }
 }
 
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct WeatherPrediction {
     pub sun: f64,
     pub rain: f64,
@@ -92,6 +261,19 @@ impl WeatherPrediction {
         }
     }
 
+    /// Chance of `weather` occurring.
+    pub fn chance(&self, weather: &Weather) -> f64 {
+        match weather {
+            Weather::Sun => self.sun,
+            Weather::Rain => self.rain,
+            Weather::Wind => self.wind,
+            Weather::Storm => self.storm,
+            Weather::Snow => self.snow,
+            Weather::Festival => self.fesival,
+            Weather::GreenRain => self.green_rain,
+        }
+    }
+
     pub fn from_pratials(partials: &[PartialPrediction]) -> Self {
         partials.iter().fold(Self::default(), |mut acc, partial| {
             match partial.weather {
@@ -109,22 +291,22 @@ impl WeatherPrediction {
 }
 
 #[derive(Clone, Debug)]
-pub struct PartialPrediction {
+pub struct PartialPrediction<'a> {
     weather: Weather,
     chance: f64,
 
     // Used for debugging.
     #[allow(unused)]
-    condition: &'static str,
+    condition: &'a str,
 }
 
-impl PartialEq for PartialPrediction {
+impl PartialEq for PartialPrediction<'_> {
     fn eq(&self, other: &Self) -> bool {
         self.weather == other.weather && self.chance == other.chance
     }
 }
 
-impl PartialPrediction {
+impl<'a> PartialPrediction<'a> {
     pub fn new(weather: Weather, chance: f64) -> Self {
         Self {
             weather,
@@ -133,7 +315,7 @@ impl PartialPrediction {
         }
     }
 
-    pub fn new_condition(weather: Weather, chance: f64, condition: &'static str) -> Self {
+    pub fn new_condition(weather: Weather, chance: f64, condition: &'a str) -> Self {
         Self {
             weather,
             chance,
@@ -142,232 +324,141 @@ impl PartialPrediction {
     }
 }
 
-fn synced_day_random<G: SeedGenerator>(
-    state: &PredictionGameState,
-    key: &str,
-    chance: f64,
-) -> bool {
-    Rng::new(generate_seed!(
-        G,
-        xxh32(key.as_bytes(), 0) as i32, // i32 conversion here is very important
-        state.game_id,
-        state.days_played
-    ))
-    .next_weighted_bool(chance)
+/// [QueryContext] used when evaluating location weather conditions.
+struct WeatherQueryContext<'a> {
+    state: &'a PredictionGameState,
+    location: &'a WeatherLocation,
 }
 
-fn synced_summer_rain_random<G: SeedGenerator>(state: &PredictionGameState) -> bool {
-    // i32 conversion here is very important
-    let key = xxh32("summer_rain_chance".as_bytes(), 0) as i32;
-    Rng::new(generate_day_save_seed!(
-        G,
-        state.days_played,
-        state.game_id,
-        key
-    ))
-    .next_weighted_bool((0.12f32 + (state.day_of_month() as f32) * 0.003) as f64)
-}
+impl QueryContext for WeatherQueryContext<'_> {
+    fn game_id(&self) -> u32 {
+        self.state.game_id()
+    }
 
-fn is_green_rain_day<G: SeedGenerator>(state: &PredictionGameState) -> bool {
-    if !state.is_season(Season::Summer) {
-        return false;
+    fn days_played(&self) -> u32 {
+        self.state.days_played()
     }
-    let mut rng = Rng::new(generate_seed!(G, state.year() * 777, state.game_id));
 
-    state.day_of_month() == *rng.chooose_from(&[5u32, 6, 7, 14, 15, 16, 18, 23])
-}
+    fn daily_luck(&self) -> f64 {
+        self.state.daily_luck()
+    }
 
-// Stub until we get real condition parsing working
-fn evaulate_condition<G: SeedGenerator>(
-    //    r: &mut Rng,
-    state: &PredictionGameState,
-    condition: &Option<HashedString>,
-) -> Option<PartialPrediction> {
-    let Some(condition) = condition else {
-        return Some(PartialPrediction::new_condition(
-            Weather::Sun,
-            1.0,
-            "Default",
-        ));
-    };
+    fn location_context(&self) -> &str {
+        &self.location.context
+    }
 
-    match condition {
-        // GreenRain
-        hashed_match!("IS_GREEN_RAIN_DAY") => {
-            // Since conditions are evaluated as if they were the previous day,
-            // `getWeatherModificationsForDate()` also check for green rain with
-            // the correct day, and there can only be one green rain day per year,
-            // This condition will never be valid.  We're forcing to `None` here
-            // to avoid having to carry state between weather predictions.
-            None
-        }
+    fn is_green_rain_day<G: SeedGenerator>(&self) -> bool {
+        // Since conditions are evaluated as if they were the previous day,
+        // `getWeatherModificationsForDate()` also check for green rain with
+        // the correct day, and there can only be one green rain day per year,
+        // This condition will never be valid.  We're forcing to `false` here
+        // to avoid having to carry state between weather predictions.
+        false
+    }
 
-        // FirstWeekSun
-        hashed_match!("SEASON_DAY Spring 0 Spring 1 Spring 2 Spring 4, YEAR 1") => {
-            if (state.is_season_day(Season::Spring, 0)
-                || state.is_season_day(Season::Spring, 1)
-                || state.is_season_day(Season::Spring, 2)
-                || state.is_season_day(Season::Spring, 4))
-                && state.is_year(1)
-            {
-                Some(PartialPrediction::new_condition(
-                    Weather::Sun,
-                    1.0,
-                    "FirstWeekSun",
-                ))
-            } else {
-                None
-            }
-        } // Sun
-
-        // FirstWeekRain
-        hashed_match!("SEASON_DAY Spring 3, YEAR 1") => {
-            if state.is_season_day(Season::Spring, 3) && state.is_year(1) {
-                Some(PartialPrediction::new_condition(
-                    Weather::Rain,
-                    1.0,
-                    "FirstWeekRain",
-                ))
-            } else {
-                None
-            }
-        }
+    fn player_stat(&self, player: &PlayerTarget, stat: &str) -> Option<u32> {
+        self.state.player_stat(player, stat)
+    }
 
-        // SummerStorm
-        hashed_match!("SEASON summer, SYNCED_SUMMER_RAIN_RANDOM, RANDOM .85") => {
-            if state.is_season(Season::Summer) && synced_summer_rain_random::<G>(state) {
-                Some(PartialPrediction::new_condition(
-                    Weather::Storm,
-                    0.85,
-                    "SummerStorm",
-                ))
-            } else {
-                None
-            }
-        }
+    fn player_has_mail(&self, player: &PlayerTarget, mail: &str) -> Option<bool> {
+        self.state.player_has_mail(player, mail)
+    }
 
-        // SummerStorm2
-        hashed_match!(
-            "SEASON summer, SYNCED_SUMMER_RAIN_RANDOM, RANDOM .25, \
-	     DAYS_PLAYED 28, !DAY_OF_MONTH 1, !DAY_OF_MONTH 2"
-        ) => {
-            if state.is_season(Season::Summer)
-                && synced_summer_rain_random::<G>(state)
-                && state.days_played > 28
-                && !state.is_day_of_month(1)
-                && !state.is_day_of_month(2)
-            {
-                Some(PartialPrediction::new_condition(
-                    Weather::Storm,
-                    0.25,
-                    "SummerStorm2",
-                ))
-            } else {
-                None
-            }
-        }
+    fn player_has_seen_event(&self, player: &PlayerTarget, event: &str) -> Option<bool> {
+        self.state.player_has_seen_event(player, event)
+    }
 
-        // FallStorm
-        hashed_match!(
-            "SEASON spring fall, SYNCED_RANDOM day location_weather .183, \
-	     RANDOM .25, DAYS_PLAYED 28, !DAY_OF_MONTH 1, !DAY_OF_MONTH 2"
-        ) => {
-            if (state.is_season(Season::Spring) || state.is_season(Season::Fall))
-                && synced_day_random::<G>(state, "location_weather", 0.183)
-                && state.days_played > 28
-                && !state.is_day_of_month(1)
-                && !state.is_day_of_month(2)
-            {
-                Some(PartialPrediction::new_condition(
-                    Weather::Storm,
-                    0.25,
-                    "FallStorm",
-                ))
-            } else {
-                None
-            }
-        }
+    fn player_special_order_rule_active(&self, player: &PlayerTarget, rule: &str) -> Option<bool> {
+        self.state.player_special_order_rule_active(player, rule)
+    }
+}
 
-        // WinterSnow
-        hashed_match!("SEASON winter, SYNCED_RANDOM day location_weather 0.63") => {
-            if state.is_season(Season::Winter)
-                && synced_day_random::<G>(state, "location_weather", 0.63)
-            {
-                Some(PartialPrediction::new_condition(
-                    Weather::Snow,
-                    1.0,
-                    "WinterSnow",
-                ))
-            } else {
-                None
-            }
-        }
+fn evaulate_condition<'a, G: SeedGenerator>(
+    context: &WeatherQueryContext,
+    condition: &'a WeatherCondition,
+) -> Result<Option<PartialPrediction<'a>>> {
+    let chance = match &condition.query {
+        // `RANDOM` clauses are not rolled so that their chance ends up in the
+        // prediction.
+        Some(query) => query.evaluate::<G, _>(context, None)?.chance,
+        None => 1.0,
+    };
 
-        // SummerRain
-        hashed_match!("SEASON summer, SYNCED_SUMMER_RAIN_RANDOM, !DAY_OF_MONTH 1") => {
-            if state.is_season(Season::Summer)
-                && synced_summer_rain_random::<G>(state)
-                && !state.is_day_of_month(1)
-            {
-                Some(PartialPrediction::new_condition(
-                    Weather::Rain,
-                    1.0,
-                    "SummerRain",
-                ))
-            } else {
-                None
-            }
-        }
+    Ok((chance > 0.0).then(|| {
+        PartialPrediction::new_condition(condition.weather.clone(), chance, &condition.id)
+    }))
+}
 
-        // FallRain
-        hashed_match!("SEASON spring fall, SYNCED_RANDOM day location_weather 0.183") => {
-            if (state.is_season(Season::Spring) || state.is_season(Season::Fall))
-                && synced_day_random::<G>(state, "location_weather", 0.183)
-            {
-                Some(PartialPrediction::new_condition(
-                    Weather::Rain,
-                    1.0,
-                    "FallRain",
-                ))
-            } else {
-                None
-            }
-        }
+// Hard coded weather for the valley that overrides the location context's
+// conditions.
+//
+// Logic found in `Game1.getWeatherModificationsForDate()`.  The game uses
+// `Game1.stats.DaysPlayed + day_offset` where `day_offset` is the number of
+// days between the date and today.  That is the `days_played` of the date
+// itself so `state.days_played` is used directly.
+fn weather_modifications<G: SeedGenerator>(
+    location: &WeatherLocation,
+    state: &PredictionGameState,
+) -> Result<Option<WeatherPrediction>> {
+    // These are in reverse order that they are in the game because they return
+    // early, yielding the same results.
 
-        // SpringWind
-        hashed_match!("DAYS_PLAYED 3, SEASON spring, RANDOM .20") => {
-            if state.days_played > 3 && state.is_season(Season::Spring) {
-                Some(PartialPrediction::new_condition(
-                    Weather::Wind,
-                    0.2,
-                    "SpringWind",
-                ))
-            } else {
-                None
-            }
+    // Passive festivals (like the night market) that replace a map in the
+    // valley force sunny weather.  Their conditions are checked against the
+    // current day rather than the date.
+    let mut today = state.clone();
+    today.days_played -= 1;
+    let context = WeatherQueryContext {
+        state: &today,
+        location,
+    };
+    for festival in &location.passive_festivals {
+        if state.season() != festival.season
+            || !(festival.start_day..=festival.end_day).contains(&state.day_of_month())
+        {
+            continue;
         }
-
-        // FallWind
-        hashed_match!("DAYS_PLAYED 3, SEASON fall, RANDOM .6") => {
-            if state.days_played > 3 && state.is_season(Season::Spring) {
-                Some(PartialPrediction::new_condition(
-                    Weather::Wind,
-                    0.3,
-                    "FallWind",
-                ))
-            } else {
-                None
-            }
+        let passes = match &festival.query {
+            Some(query) => query.evaluate::<G, _>(&context, None)?.is_certain(),
+            None => true,
+        };
+        if passes {
+            return Ok(Some(WeatherPrediction::sun()));
         }
+    }
 
-        _ => panic!("unknown condition \"|{}\"", condition),
+    if state.is_festival_day() {
+        return Ok(Some(WeatherPrediction::fesival()));
+    }
+
+    if state.is_season(Season::Summer) && (state.day_of_month() % 13) == 0 {
+        return Ok(Some(WeatherPrediction::storm()));
     }
+
+    if state.is_green_rain_day::<G>() {
+        return Ok(Some(WeatherPrediction::green_rain()));
+    }
+
+    if state.days_played == 3 {
+        return Ok(Some(WeatherPrediction::rain()));
+    }
+
+    if state.is_day_of_month(1) || state.days_played <= 4 {
+        return Ok(Some(WeatherPrediction::sun()));
+    }
+
+    Ok(None)
 }
 
+/// Predict the weather of `location` on `state.days_played`.
 pub fn predict_weather<G: SeedGenerator>(
     location: &WeatherLocation,
     state: &PredictionGameState,
-) -> WeatherPrediction {
+) -> Result<WeatherPrediction> {
+    if state.days_played == 0 {
+        return Err(anyhow!("weather can't be predicted before the first day"));
+    }
+
     // TODO: explore ways of returning weather that do not involve allocation.
 
     // See UpdateDailyWeather and getWeatherModificationsForDate
@@ -382,35 +473,20 @@ pub fn predict_weather<G: SeedGenerator>(
     // location context's weather conditions.  For accuracy's sake, no attepemt
     // has been made to optimize these checks away.
 
-    // `getWeatherModificationsForData()` calculates a `day_offset` and uses that
-    // in its calculations.  Here we assume that day_offset is always zero and
-    // `state.day_played` represents the day of interest.
-
-    // These are in reverse order that they are in the game because they return
-    // early, yielding the same results.
-
-    // Here the game checks passive festivals (aka desert festival and fishing derbies).
-    // None of those are declared in a way that affect any weather so we elide the
-    // calculation here.
-
-    if state.is_festival_day() {
-        return WeatherPrediction::fesival();
-    }
-
-    if state.is_season(Season::Summer) && (state.day_of_month() % 13) == 0 {
-        return WeatherPrediction::storm();
-    }
-
-    if is_green_rain_day::<G>(state) {
-        return WeatherPrediction::green_rain();
+    // A rain totem used the previous day overrides the weather.  In the valley
+    // it does nothing if the date is a festival.
+    if state.rain_totem_context.as_deref() == Some(location.id.as_str())
+        && !(location.id == DEFAULT_CONTEXT && state.is_festival_day())
+    {
+        return Ok(WeatherPrediction::rain());
     }
 
-    if state.days_played == 3 {
-        return WeatherPrediction::rain();
-    }
-
-    if state.is_day_of_month(1) || state.days_played <= 4 {
-        return WeatherPrediction::sun();
+    // Only the valley's weather is modified.  Contexts that copy the valley's
+    // weather get the modified weather.
+    if location.context == DEFAULT_CONTEXT {
+        if let Some(prediction) = weather_modifications::<G>(location, state)? {
+            return Ok(prediction);
+        }
     }
 
     // Below is the logic for `UpdateDailyWeather()`.
@@ -421,10 +497,14 @@ pub fn predict_weather<G: SeedGenerator>(
     // checks here.  We elide those calculation because we would have returned
     // early above if they were true.
 
+    let context = WeatherQueryContext {
+        state: &state,
+        location,
+    };
     let mut partial_predictions = Vec::new();
     let mut current_probability = 1.0;
     for condition in &location.conditions {
-        if let Some(mut weather) = evaulate_condition::<G>(&state, condition) {
+        if let Some(mut weather) = evaulate_condition::<G>(&context, condition)? {
             let base_chance = weather.chance;
             weather.chance *= current_probability;
             current_probability *= 1.0 - base_chance;
@@ -438,12 +518,65 @@ pub fn predict_weather<G: SeedGenerator>(
         partial_predictions.push(PartialPrediction::new(Weather::Sun, current_probability))
     }
 
-    WeatherPrediction::from_pratials(&partial_predictions)
+    Ok(WeatherPrediction::from_pratials(&partial_predictions))
+}
+
+/// Weather prediction for a single day of a forecast.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ForecastDay {
+    pub days_played: u32,
+    pub year: u32,
+    pub season: Season,
+    pub day_of_month: u32,
+    pub weather: WeatherPrediction,
+}
+
+/// Per day weather predictions keyed by location context ID.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct WeatherForecast {
+    pub contexts: IndexMap<String, Vec<ForecastDay>>,
+}
+
+/// Predict the weather of each of `locations` for `days` days starting with
+/// `start_days_played`.
+///
+/// `state.rain_totem_context` only applies to the first day.
+pub fn forecast_weather<G: SeedGenerator>(
+    locations: &[WeatherLocation],
+    state: &PredictionGameState,
+    start_days_played: u32,
+    days: u32,
+) -> Result<WeatherForecast> {
+    if start_days_played == 0 {
+        return Err(anyhow!("forecasts start on day 1 or later"));
+    }
+
+    let mut forecast = WeatherForecast::default();
+    for location in locations {
+        let mut state = state.clone();
+        let mut calendar = Vec::with_capacity(days as usize);
+        for days_played in start_days_played..start_days_played + days {
+            state.days_played = days_played;
+            calendar.push(ForecastDay {
+                days_played,
+                year: state.year(),
+                season: state.season(),
+                day_of_month: state.day_of_month(),
+                weather: predict_weather::<G>(location, &state)?,
+            });
+            state.rain_totem_context = None;
+        }
+        forecast.contexts.insert(location.id.clone(), calendar);
+    }
+
+    Ok(forecast)
 }
 
 #[cfg(test)]
 mod tests {
 
+    use std::convert::TryInto;
+
     use crate::{rng::HashedSeedGenerator, GameData};
 
     use super::*;
@@ -452,7 +585,12 @@ mod tests {
     fn weather_prediction_returns_correct_restults() {
         let data =
             GameData::from_content_dir(crate::gamedata::get_game_content_path().unwrap()).unwrap();
-        let location = data.location_contexts.get("Default").unwrap().into();
+        let location = data
+            .location_contexts
+            .get("Default")
+            .unwrap()
+            .try_into()
+            .unwrap();
         let mut state = PredictionGameState {
             game_id: 7269403,
             ..Default::default()
@@ -460,7 +598,7 @@ mod tests {
 
         for day in 1..(4 * 28) {
             state.days_played = day;
-            let weather = predict_weather::<HashedSeedGenerator>(&location, &state);
+            let weather = predict_weather::<HashedSeedGenerator>(&location, &state).unwrap();
             let expected = match (state.season(), state.day_of_month()) {
                 (Season::Spring, 3) => WeatherPrediction::rain(),
 
@@ -552,4 +690,151 @@ mod tests {
             );
         }
     }
+
+    fn rainy_context(id: &str) -> WeatherLocation {
+        WeatherLocation {
+            id: id.to_string(),
+            context: id.to_string(),
+            conditions: vec![WeatherCondition {
+                id: "Rain".to_string(),
+                weather: Weather::Rain,
+                query: Some(GameStateQuery::parse("RANDOM 0.25").unwrap()),
+            }],
+            rain_totem_target: Some(id.to_string()),
+            passive_festivals: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn valley_modifications_only_apply_to_default_context() {
+        let state = PredictionGameState {
+            game_id: 7269403,
+            days_played: 3,
+            ..Default::default()
+        };
+        let island = rainy_context("Island");
+        let weather = predict_weather::<HashedSeedGenerator>(&island, &state).unwrap();
+        assert_eq!(
+            weather,
+            WeatherPrediction {
+                sun: 0.75,
+                rain: 0.25,
+                ..Default::default()
+            }
+        );
+
+        let valley = rainy_context(DEFAULT_CONTEXT);
+        let weather = predict_weather::<HashedSeedGenerator>(&valley, &state).unwrap();
+        assert_eq!(weather, WeatherPrediction::rain());
+    }
+
+    #[test]
+    fn passive_festival_forces_sun() {
+        let mut valley = rainy_context(DEFAULT_CONTEXT);
+        valley.passive_festivals.push(PassiveFestival {
+            id: "NightMarket".to_string(),
+            season: Season::Winter,
+            start_day: 15,
+            end_day: 17,
+            query: None,
+        });
+        let state = PredictionGameState {
+            game_id: 7269403,
+            days_played: 28 * 3 + 16,
+            ..Default::default()
+        };
+        let weather = predict_weather::<HashedSeedGenerator>(&valley, &state).unwrap();
+        assert_eq!(weather, WeatherPrediction::sun());
+    }
+
+    #[test]
+    fn weather_is_not_predicted_for_day_zero() {
+        let valley = rainy_context(DEFAULT_CONTEXT);
+        let state = PredictionGameState {
+            game_id: 7269403,
+            days_played: 0,
+            ..Default::default()
+        };
+        assert!(predict_weather::<HashedSeedGenerator>(&valley, &state).is_err());
+    }
+
+    #[test]
+    fn valley_loads_passive_festivals_that_replace_valley_maps() {
+        let data =
+            GameData::from_content_dir(crate::gamedata::get_game_content_path().unwrap()).unwrap();
+        assert_eq!(location_context("Beach", &data), Some(DEFAULT_CONTEXT));
+        assert_eq!(location_context("Desert", &data), Some("Desert"));
+        assert_eq!(location_context("NotALocation", &data), None);
+
+        // The night market replaces the beach and the desert festival the
+        // desert.
+        let valley = WeatherLocation::new(DEFAULT_CONTEXT, &data).unwrap();
+        let festivals: Vec<&str> = valley
+            .passive_festivals
+            .iter()
+            .map(|festival| festival.id.as_str())
+            .collect();
+        assert!(festivals.contains(&"NightMarket"));
+        assert!(!festivals.contains(&"DesertFestival"));
+    }
+
+    #[test]
+    fn rain_totem_skips_valley_festivals() {
+        let valley = rainy_context(DEFAULT_CONTEXT);
+        let mut state = PredictionGameState {
+            game_id: 7269403,
+            days_played: 10,
+            rain_totem_context: Some(DEFAULT_CONTEXT.to_string()),
+            ..Default::default()
+        };
+        let weather = predict_weather::<HashedSeedGenerator>(&valley, &state).unwrap();
+        assert_eq!(weather, WeatherPrediction::rain());
+
+        // Spring 13 is the egg festival.
+        state.days_played = 13;
+        let weather = predict_weather::<HashedSeedGenerator>(&valley, &state).unwrap();
+        assert_eq!(weather, WeatherPrediction::fesival());
+
+        // The totem only affects its own context.
+        state.days_played = 10;
+        let island = rainy_context("Island");
+        let weather = predict_weather::<HashedSeedGenerator>(&island, &state).unwrap();
+        assert_ne!(weather, WeatherPrediction::rain());
+    }
+
+    #[test]
+    fn forecast_crosses_seasons() {
+        let state = PredictionGameState {
+            game_id: 7269403,
+            rain_totem_context: Some("Island".to_string()),
+            ..Default::default()
+        };
+        let locations = [rainy_context(DEFAULT_CONTEXT), rainy_context("Island")];
+        let forecast = forecast_weather::<HashedSeedGenerator>(&locations, &state, 27, 4).unwrap();
+
+        assert_eq!(
+            forecast.contexts.keys().collect::<Vec<_>>(),
+            vec![DEFAULT_CONTEXT, "Island"]
+        );
+        let valley = &forecast.contexts[DEFAULT_CONTEXT];
+        assert_eq!(
+            valley
+                .iter()
+                .map(|day| (day.season.clone(), day.day_of_month))
+                .collect::<Vec<_>>(),
+            vec![
+                (Season::Spring, 27),
+                (Season::Spring, 28),
+                (Season::Summer, 1),
+                (Season::Summer, 2),
+            ]
+        );
+        // The first of the month is always sunny in the valley.
+        assert_eq!(valley[2].weather, WeatherPrediction::sun());
+
+        // The rain totem only affects the first day.
+        let island = &forecast.contexts["Island"];
+        assert_eq!(island[0].weather, WeatherPrediction::rain());
+        assert_ne!(island[1].weather, WeatherPrediction::rain());
+    }
 }
@@ -0,0 +1,1127 @@
+//! Game State Query parsing and evaluation.
+//!
+//! Much of the game's data uses [Game State Queries] to describe when
+//! something should happen (weather, garbage can drops, geode drops, etc.)
+//! Queries are parsed once into a [GameStateQuery] so that successive
+//! evaluations (i.e. when seed finding) do not need to reparse them.
+//!
+//! Evaluation happens against a [QueryContext] which provides the game state
+//! the queries need.
+//!
+//! [Game State Queries]: https://stardewvalleywiki.com/Modding:Game_state_queries
+
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use xxhash_rust::xxh32::xxh32;
+
+use crate::{
+    common::Season,
+    generate_day_save_seed, generate_seed,
+    rng::{Rng, SeedGenerator},
+};
+
+mod parser;
+
+/// The maximum daily luck a player can have.
+///
+/// Luck dependent results that need more luck than this can never pass.
+pub const MAX_DAILY_LUCK: f64 = 0.100001;
+
+/// Which player(s) a player query should check.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PlayerTarget {
+    Any,
+    All,
+    Current,
+    Host,
+    Target,
+    Id(i64),
+}
+
+/// How often a `SYNCED_RANDOM` query rerolls.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RandomInterval {
+    Tick,
+    Day,
+    Season,
+    Year,
+}
+
+/// An argument to `DAY_OF_MONTH`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DayOfMonth {
+    Day(u32),
+    Even,
+    Odd,
+}
+
+/// The item `ITEM_*` queries check.
+///
+/// Only the `Target` item is supported.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TargetItem {
+    /// The item's qualified ID (i.e. `(O)388`).
+    pub qualified_id: String,
+    pub category: i32,
+
+    /// The object type (i.e. `Arch` or `Minerals`) for objects.
+    pub object_type: Option<String>,
+}
+
+impl TargetItem {
+    /// The item's type (i.e. `(O)`).
+    pub fn type_definition_id(&self) -> &str {
+        self.qualified_id
+            .find(')')
+            .map_or("", |end| &self.qualified_id[..=end])
+    }
+
+    // Logic found in `ItemRegistry.HasItemId()`.  Unqualified IDs match
+    // items of any type.
+    fn has_id(&self, id: &str) -> bool {
+        if id.starts_with('(') {
+            return self.qualified_id.eq_ignore_ascii_case(id);
+        }
+        self.qualified_id
+            .split_once(')')
+            .is_some_and(|(_, unqualified)| unqualified.eq_ignore_ascii_case(id))
+    }
+}
+
+/// A single, parsed query.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Query {
+    Any(Vec<GameStateQuery>),
+    True,
+    False,
+    DayOfMonth(Vec<DayOfMonth>),
+    DaysPlayed {
+        min: u32,
+        max: Option<u32>,
+    },
+    IsGreenRainDay,
+    ItemCategory(Vec<i32>),
+    ItemId(Vec<String>),
+    ItemIdPrefix(Vec<String>),
+    ItemObjectType(Vec<String>),
+    ItemType(Vec<String>),
+    LocationContext {
+        location: String,
+        contexts: Vec<String>,
+    },
+    PlayerHasMail {
+        player: PlayerTarget,
+        mail: String,
+    },
+    PlayerHasSeenEvent {
+        player: PlayerTarget,
+        events: Vec<String>,
+    },
+    PlayerSpecialOrderRuleActive {
+        player: PlayerTarget,
+        rule: String,
+    },
+    PlayerStat {
+        player: PlayerTarget,
+        stat: String,
+        min: u32,
+        max: Option<u32>,
+    },
+    Random {
+        chance: f64,
+        add_daily_luck: bool,
+    },
+    Season(Vec<Season>),
+    SeasonDay(Vec<(Season, u32)>),
+    SyncedRandom {
+        interval: RandomInterval,
+        key: String,
+        chance: f64,
+        add_daily_luck: bool,
+    },
+    SyncedSummerRainRandom {
+        base_chance: f64,
+        day_multiplier: f64,
+    },
+    Year {
+        min: u32,
+        max: Option<u32>,
+    },
+
+    /// A query that is not known to the parser.
+    ///
+    /// These parse successfully so that data containing them can still be
+    /// loaded.  Evaluating them returns an error.
+    Unknown {
+        name: String,
+        args: Vec<String>,
+    },
+}
+
+/// A query along with whether its result should be negated (`!`).
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueryClause {
+    pub negated: bool,
+    pub query: Query,
+}
+
+/// A parsed Game State Query.
+///
+/// A query is a list of clauses which all must pass for the query to pass.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GameStateQuery {
+    pub clauses: Vec<QueryClause>,
+}
+
+/// How well a query is supported by the evaluator.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, strum::Display)]
+pub enum QuerySupport {
+    /// Every clause in the query can be evaluated.
+    Full,
+
+    /// Some, but not all, of the clauses in the query can be evaluated.
+    Partial,
+
+    /// None of the clauses in the query can be evaluated.
+    Unsupported,
+}
+
+/// The result of evaluating a query.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QueryResult {
+    /// Probability that the query passes.
+    ///
+    /// Rolls against a seeded [Rng] are resolved and yield `0.0` or `1.0`.
+    /// Unseeded rolls (`RANDOM` with no [Rng]) yield their chance.
+    pub chance: f64,
+
+    /// The daily luck the player needs to have more than for the query to
+    /// pass.
+    ///
+    /// `@addDailyLuck` rolls are not resolved against a specific daily luck.
+    /// Instead the minimum luck needed to pass is tracked so callers can
+    /// report it.
+    pub min_daily_luck: Option<f64>,
+}
+
+impl QueryResult {
+    pub const TRUE: Self = Self {
+        chance: 1.0,
+        min_daily_luck: None,
+    };
+
+    pub const FALSE: Self = Self {
+        chance: 0.0,
+        min_daily_luck: None,
+    };
+
+    pub const fn from_bool(value: bool) -> Self {
+        if value {
+            Self::TRUE
+        } else {
+            Self::FALSE
+        }
+    }
+
+    pub const fn with_chance(chance: f64) -> Self {
+        Self {
+            chance,
+            min_daily_luck: None,
+        }
+    }
+
+    pub const fn with_daily_luck(min_daily_luck: f64) -> Self {
+        Self {
+            chance: 1.0,
+            min_daily_luck: Some(min_daily_luck),
+        }
+    }
+
+    /// Returns true if there is any state in which this query could pass.
+    pub fn is_possible(&self) -> bool {
+        self.chance > 0.0 && self.min_daily_luck.is_none_or(|luck| luck < MAX_DAILY_LUCK)
+    }
+
+    /// Returns true if this query passes regardless of chance or daily luck.
+    pub fn is_certain(&self) -> bool {
+        self.chance >= 1.0 && self.min_daily_luck.is_none()
+    }
+
+    /// Returns true if the query passes with the given daily luck.
+    ///
+    /// Unseeded chances are treated as passing as long as they are possible.
+    pub fn passes_with_luck(&self, daily_luck: f64) -> bool {
+        self.chance > 0.0 && self.min_daily_luck.is_none_or(|luck| daily_luck > luck)
+    }
+
+    /// Combine two results where both must pass.
+    pub fn and(self, other: Self) -> Self {
+        if !self.is_possible() || !other.is_possible() {
+            return Self::FALSE;
+        }
+
+        let min_daily_luck = match (self.min_daily_luck, other.min_daily_luck) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+
+        Self {
+            chance: self.chance * other.chance,
+            min_daily_luck,
+        }
+    }
+
+    /// Combine two results where either must pass.
+    pub fn or(self, other: Self) -> Self {
+        if self.is_certain() || !other.is_possible() {
+            return self;
+        }
+        if other.is_certain() || !self.is_possible() {
+            return other;
+        }
+
+        // If either side passes independently of luck, the combination does
+        // as well.
+        let min_daily_luck = match (self.min_daily_luck, other.min_daily_luck) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            _ => None,
+        };
+
+        Self {
+            chance: 1.0 - (1.0 - self.chance) * (1.0 - other.chance),
+            min_daily_luck,
+        }
+    }
+
+    // Daily luck thresholds are kept as is.
+    fn not(self) -> Self {
+        Self {
+            chance: 1.0 - self.chance,
+            min_daily_luck: self.min_daily_luck,
+        }
+    }
+}
+
+impl From<QueryResult> for bool {
+    fn from(value: QueryResult) -> Self {
+        value.is_possible()
+    }
+}
+
+/// Game state needed to evaluate queries.
+pub trait QueryContext {
+    fn game_id(&self) -> u32;
+    fn days_played(&self) -> u32;
+
+    /// Daily luck used for unseeded `@addDailyLuck` rolls.
+    fn daily_luck(&self) -> f64;
+
+    // Day one is `days_played() == 1`.  Contexts that haven't started the
+    // first day yet are treated as being on it.
+    fn year(&self) -> u32 {
+        self.days_played().saturating_sub(1) / (28 * 4) + 1
+    }
+
+    fn season(&self) -> Season {
+        match (self.days_played().saturating_sub(1) / 28) % 4 {
+            0 => Season::Spring,
+            1 => Season::Summer,
+            2 => Season::Fall,
+            _ => Season::Winter,
+        }
+    }
+
+    fn day_of_month(&self) -> u32 {
+        self.days_played().saturating_sub(1) % 28 + 1
+    }
+
+    /// The ID of the location context (i.e. `Default`, `Island`, or
+    /// `Desert`) that the query is being evaluated in.
+    fn location_context(&self) -> &str {
+        "Default"
+    }
+
+    fn is_green_rain_day<G: SeedGenerator>(&self) -> bool {
+        if self.season() != Season::Summer {
+            return false;
+        }
+        let mut rng = Rng::new(generate_seed!(G, self.year() * 777, self.game_id()));
+
+        self.day_of_month() == *rng.chooose_from(&[5u32, 6, 7, 14, 15, 16, 18, 23])
+    }
+
+    /// The item `ITEM_*` queries check or `None` if there isn't one.
+    fn target_item(&self) -> Option<&TargetItem> {
+        None
+    }
+
+    /// Returns the value of a stat or `None` if it is unknown.
+    fn player_stat(&self, player: &PlayerTarget, stat: &str) -> Option<u32>;
+
+    /// Returns whether a player has received a piece of mail or `None` if it
+    /// is unknown.
+    fn player_has_mail(&self, player: &PlayerTarget, mail: &str) -> Option<bool>;
+
+    /// Returns whether a player has seen an event or `None` if it is unknown.
+    fn player_has_seen_event(&self, player: &PlayerTarget, event: &str) -> Option<bool>;
+
+    /// Returns whether a special order rule is active or `None` if it is
+    /// unknown.
+    fn player_special_order_rule_active(&self, player: &PlayerTarget, rule: &str) -> Option<bool>;
+}
+
+fn roll(rng: Option<&mut Rng>, chance: f64, daily_luck: Option<f64>) -> QueryResult {
+    match (rng, daily_luck) {
+        // The game compares `NextDouble() < chance + dailyLuck`.  Rearranging
+        // that gives us the luck needed to pass.
+        (Some(rng), Some(_)) => QueryResult::with_daily_luck(rng.next_double() - chance),
+        (Some(rng), None) => QueryResult::from_bool(rng.next_weighted_bool(chance)),
+        (None, luck) => QueryResult::with_chance((chance + luck.unwrap_or(0.0)).clamp(0.0, 1.0)),
+    }
+}
+
+fn unknown<T>(query: &str, what: &str) -> Result<T> {
+    Err(anyhow!("{query}: can't determine {what}"))
+}
+
+fn target_item<'a, C: QueryContext>(context: &'a C, query: &str) -> Result<&'a TargetItem> {
+    context
+        .target_item()
+        .map_or_else(|| unknown(query, "the target item"), Ok)
+}
+
+impl Query {
+    /// The name of the query as it appears in game data.
+    pub fn name(&self) -> &str {
+        match self {
+            Query::Any(_) => "ANY",
+            Query::True => "TRUE",
+            Query::False => "FALSE",
+            Query::DayOfMonth(_) => "DAY_OF_MONTH",
+            Query::DaysPlayed { .. } => "DAYS_PLAYED",
+            Query::IsGreenRainDay => "IS_GREEN_RAIN_DAY",
+            Query::ItemCategory(_) => "ITEM_CATEGORY",
+            Query::ItemId(_) => "ITEM_ID",
+            Query::ItemIdPrefix(_) => "ITEM_ID_PREFIX",
+            Query::ItemObjectType(_) => "ITEM_OBJECT_TYPE",
+            Query::ItemType(_) => "ITEM_TYPE",
+            Query::LocationContext { .. } => "LOCATION_CONTEXT",
+            Query::PlayerHasMail { .. } => "PLAYER_HAS_MAIL",
+            Query::PlayerHasSeenEvent { .. } => "PLAYER_HAS_SEEN_EVENT",
+            Query::PlayerSpecialOrderRuleActive { .. } => "PLAYER_SPECIAL_ORDER_RULE_ACTIVE",
+            Query::PlayerStat { .. } => "PLAYER_STAT",
+            Query::Random { .. } => "RANDOM",
+            Query::Season(_) => "SEASON",
+            Query::SeasonDay(_) => "SEASON_DAY",
+            Query::SyncedRandom { .. } => "SYNCED_RANDOM",
+            Query::SyncedSummerRainRandom { .. } => "SYNCED_SUMMER_RAIN_RANDOM",
+            Query::Year { .. } => "YEAR",
+            Query::Unknown { name, .. } => name,
+        }
+    }
+
+    // Whether the evaluator knows how to evaluate the query at all.
+    fn is_implemented(&self) -> bool {
+        !matches!(
+            self,
+            Query::Unknown { .. }
+                | Query::SyncedRandom {
+                    interval: RandomInterval::Tick,
+                    ..
+                }
+        )
+    }
+
+    /// Collect the names of supported and unsupported queries.
+    ///
+    /// `ANY` is not counted itself.  Instead its sub-queries are.
+    fn collect_support<'a>(
+        &'a self,
+        is_supported: &impl Fn(&Query) -> bool,
+        supported: &mut Vec<&'a str>,
+        unsupported: &mut Vec<&'a str>,
+    ) {
+        match self {
+            Query::Any(queries) => queries
+                .iter()
+                .for_each(|query| query.collect_support_into(is_supported, supported, unsupported)),
+            _ if is_supported(self) => supported.push(self.name()),
+            _ => unsupported.push(self.name()),
+        }
+    }
+
+    fn evaluate<G: SeedGenerator, C: QueryContext>(
+        &self,
+        context: &C,
+        rng: Option<&mut Rng>,
+    ) -> Result<QueryResult> {
+        let result = match self {
+            Query::Any(queries) => {
+                let mut rng = rng;
+                let mut result = QueryResult::FALSE;
+                for query in queries {
+                    result = result.or(query.evaluate::<G, C>(context, rng.as_deref_mut())?);
+                    if result.is_certain() {
+                        break;
+                    }
+                }
+                result
+            }
+            Query::True => QueryResult::TRUE,
+            Query::False => QueryResult::FALSE,
+            Query::DayOfMonth(days) => {
+                let day_of_month = context.day_of_month();
+                QueryResult::from_bool(days.iter().any(|day| match day {
+                    DayOfMonth::Day(day) => *day == day_of_month,
+                    DayOfMonth::Even => day_of_month.is_multiple_of(2),
+                    DayOfMonth::Odd => !day_of_month.is_multiple_of(2),
+                }))
+            }
+            Query::DaysPlayed { min, max } => {
+                let days_played = context.days_played();
+                QueryResult::from_bool(
+                    days_played >= *min && max.is_none_or(|max| days_played <= max),
+                )
+            }
+            Query::IsGreenRainDay => QueryResult::from_bool(context.is_green_rain_day::<G>()),
+            Query::ItemCategory(categories) => {
+                let item = target_item(context, self.name())?;
+                QueryResult::from_bool(categories.contains(&item.category))
+            }
+            Query::ItemId(ids) => {
+                let item = target_item(context, self.name())?;
+                QueryResult::from_bool(ids.iter().any(|id| item.has_id(id)))
+            }
+            Query::ItemIdPrefix(prefixes) => {
+                let item = target_item(context, self.name())?;
+                QueryResult::from_bool(
+                    prefixes
+                        .iter()
+                        .any(|prefix| item.qualified_id.starts_with(prefix.as_str())),
+                )
+            }
+            Query::ItemObjectType(types) => {
+                let item = target_item(context, self.name())?;
+                QueryResult::from_bool(item.object_type.as_ref().is_some_and(|object_type| {
+                    types.iter().any(|ty| ty.eq_ignore_ascii_case(object_type))
+                }))
+            }
+            Query::ItemType(types) => {
+                let item = target_item(context, self.name())?;
+                QueryResult::from_bool(
+                    types
+                        .iter()
+                        .any(|ty| ty.eq_ignore_ascii_case(item.type_definition_id())),
+                )
+            }
+            Query::LocationContext { location, contexts } => {
+                if !location.eq_ignore_ascii_case("Here")
+                    && !location.eq_ignore_ascii_case("Target")
+                {
+                    return unknown("LOCATION_CONTEXT", &format!("location {location}"));
+                }
+                let current = context.location_context();
+                QueryResult::from_bool(contexts.iter().any(|id| id.eq_ignore_ascii_case(current)))
+            }
+            Query::PlayerHasMail { player, mail } => match context.player_has_mail(player, mail) {
+                Some(value) => QueryResult::from_bool(value),
+                None => return unknown("PLAYER_HAS_MAIL", &format!("mail {mail}")),
+            },
+            Query::PlayerHasSeenEvent { player, events } => {
+                let mut seen = false;
+                for event in events {
+                    match context.player_has_seen_event(player, event) {
+                        Some(value) => seen |= value,
+                        None => return unknown("PLAYER_HAS_SEEN_EVENT", &format!("event {event}")),
+                    }
+                }
+                QueryResult::from_bool(seen)
+            }
+            Query::PlayerSpecialOrderRuleActive { player, rule } => {
+                match context.player_special_order_rule_active(player, rule) {
+                    Some(value) => QueryResult::from_bool(value),
+                    None => {
+                        return unknown("PLAYER_SPECIAL_ORDER_RULE_ACTIVE", &format!("rule {rule}"))
+                    }
+                }
+            }
+            Query::PlayerStat {
+                player,
+                stat,
+                min,
+                max,
+            } => match context.player_stat(player, stat) {
+                Some(value) => {
+                    QueryResult::from_bool(value >= *min && max.is_none_or(|max| value <= max))
+                }
+                None => return unknown("PLAYER_STAT", &format!("stat {stat}")),
+            },
+            Query::Random {
+                chance,
+                add_daily_luck,
+            } => roll(rng, *chance, add_daily_luck.then(|| context.daily_luck())),
+            Query::Season(seasons) => QueryResult::from_bool(seasons.contains(&context.season())),
+            Query::SeasonDay(days) => {
+                let season = context.season();
+                let day_of_month = context.day_of_month();
+                QueryResult::from_bool(
+                    days.iter()
+                        .any(|(s, day)| *s == season && *day == day_of_month),
+                )
+            }
+            Query::SyncedRandom {
+                interval,
+                key,
+                chance,
+                add_daily_luck,
+            } => {
+                let interval_seed = match interval {
+                    RandomInterval::Day => context.days_played() as i32,
+                    // The game hashes the lowercase season name (i.e. "spring1").
+                    RandomInterval::Season => xxh32(
+                        format!("{}{}", context.season(), context.year())
+                            .to_lowercase()
+                            .as_bytes(),
+                        0,
+                    ) as i32,
+                    RandomInterval::Year => context.year() as i32,
+                    RandomInterval::Tick => return unknown("SYNCED_RANDOM", "game ticks"),
+                };
+                let mut rng = Rng::new(generate_seed!(
+                    G,
+                    xxh32(key.as_bytes(), 0) as i32, // i32 conversion here is very important
+                    context.game_id(),
+                    interval_seed
+                ));
+                roll(
+                    Some(&mut rng),
+                    *chance,
+                    add_daily_luck.then(|| context.daily_luck()),
+                )
+            }
+            Query::SyncedSummerRainRandom {
+                base_chance,
+                day_multiplier,
+            } => {
+                // i32 conversion here is very important
+                let key = xxh32("summer_rain_chance".as_bytes(), 0) as i32;
+                let chance = (*base_chance as f32
+                    + (context.day_of_month() as f32) * *day_multiplier as f32)
+                    as f64;
+                QueryResult::from_bool(
+                    Rng::new(generate_day_save_seed!(
+                        G,
+                        context.days_played(),
+                        context.game_id(),
+                        key
+                    ))
+                    .next_weighted_bool(chance),
+                )
+            }
+            Query::Year { min, max } => {
+                let year = context.year();
+                QueryResult::from_bool(year >= *min && max.is_none_or(|max| year <= max))
+            }
+            Query::Unknown { name, .. } => {
+                return Err(anyhow!("unsupported query {name}"));
+            }
+        };
+
+        Ok(result)
+    }
+}
+
+impl GameStateQuery {
+    pub fn parse(query: &str) -> Result<Self> {
+        parser::parse(query)
+    }
+
+    fn collect_support_into<'a>(
+        &'a self,
+        is_supported: &impl Fn(&Query) -> bool,
+        supported: &mut Vec<&'a str>,
+        unsupported: &mut Vec<&'a str>,
+    ) {
+        self.clauses.iter().for_each(|clause| {
+            clause
+                .query
+                .collect_support(is_supported, supported, unsupported)
+        });
+    }
+
+    // Returns the supported and unsupported query names.
+    fn collect_support(&self, is_supported: impl Fn(&Query) -> bool) -> (Vec<&str>, Vec<&str>) {
+        let mut supported = Vec::new();
+        let mut unsupported = Vec::new();
+        self.collect_support_into(&is_supported, &mut supported, &mut unsupported);
+        (supported, unsupported)
+    }
+
+    fn classify(supported: &[&str], unsupported: &[&str]) -> QuerySupport {
+        match (supported.is_empty(), unsupported.is_empty()) {
+            (_, true) => QuerySupport::Full,
+            (true, false) => QuerySupport::Unsupported,
+            (false, false) => QuerySupport::Partial,
+        }
+    }
+
+    /// Returns the names of the queries which can not be evaluated.
+    pub fn unsupported_queries(&self) -> Vec<&str> {
+        self.collect_support(Query::is_implemented).1
+    }
+
+    /// Returns how well the evaluator supports this query.
+    pub fn support(&self) -> QuerySupport {
+        let (supported, unsupported) = self.collect_support(Query::is_implemented);
+        Self::classify(&supported, &unsupported)
+    }
+
+    /// Returns the names of the queries which fail to evaluate against
+    /// `context`.
+    ///
+    /// Unlike [GameStateQuery::unsupported_queries] this includes queries
+    /// that are implemented but ask for state the context doesn't track,
+    /// like unknown mail flags or a target item.
+    pub fn unsupported_queries_for<G: SeedGenerator, C: QueryContext>(
+        &self,
+        context: &C,
+    ) -> Vec<&str> {
+        self.collect_support(|query| query.evaluate::<G, C>(context, None).is_ok())
+            .1
+    }
+
+    /// Returns how well this query can be evaluated against `context`.
+    pub fn support_for<G: SeedGenerator, C: QueryContext>(&self, context: &C) -> QuerySupport {
+        let (supported, unsupported) =
+            self.collect_support(|query| query.evaluate::<G, C>(context, None).is_ok());
+        Self::classify(&supported, &unsupported)
+    }
+
+    /// Evaluate the query.
+    ///
+    /// `rng` is the random number generator the game passes to the query.  If
+    /// it is `None`, `RANDOM` queries are not rolled and their chance is
+    /// returned in the result instead.
+    ///
+    /// Like the game, clauses are evaluated in order and evaluation stops at
+    /// the first clause that can not pass.  This is important to preserve
+    /// the number of pulls made on `rng`.
+    pub fn evaluate<G: SeedGenerator, C: QueryContext>(
+        &self,
+        context: &C,
+        mut rng: Option<&mut Rng>,
+    ) -> Result<QueryResult> {
+        let mut result = QueryResult::TRUE;
+        for clause in &self.clauses {
+            let clause_result = clause.query.evaluate::<G, C>(context, rng.as_deref_mut())?;
+            let clause_result = if clause.negated {
+                clause_result.not()
+            } else {
+                clause_result
+            };
+
+            result = result.and(clause_result);
+            if !result.is_possible() {
+                break;
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+impl FromStr for GameStateQuery {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl Display for PlayerTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Any => f.write_str("Any"),
+            Self::All => f.write_str("All"),
+            Self::Current => f.write_str("Current"),
+            Self::Host => f.write_str("Host"),
+            Self::Target => f.write_str("Target"),
+            Self::Id(id) => write!(f, "{id}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rng::HashedSeedGenerator;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct TestContext {
+        days_played: u32,
+        trash_cans_checked: u32,
+        has_mail: bool,
+        target_item: Option<TargetItem>,
+    }
+
+    impl QueryContext for TestContext {
+        fn game_id(&self) -> u32 {
+            254546202
+        }
+
+        fn days_played(&self) -> u32 {
+            self.days_played
+        }
+
+        fn daily_luck(&self) -> f64 {
+            0.0
+        }
+
+        fn target_item(&self) -> Option<&TargetItem> {
+            self.target_item.as_ref()
+        }
+
+        fn player_stat(&self, _player: &PlayerTarget, stat: &str) -> Option<u32> {
+            stat.eq_ignore_ascii_case("trashCansChecked")
+                .then_some(self.trash_cans_checked)
+        }
+
+        fn player_has_mail(&self, _player: &PlayerTarget, _mail: &str) -> Option<bool> {
+            Some(self.has_mail)
+        }
+
+        fn player_has_seen_event(&self, _player: &PlayerTarget, _event: &str) -> Option<bool> {
+            None
+        }
+
+        fn player_special_order_rule_active(
+            &self,
+            _player: &PlayerTarget,
+            _rule: &str,
+        ) -> Option<bool> {
+            Some(false)
+        }
+    }
+
+    fn evaluate(query: &str, context: &TestContext) -> Result<QueryResult> {
+        GameStateQuery::parse(query)?.evaluate::<HashedSeedGenerator, _>(context, None)
+    }
+
+    #[test]
+    fn query_parses_correctly() {
+        assert_eq!(
+            GameStateQuery::parse(
+                "SEASON spring fall, !DAY_OF_MONTH 1 even, RANDOM .25 @addDailyLuck"
+            )
+            .unwrap(),
+            GameStateQuery {
+                clauses: vec![
+                    QueryClause {
+                        negated: false,
+                        query: Query::Season(vec![Season::Spring, Season::Fall]),
+                    },
+                    QueryClause {
+                        negated: true,
+                        query: Query::DayOfMonth(vec![DayOfMonth::Day(1), DayOfMonth::Even]),
+                    },
+                    QueryClause {
+                        negated: false,
+                        query: Query::Random {
+                            chance: 0.25,
+                            add_daily_luck: true,
+                        },
+                    },
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn any_query_parses_sub_queries() {
+        assert_eq!(
+            GameStateQuery::parse("ANY \"SEASON_DAY Spring 1\" \"YEAR 2, FALSE\"").unwrap(),
+            GameStateQuery {
+                clauses: vec![QueryClause {
+                    negated: false,
+                    query: Query::Any(vec![
+                        GameStateQuery {
+                            clauses: vec![QueryClause {
+                                negated: false,
+                                query: Query::SeasonDay(vec![(Season::Spring, 1)]),
+                            }]
+                        },
+                        GameStateQuery {
+                            clauses: vec![
+                                QueryClause {
+                                    negated: false,
+                                    query: Query::Year { min: 2, max: None },
+                                },
+                                QueryClause {
+                                    negated: false,
+                                    query: Query::False,
+                                },
+                            ]
+                        },
+                    ]),
+                }]
+            }
+        );
+    }
+
+    #[test]
+    fn bad_arguments_return_error() {
+        assert!(GameStateQuery::parse("DAYS_PLAYED").is_err());
+        assert!(GameStateQuery::parse("SEASON autumn").is_err());
+        assert!(GameStateQuery::parse("SEASON_DAY spring").is_err());
+        assert!(GameStateQuery::parse("PLAYER_STAT Nobody stat 1").is_err());
+        assert!(GameStateQuery::parse("DAYS_PLAYED 3 @addDailyLuck").is_err());
+    }
+
+    #[test]
+    fn synced_season_random_hashes_lowercase_season() {
+        // Seeded with the hash of "spring1" the roll is 0.765.  Hashing
+        // "Spring1" instead would roll 0.831.
+        for days_played in 1..=28 {
+            let context = TestContext {
+                days_played,
+                ..Default::default()
+            };
+            assert_eq!(
+                evaluate("SYNCED_RANDOM season test_key .77", &context).unwrap(),
+                QueryResult::from_bool(true)
+            );
+            assert_eq!(
+                evaluate("SYNCED_RANDOM season test_key .76", &context).unwrap(),
+                QueryResult::from_bool(false)
+            );
+        }
+    }
+
+    #[test]
+    fn date_queries_handle_first_day() {
+        let context = TestContext::default();
+        assert_eq!(
+            evaluate("SEASON_DAY spring 1, YEAR 1", &context).unwrap(),
+            QueryResult::from_bool(true)
+        );
+    }
+
+    #[test]
+    fn item_queries_check_target_item() {
+        let context = TestContext {
+            target_item: Some(TargetItem {
+                qualified_id: "(O)388".to_string(),
+                category: -16,
+                object_type: Some("Basic".to_string()),
+            }),
+            ..Default::default()
+        };
+        let passes = |query: &str| evaluate(query, &context).unwrap().is_certain();
+
+        assert!(passes("ITEM_ID Target (O)390 (O)388"));
+        assert!(passes("ITEM_ID Target 388"));
+        assert!(!passes("ITEM_ID Target (BC)388"));
+        assert!(passes("ITEM_ID_PREFIX Target (O)38"));
+        assert!(passes("ITEM_CATEGORY Target -15 -16"));
+        assert!(!passes("ITEM_CATEGORY Target -15"));
+        assert!(passes("!ITEM_OBJECT_TYPE Target Arch Minerals"));
+        assert!(passes("ITEM_TYPE Target (O)"));
+        assert!(!passes("ITEM_TYPE Target (O)3"));
+        assert!(!passes("ITEM_TYPE Target ("));
+
+        // Only the target item is supported.
+        assert!(evaluate("ITEM_ID Input (O)388", &context).is_err());
+        assert!(evaluate("ITEM_ID Target (O)388", &TestContext::default()).is_err());
+    }
+
+    #[test]
+    fn unknown_queries_fail_evaluation() {
+        let query = GameStateQuery::parse("SOME_MOD_QUERY arg").unwrap();
+        assert!(query
+            .evaluate::<HashedSeedGenerator, _>(&TestContext::default(), None)
+            .is_err());
+    }
+
+    #[test]
+    fn query_support_is_classified() {
+        let support = |query: &str| GameStateQuery::parse(query).unwrap().support();
+        assert_eq!(support("SEASON spring, RANDOM .5"), QuerySupport::Full);
+        assert_eq!(support("TRUE"), QuerySupport::Full);
+        assert_eq!(
+            support("SEASON spring, ITEM_HAS_TAG Target book_item"),
+            QuerySupport::Partial
+        );
+        assert_eq!(
+            support("ANY \"YEAR 2\" \"WORLD_STATE_ID foo\""),
+            QuerySupport::Partial
+        );
+        assert_eq!(
+            support("SYNCED_RANDOM tick key .5"),
+            QuerySupport::Unsupported
+        );
+        assert_eq!(
+            GameStateQuery::parse("SEASON fall, ITEM_HAS_TAG Target book_item, FOO")
+                .unwrap()
+                .unsupported_queries(),
+            vec!["ITEM_HAS_TAG", "FOO"]
+        );
+    }
+
+    #[test]
+    fn query_support_depends_on_context() {
+        let context = TestContext::default();
+        let query = |query: &str| GameStateQuery::parse(query).unwrap();
+        let support = |q: &str| query(q).support_for::<HashedSeedGenerator, _>(&context);
+
+        assert_eq!(
+            support("SEASON spring, PLAYER_STAT Current trashCansChecked 5"),
+            QuerySupport::Full
+        );
+        assert_eq!(
+            support("SEASON spring, PLAYER_STAT Current giftsGiven 5"),
+            QuerySupport::Partial
+        );
+        assert_eq!(
+            support("PLAYER_HAS_SEEN_EVENT Current 1234"),
+            QuerySupport::Unsupported
+        );
+        assert_eq!(support("ITEM_ID Target (O)74"), QuerySupport::Unsupported);
+        assert_eq!(query("ITEM_ID Target (O)74").support(), QuerySupport::Full);
+
+        let context = TestContext {
+            target_item: Some(TargetItem {
+                qualified_id: "(O)74".to_string(),
+                category: 0,
+                object_type: None,
+            }),
+            ..Default::default()
+        };
+        assert_eq!(
+            query("ANY \"ITEM_ID Target (O)74\" \"PLAYER_HAS_SEEN_EVENT Current 1\"")
+                .unsupported_queries_for::<HashedSeedGenerator, _>(&context),
+            vec!["PLAYER_HAS_SEEN_EVENT"]
+        );
+    }
+
+    #[test]
+    fn date_queries_evaluate_correctly() {
+        let context = TestContext {
+            days_played: 28 + 14,
+            ..Default::default()
+        };
+        assert!(evaluate("SEASON summer", &context).unwrap().is_certain());
+        assert!(evaluate("SEASON_DAY spring 14 summer 14", &context)
+            .unwrap()
+            .is_certain());
+        assert!(!evaluate("SEASON_DAY spring 14", &context)
+            .unwrap()
+            .is_possible());
+        assert!(evaluate("DAY_OF_MONTH even", &context)
+            .unwrap()
+            .is_certain());
+        assert!(!evaluate("!DAY_OF_MONTH 14", &context)
+            .unwrap()
+            .is_possible());
+        assert!(evaluate("DAYS_PLAYED 42 42, YEAR 1", &context)
+            .unwrap()
+            .is_certain());
+        assert!(!evaluate("YEAR 2", &context).unwrap().is_possible());
+    }
+
+    #[test]
+    fn unseeded_random_returns_chance() {
+        let context = TestContext {
+            days_played: 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            evaluate("RANDOM .25, !RANDOM .5", &context).unwrap(),
+            QueryResult::with_chance(0.125)
+        );
+        assert_eq!(
+            evaluate("ANY \"RANDOM .5\" \"RANDOM .5\"", &context).unwrap(),
+            QueryResult::with_chance(0.75)
+        );
+    }
+
+    #[test]
+    fn clauses_short_circuit() {
+        let context = TestContext {
+            days_played: 1,
+            trash_cans_checked: 10,
+            ..Default::default()
+        };
+        let mut rng = Rng::new(1234);
+        let query =
+            GameStateQuery::parse("PLAYER_STAT Current trashCansChecked 20, RANDOM .002").unwrap();
+        query
+            .evaluate::<HashedSeedGenerator, _>(&context, Some(&mut rng))
+            .unwrap();
+
+        // The failing stat check means no value should have been pulled.
+        let mut expected = Rng::new(1234);
+        assert_eq!(rng.next_i32(), expected.next_i32());
+    }
+
+    #[test]
+    fn seeded_daily_luck_random_returns_min_luck() {
+        let context = TestContext {
+            days_played: 1,
+            ..Default::default()
+        };
+        let mut rng = Rng::new(1234);
+        let result = GameStateQuery::parse("RANDOM 0.9 @addDailyLuck")
+            .unwrap()
+            .evaluate::<HashedSeedGenerator, _>(&context, Some(&mut rng))
+            .unwrap();
+
+        let mut expected = Rng::new(1234);
+        assert_eq!(
+            result,
+            QueryResult::with_daily_luck(expected.next_double() - 0.9)
+        );
+    }
+
+    #[test]
+    fn negated_daily_luck_random_keeps_min_luck() {
+        let result = QueryResult {
+            chance: 0.25,
+            min_daily_luck: Some(0.02),
+        };
+        assert_eq!(
+            result.not(),
+            QueryResult {
+                chance: 0.75,
+                min_daily_luck: Some(0.02),
+            }
+        );
+
+        let context = TestContext {
+            days_played: 1,
+            ..Default::default()
+        };
+        let mut rng = Rng::new(1234);
+        let result = GameStateQuery::parse("!RANDOM 0.9 @addDailyLuck")
+            .unwrap()
+            .evaluate::<HashedSeedGenerator, _>(&context, Some(&mut rng))
+            .unwrap();
+        assert!(!result.is_possible());
+    }
+
+    #[test]
+    fn unknown_state_returns_error() {
+        let context = TestContext {
+            days_played: 1,
+            ..Default::default()
+        };
+        assert!(evaluate("PLAYER_HAS_SEEN_EVENT Any 191393", &context).is_err());
+        assert!(evaluate("PLAYER_STAT Current GeodesCracked 16", &context).is_err());
+        assert!(evaluate("PLAYER_HAS_MAIL Host ccMovieTheater", &context)
+            .unwrap()
+            .chance
+            .eq(&0.0));
+    }
+}
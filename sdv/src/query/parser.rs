@@ -0,0 +1,370 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+
+use crate::common::Season;
+
+use super::{DayOfMonth, GameStateQuery, PlayerTarget, Query, QueryClause, RandomInterval};
+
+const ADD_DAILY_LUCK: &str = "@addDailyLuck";
+
+/// Split a query into its comma separated clauses.
+///
+/// Commas inside of quoted arguments (as used by `ANY`) do not split clauses.
+fn split_clauses(query: &str) -> Vec<&str> {
+    let mut clauses = Vec::new();
+    let mut in_quotes = false;
+    let mut escaped = false;
+    let mut start = 0;
+
+    for (i, c) in query.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => {
+                clauses.push(&query[start..i]);
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    clauses.push(&query[start..]);
+
+    clauses
+        .into_iter()
+        .map(|clause| clause.trim())
+        .filter(|clause| !clause.is_empty())
+        .collect()
+}
+
+/// Split a clause into space separated arguments.
+///
+/// This mirrors the game's `ArgUtility.SplitBySpaceQuoteAware()`.  Quoted
+/// arguments may contain spaces and `\"` can be used to escape a quote.
+fn split_args(clause: &str) -> Result<Vec<String>> {
+    let mut args = Vec::new();
+    let mut arg = String::new();
+    let mut in_quotes = false;
+    let mut was_quoted = false;
+    let mut chars = clause.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(escaped) => arg.push(escaped),
+                None => return Err(anyhow!("trailing escape in query clause \"{clause}\"")),
+            },
+            '"' => {
+                in_quotes = !in_quotes;
+                was_quoted = true;
+            }
+            ' ' if !in_quotes => {
+                if !arg.is_empty() || was_quoted {
+                    args.push(std::mem::take(&mut arg));
+                }
+                was_quoted = false;
+            }
+            _ => arg.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(anyhow!("unterminated quote in query clause \"{clause}\""));
+    }
+
+    if !arg.is_empty() || was_quoted {
+        args.push(arg);
+    }
+
+    Ok(args)
+}
+
+fn arg<'a>(name: &str, args: &'a [String], index: usize) -> Result<&'a str> {
+    args.get(index)
+        .map(|arg| arg.as_str())
+        .ok_or_else(|| anyhow!("{name} requires at least {} argument(s)", index + 1))
+}
+
+fn parse_arg<T: FromStr>(name: &str, args: &[String], index: usize) -> Result<T> {
+    let value = arg(name, args, index)?;
+    value
+        .parse()
+        .map_err(|_| anyhow!("{name} can't parse argument \"{value}\""))
+}
+
+fn parse_optional_arg<T: FromStr>(name: &str, args: &[String], index: usize) -> Result<Option<T>> {
+    if args.len() <= index {
+        return Ok(None);
+    }
+    parse_arg(name, args, index).map(Some)
+}
+
+fn parse_season(name: &str, value: &str) -> Result<Season> {
+    match value.to_ascii_lowercase().as_str() {
+        "spring" => Ok(Season::Spring),
+        "summer" => Ok(Season::Summer),
+        "fall" => Ok(Season::Fall),
+        "winter" => Ok(Season::Winter),
+        _ => Err(anyhow!("{name} has unknown season \"{value}\"")),
+    }
+}
+
+impl FromStr for PlayerTarget {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "any" => Ok(Self::Any),
+            "all" => Ok(Self::All),
+            "current" => Ok(Self::Current),
+            "host" => Ok(Self::Host),
+            "target" => Ok(Self::Target),
+            _ => s
+                .parse::<i64>()
+                .map(Self::Id)
+                .map_err(|_| anyhow!("unknown player target \"{s}\"")),
+        }
+    }
+}
+
+impl FromStr for RandomInterval {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "tick" => Ok(Self::Tick),
+            "day" => Ok(Self::Day),
+            "season" => Ok(Self::Season),
+            "year" => Ok(Self::Year),
+            _ => Err(anyhow!("unknown random interval \"{s}\"")),
+        }
+    }
+}
+
+impl FromStr for DayOfMonth {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "even" => Ok(Self::Even),
+            "odd" => Ok(Self::Odd),
+            _ => s
+                .parse::<u32>()
+                .map(Self::Day)
+                .map_err(|_| anyhow!("unknown day of month \"{s}\"")),
+        }
+    }
+}
+
+// Arguments of an `ITEM_*` query that checks the `Target` item.  Returns
+// `None` for other items, which aren't supported.
+fn item_args<'a>(name: &str, args: &'a [String]) -> Result<Option<&'a [String]>> {
+    arg(name, args, 1)?;
+    Ok(args[0].eq_ignore_ascii_case("Target").then_some(&args[1..]))
+}
+
+fn parse_query(name: &str, args: &[String]) -> Result<Query> {
+    // Query names are case insensitive in game.
+    let query = match name.to_ascii_uppercase().as_str() {
+        "ANY" => Query::Any(
+            args.iter()
+                .map(|arg| GameStateQuery::parse(arg))
+                .collect::<Result<Vec<_>>>()?,
+        ),
+        "TRUE" => Query::True,
+        "FALSE" => Query::False,
+        "DAY_OF_MONTH" => Query::DayOfMonth(
+            args.iter()
+                .map(|arg| arg.parse())
+                .collect::<Result<Vec<_>>>()?,
+        ),
+        "DAYS_PLAYED" => Query::DaysPlayed {
+            min: parse_arg(name, args, 0)?,
+            max: parse_optional_arg(name, args, 1)?,
+        },
+        "IS_GREEN_RAIN_DAY" => Query::IsGreenRainDay,
+        "ITEM_CATEGORY" | "ITEM_ID" | "ITEM_ID_PREFIX" | "ITEM_OBJECT_TYPE" | "ITEM_TYPE" => {
+            parse_item_query(name, args)?
+        }
+        "LOCATION_CONTEXT" => {
+            arg(name, args, 1)?;
+            Query::LocationContext {
+                location: args[0].clone(),
+                contexts: args[1..].to_vec(),
+            }
+        }
+        "PLAYER_HAS_MAIL" => Query::PlayerHasMail {
+            player: parse_arg(name, args, 0)?,
+            mail: arg(name, args, 1)?.to_string(),
+        },
+        "PLAYER_HAS_SEEN_EVENT" => {
+            arg(name, args, 1)?;
+            Query::PlayerHasSeenEvent {
+                player: parse_arg(name, args, 0)?,
+                events: args[1..].to_vec(),
+            }
+        }
+        "PLAYER_SPECIAL_ORDER_RULE_ACTIVE" => Query::PlayerSpecialOrderRuleActive {
+            player: parse_arg(name, args, 0)?,
+            rule: arg(name, args, 1)?.to_string(),
+        },
+        "PLAYER_STAT" => Query::PlayerStat {
+            player: parse_arg(name, args, 0)?,
+            stat: arg(name, args, 1)?.to_string(),
+            min: parse_arg(name, args, 2)?,
+            max: parse_optional_arg(name, args, 3)?,
+        },
+        "RANDOM" => Query::Random {
+            chance: parse_arg(name, args, 0)?,
+            add_daily_luck: false,
+        },
+        "SEASON" => {
+            arg(name, args, 0)?;
+            Query::Season(
+                args.iter()
+                    .map(|arg| parse_season(name, arg))
+                    .collect::<Result<Vec<_>>>()?,
+            )
+        }
+        "SEASON_DAY" => {
+            if args.is_empty() || !args.len().is_multiple_of(2) {
+                return Err(anyhow!("{name} requires season and day pairs"));
+            }
+            Query::SeasonDay(
+                args.chunks(2)
+                    .map(|pair| Ok((parse_season(name, &pair[0])?, parse_arg(name, pair, 1)?)))
+                    .collect::<Result<Vec<_>>>()?,
+            )
+        }
+        "SYNCED_RANDOM" => Query::SyncedRandom {
+            interval: parse_arg(name, args, 0)?,
+            key: arg(name, args, 1)?.to_string(),
+            chance: parse_arg(name, args, 2)?,
+            add_daily_luck: false,
+        },
+        "SYNCED_SUMMER_RAIN_RANDOM" => Query::SyncedSummerRainRandom {
+            base_chance: parse_optional_arg(name, args, 0)?.unwrap_or(0.12),
+            day_multiplier: parse_optional_arg(name, args, 1)?.unwrap_or(0.003),
+        },
+        "YEAR" => Query::Year {
+            min: parse_arg(name, args, 0)?,
+            max: parse_optional_arg(name, args, 1)?,
+        },
+        _ => Query::Unknown {
+            name: name.to_string(),
+            args: args.to_vec(),
+        },
+    };
+
+    Ok(query)
+}
+
+fn parse_item_query(name: &str, args: &[String]) -> Result<Query> {
+    let Some(item_args) = item_args(name, args)? else {
+        return Ok(Query::Unknown {
+            name: name.to_string(),
+            args: args.to_vec(),
+        });
+    };
+
+    let query = match name.to_ascii_uppercase().as_str() {
+        "ITEM_CATEGORY" => Query::ItemCategory(
+            (0..item_args.len())
+                .map(|i| parse_arg(name, item_args, i))
+                .collect::<Result<Vec<_>>>()?,
+        ),
+        "ITEM_ID" => Query::ItemId(item_args.to_vec()),
+        "ITEM_ID_PREFIX" => Query::ItemIdPrefix(item_args.to_vec()),
+        "ITEM_OBJECT_TYPE" => Query::ItemObjectType(item_args.to_vec()),
+        _ => Query::ItemType(item_args.to_vec()),
+    };
+    Ok(query)
+}
+
+fn parse_clause(clause: &str) -> Result<QueryClause> {
+    let (negated, clause) = match clause.strip_prefix('!') {
+        Some(clause) => (true, clause),
+        None => (false, clause),
+    };
+
+    let mut args = split_args(clause)?;
+    if args.is_empty() {
+        return Err(anyhow!("empty query clause"));
+    }
+    let name = args.remove(0);
+
+    // `@addDailyLuck` can appear anywhere after the required arguments of the
+    // random queries.
+    let add_daily_luck = args
+        .iter()
+        .any(|arg| arg.eq_ignore_ascii_case(ADD_DAILY_LUCK));
+    args.retain(|arg| !arg.eq_ignore_ascii_case(ADD_DAILY_LUCK));
+
+    let mut query = parse_query(&name, &args)
+        .map_err(|e| anyhow!("error parsing query clause \"{clause}\": {e}"))?;
+
+    match &mut query {
+        Query::Random {
+            add_daily_luck: luck,
+            ..
+        }
+        | Query::SyncedRandom {
+            add_daily_luck: luck,
+            ..
+        } => *luck = add_daily_luck,
+        _ if add_daily_luck => {
+            return Err(anyhow!(
+                "{ADD_DAILY_LUCK} is not supported by query clause \"{clause}\""
+            ))
+        }
+        _ => (),
+    }
+
+    Ok(QueryClause { negated, query })
+}
+
+pub(super) fn parse(query: &str) -> Result<GameStateQuery> {
+    let clauses = split_clauses(query)
+        .into_iter()
+        .map(parse_clause)
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(GameStateQuery { clauses })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clauses_split_on_unquoted_commas() {
+        assert_eq!(
+            split_clauses("SEASON spring, ANY \"DAY_OF_MONTH 1, YEAR 2\" \"FALSE\",RANDOM .5"),
+            vec![
+                "SEASON spring",
+                "ANY \"DAY_OF_MONTH 1, YEAR 2\" \"FALSE\"",
+                "RANDOM .5"
+            ]
+        );
+    }
+
+    #[test]
+    fn args_respect_quotes_and_escapes() {
+        assert_eq!(
+            split_args("ANY \"SEASON spring\"  \"PLAYER_HAS_MAIL Any \\\"quoted\\\"\" \"\"")
+                .unwrap(),
+            vec![
+                "ANY".to_string(),
+                "SEASON spring".to_string(),
+                "PLAYER_HAS_MAIL Any \"quoted\"".to_string(),
+                "".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn unterminated_quote_returns_error() {
+        assert!(split_args("ANY \"SEASON spring").is_err());
+    }
+}
@@ -17,6 +17,8 @@
 use std::num::Wrapping;
 
 use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
 use xxhash_rust::xxh32::Xxh32;
 
 const SEED: Wrapping<i32> = Wrapping(161803398);
@@ -70,11 +72,26 @@ impl SeedGenerator for HashedSeedGenerator {
     }
 }
 
+/// A snapshot of the internal state of an [Rng].
+///
+/// Created with [Rng::state] and turned back into an [Rng] with
+/// [Rng::from_state] or [Rng::restore].  This allows a generator to be saved
+/// and later resumed from the exact same point.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RngState {
+    pub next: usize,
+    pub next_p: usize,
+    pub seed_array: Vec<i32>,
+    pub pulls: u64,
+}
+
 /// .NET Random Number Generator
+#[derive(Clone, Debug)]
 pub struct Rng {
     next: usize,
     next_p: usize,
     seed_array: [Wrapping<i32>; SEED_ARRAY_LEN],
+    pulls: u64,
 }
 
 impl Rng {
@@ -114,9 +131,64 @@ impl Rng {
             next: 0,
             next_p: 21,
             seed_array,
+            pulls: 0,
+        }
+    }
+
+    /// Create an [Rng] from a previously captured [RngState].
+    pub fn from_state(state: &RngState) -> Result<Rng> {
+        if state.seed_array.len() != SEED_ARRAY_LEN {
+            return Err(anyhow!(
+                "rng state has {} seed values instead of {}",
+                state.seed_array.len(),
+                SEED_ARRAY_LEN
+            ));
+        }
+        if state.next >= SEED_ARRAY_LEN || state.next_p >= SEED_ARRAY_LEN {
+            return Err(anyhow!(
+                "rng state indexes ({}, {}) are out of range",
+                state.next,
+                state.next_p
+            ));
+        }
+
+        let mut seed_array = [Wrapping(0i32); SEED_ARRAY_LEN];
+        for (dest, src) in seed_array.iter_mut().zip(&state.seed_array) {
+            *dest = Wrapping(*src);
+        }
+
+        Ok(Rng {
+            next: state.next,
+            next_p: state.next_p,
+            seed_array,
+            pulls: state.pulls,
+        })
+    }
+
+    /// Capture the current state of the [Rng].
+    pub fn state(&self) -> RngState {
+        RngState {
+            next: self.next,
+            next_p: self.next_p,
+            seed_array: self.seed_array.iter().map(|v| v.0).collect(),
+            pulls: self.pulls,
         }
     }
 
+    /// Restore the [Rng] to a previously captured [RngState].
+    pub fn restore(&mut self, state: &RngState) -> Result<()> {
+        *self = Self::from_state(state)?;
+        Ok(())
+    }
+
+    /// Number of raw values pulled from the [Rng] since it was seeded.
+    ///
+    /// Most methods pull a single value.  [Rng::sample_large_range] (and
+    /// [Rng::next_range] with ranges larger than `i32::MAX`) pull two.
+    pub fn pulls(&self) -> u64 {
+        self.pulls
+    }
+
     fn internal_sample(&mut self) -> i32 {
         let mut next = self.next + 1;
         let mut next_p = self.next_p + 1;
@@ -140,6 +212,7 @@ impl Rng {
 
         self.next = next;
         self.next_p = next_p;
+        self.pulls += 1;
 
         val.0
     }
@@ -149,7 +222,9 @@ impl Rng {
     /// Returned values will be between 0.0 and 1.0.  Value will
     /// Have 31 bits of "entropy".
     pub fn sample(&mut self) -> f64 {
-        self.internal_sample() as f64 / i32::MAX as f64
+        // .NET multiplies by the reciprocal which rounds differently than
+        // dividing for some samples.
+        self.internal_sample() as f64 * (1.0 / i32::MAX as f64)
     }
 
     /// Pull a floating point sample from the [Rng].
@@ -229,13 +304,142 @@ impl Rng {
         }
     }
 
+    /// Pull a 64 bit value in the range [0, max_val).
+    ///
+    /// Mirrors `Random.NextInt64(Int64)` of .NET's seeded (compatibility)
+    /// implementation.
+    pub fn next_i64_max(&mut self, max_val: i64) -> Result<i64> {
+        self.next_i64_range(0, max_val)
+    }
+
+    /// Pull a 64 bit value in the range [min_val, max_val).
+    ///
+    /// Mirrors `Random.NextInt64(Int64, Int64)` of .NET's seeded
+    /// (compatibility) implementation.  Unlike [Rng::next_range], values are
+    /// built out of multiple pulls and may reject and retry so the number of
+    /// values pulled varies.
+    pub fn next_i64_range(&mut self, min_val: i64, max_val: i64) -> Result<i64> {
+        if min_val > max_val {
+            return Err(anyhow!(
+                "max_val ({}) must be larger than min_val ({})",
+                max_val,
+                min_val
+            ));
+        }
+
+        let range = max_val.wrapping_sub(min_val) as u64;
+        if range <= 1 {
+            return Ok(min_val);
+        }
+
+        // Narrow down to the smallest power of two range that contains
+        // `range` and retry until the value falls in `range`.
+        let bits = u64::BITS - (range - 1).leading_zeros();
+        loop {
+            let result = self.next_u64() >> (u64::BITS - bits);
+            if result < range {
+                return Ok((result as i64).wrapping_add(min_val));
+            }
+        }
+    }
+
+    // Produces a value in the range [0, u64::MAX].
+    fn next_u64(&mut self) -> u64 {
+        (self.next_max(1 << 22) as u32 as u64)
+            | ((self.next_max(1 << 22) as u32 as u64) << 22)
+            | ((self.next_max(1 << 20) as u32 as u64) << 44)
+    }
+
+    /// Pull a single precision floating point sample from the [Rng].
+    pub fn next_single(&mut self) -> f32 {
+        self.sample() as f32
+    }
+
+    /// Fill `buffer` with random bytes.
+    ///
+    /// Each byte consumes one pull.
+    pub fn next_bytes(&mut self, buffer: &mut [u8]) {
+        for byte in buffer {
+            *byte = self.internal_sample() as u8;
+        }
+    }
+
     pub fn chooose_from<'a, T>(&mut self, choices: &'a [T]) -> &'a T {
         &choices[self.next_max(choices.len() as i32) as usize]
     }
+
+    /// Choose a random element of `choices`.
+    ///
+    /// Mirrors the game's `Utility.TryGetRandom()` and returns `None` without
+    /// pulling from the [Rng] if `choices` is empty.
+    pub fn try_get_random<'a, T>(&mut self, choices: &'a [T]) -> Option<&'a T> {
+        if choices.is_empty() {
+            return None;
+        }
+        Some(self.chooose_from(choices))
+    }
+
+    /// Choose a random entry of `map`.
+    ///
+    /// Mirrors the dictionary version of the game's `Utility.TryGetRandom()`.
+    /// Entries are chosen by insertion order which matches the game's
+    /// dictionary ordering as long as no entries have been removed.
+    pub fn try_get_random_entry<'a, K, V>(
+        &mut self,
+        map: &'a IndexMap<K, V>,
+    ) -> Option<(&'a K, &'a V)> {
+        if map.is_empty() {
+            return None;
+        }
+        map.get_index(self.next_max(map.len() as i32) as usize)
+    }
+
+    /// Choose an element of `choices` based on its weight.
+    ///
+    /// This is the selection the game uses for data entries with a `Weight`
+    /// field: a single value in [0, total weight) is pulled and each entry's
+    /// weight is subtracted from it, in order, until it goes negative.
+    ///
+    /// Returns `None` without pulling from the [Rng] if the total weight is
+    /// not positive.
+    pub fn choose_weighted<'a, T>(&mut self, choices: &'a [(T, i32)]) -> Option<&'a T> {
+        let total_weight: i32 = choices.iter().map(|(_, weight)| (*weight).max(0)).sum();
+        if total_weight <= 0 {
+            return None;
+        }
+
+        let mut roll = self.next_max(total_weight);
+        for (choice, weight) in choices {
+            roll -= (*weight).max(0);
+            if roll < 0 {
+                return Some(choice);
+            }
+        }
+
+        // Unreachable as long as the roll is less than the total weight.
+        None
+    }
+
+    /// Shuffle `list` in place.
+    ///
+    /// Mirrors the game's `Utility.Shuffle()` which performs a Fisher-Yates
+    /// shuffle from the end of the list.
+    pub fn shuffle<T>(&mut self, list: &mut [T]) {
+        let mut n = list.len();
+        while n > 1 {
+            let k = self.next_max(n as i32) as usize;
+            n -= 1;
+            list.swap(n, k);
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::fs::File;
+
+    use serde_json::Value;
+
     use super::*;
 
     #[test]
@@ -257,4 +461,142 @@ mod tests {
         assert_eq!(rng.next_range(1, 10).unwrap(), 3);
         assert_eq!(rng.next_range(1, 10).unwrap(), 4);
     }
+
+    #[test]
+    fn sample_multiplies_by_reciprocal() {
+        // .NET multiplies by the reciprocal of `int.MaxValue`.  Dividing by
+        // it instead gives 0x3f726f684024ded1 for this sample (9665346).
+        let mut rng = Rng::new(37);
+        for _ in 0..7 {
+            rng.next_i32();
+        }
+        assert_eq!(rng.next_double().to_bits(), 0x3f726f684024ded0);
+    }
+
+    fn arg_i64(args: &Value, index: usize) -> i64 {
+        args[index].as_i64().unwrap()
+    }
+
+    #[test]
+    fn rng_matches_reference_vectors() {
+        // Vectors are generated by `test-data/rng/generate.js`.
+        let f = File::open("test-data/rng/vectors.json").unwrap();
+        let vectors: Value = serde_json::from_reader(f).unwrap();
+
+        for vector in vectors.as_array().unwrap() {
+            let seed = vector["seed"].as_i64().unwrap() as i32;
+            let mut rng = Rng::new(seed);
+            for op in vector["ops"].as_array().unwrap() {
+                let name = op["op"].as_str().unwrap();
+                let args = &op["args"];
+                let expected = &op["result"];
+                let result: Value = match name {
+                    "next" => rng.next_i32().into(),
+                    "next_double" => format!("{:016x}", rng.next_double().to_bits()).into(),
+                    "next_max" => rng.next_max(arg_i64(args, 0) as i32).into(),
+                    "next_range" => rng
+                        .next_range(arg_i64(args, 0) as i32, arg_i64(args, 1) as i32)
+                        .unwrap()
+                        .into(),
+                    "next_bytes" => {
+                        let mut buffer = vec![0u8; arg_i64(args, 0) as usize];
+                        rng.next_bytes(&mut buffer);
+                        buffer.into()
+                    }
+                    "next_i64_range" => rng
+                        .next_i64_range(arg_i64(args, 0), arg_i64(args, 1))
+                        .unwrap()
+                        .into(),
+                    "shuffle" => {
+                        let mut list: Vec<u32> = (0..arg_i64(args, 0) as u32).collect();
+                        rng.shuffle(&mut list);
+                        list.into()
+                    }
+                    "choose_weighted" => {
+                        let choices: Vec<(usize, i32)> = args[0]
+                            .as_array()
+                            .unwrap()
+                            .iter()
+                            .enumerate()
+                            .map(|(i, weight)| (i, weight.as_i64().unwrap() as i32))
+                            .collect();
+                        rng.choose_weighted(&choices).copied().into()
+                    }
+                    _ => panic!("unknown op {}", name),
+                };
+                assert_eq!(
+                    &result, expected,
+                    "seed {seed} op {name} {args} returned wrong value"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn try_get_random_does_not_pull_when_empty() {
+        let mut rng = Rng::new(1234);
+        assert_eq!(rng.try_get_random::<i32>(&[]), None);
+        assert_eq!(rng.try_get_random_entry::<i32, i32>(&IndexMap::new()), None);
+        assert_eq!(rng.pulls(), 0);
+
+        let map = IndexMap::from([(1, "a"), (2, "b")]);
+        assert!(rng.try_get_random_entry(&map).is_some());
+        assert_eq!(rng.pulls(), 1);
+    }
+
+    #[test]
+    fn cloned_rng_produces_identical_values() {
+        let mut rng = Rng::new(1234);
+        rng.next_i32();
+        let mut fork = rng.clone();
+        for _ in 0..100 {
+            assert_eq!(rng.next_i32(), fork.next_i32());
+        }
+        assert_eq!(rng.pulls(), 101);
+        assert_eq!(fork.pulls(), 101);
+    }
+
+    #[test]
+    fn pulls_are_counted() {
+        let mut rng = Rng::new(1234);
+        assert_eq!(rng.pulls(), 0);
+        rng.next_double();
+        rng.next_bool();
+        assert_eq!(rng.pulls(), 2);
+        rng.sample_large_range();
+        assert_eq!(rng.pulls(), 4);
+
+        // Certain weighted bools don't pull from the rng.
+        rng.next_weighted_bool(1.0);
+        assert_eq!(rng.pulls(), 4);
+    }
+
+    #[test]
+    fn state_round_trips() {
+        let mut rng = Rng::new(34 + 327349652 / 2);
+        rng.next_i32();
+        rng.next_i32();
+
+        let state = rng.state();
+        let json = serde_json::to_string(&state).unwrap();
+        let mut restored = Rng::from_state(&serde_json::from_str(&json).unwrap()).unwrap();
+        assert_eq!(restored.pulls(), 2);
+        assert_eq!(restored.next_i32(), 539281092);
+        assert_eq!(restored.next_i32(), 729551037);
+
+        rng.next_i32();
+        rng.restore(&state).unwrap();
+        assert_eq!(rng.next_i32(), 539281092);
+    }
+
+    #[test]
+    fn invalid_state_returns_error() {
+        let mut state = Rng::new(1234).state();
+        state.next = SEED_ARRAY_LEN;
+        assert!(Rng::from_state(&state).is_err());
+
+        let mut state = Rng::new(1234).state();
+        state.seed_array.pop();
+        assert!(Rng::from_state(&state).is_err());
+    }
 }
@@ -0,0 +1,57 @@
+use roxmltree::Node;
+use std::convert::TryInto;
+
+use super::{Finder, SaveResult};
+
+#[derive(Clone, Debug)]
+pub struct FarmAnimal {
+    pub id: i64,
+    pub name: String,
+
+    /// Animal type from `Data/FarmAnimals` (i.e. `White Chicken`).
+    pub ty: String,
+    pub owner_id: i64,
+    pub home_building_type: String,
+    pub age: i32,
+    pub days_owned: i32,
+    pub happiness: i32,
+    pub friendship: i32,
+    pub fullness: i32,
+
+    /// Item ID of the produce ready to be collected, if any.
+    pub current_produce: Option<String>,
+    pub produce_quality: i32,
+    pub days_since_last_lay: i32,
+    pub was_pet: bool,
+}
+
+impl FarmAnimal {
+    pub(crate) fn from_node<'a, 'input: 'a>(
+        node: Node<'a, 'input>,
+    ) -> SaveResult<'a, 'input, Self> {
+        let current_produce: Option<String> = node.child("currentProduce").try_into().ok();
+
+        Ok(FarmAnimal {
+            id: node.child("myID").try_into()?,
+            name: node.child("name").try_into()?,
+            ty: node.child("type").try_into()?,
+            owner_id: node.child("ownerID").try_into()?,
+            home_building_type: node
+                .child("buildingTypeILiveIn")
+                .try_into()
+                .unwrap_or_default(),
+            age: node.child("age").try_into()?,
+            days_owned: node.child("daysOwned").try_into().unwrap_or_default(),
+            happiness: node.child("happiness").try_into()?,
+            friendship: node.child("friendshipTowardFarmer").try_into()?,
+            fullness: node.child("fullness").try_into().unwrap_or_default(),
+            current_produce: current_produce.filter(|produce| !produce.is_empty()),
+            produce_quality: node.child("produceQuality").try_into().unwrap_or_default(),
+            days_since_last_lay: node
+                .child("daysSinceLastLay")
+                .try_into()
+                .unwrap_or_default(),
+            was_pet: node.child("wasPet").try_into().unwrap_or_default(),
+        })
+    }
+}
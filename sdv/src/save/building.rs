@@ -0,0 +1,69 @@
+use roxmltree::Node;
+use std::convert::TryInto;
+
+use super::{location::Location, Finder, SaveResult};
+
+use crate::common::Point;
+
+#[derive(Debug)]
+pub struct Building {
+    pub id: String,
+
+    /// Building type from `Data/Buildings` (i.e. `Coop` or `Shed`).
+    pub ty: String,
+    pub tile: Point<i32>,
+    pub tiles_wide: i32,
+    pub tiles_high: i32,
+    pub days_of_construction_left: i32,
+    pub days_until_upgrade: i32,
+
+    /// The building's interior for buildings with their own instanced
+    /// location like sheds, barns, coops and cabins.
+    pub indoors: Option<Location>,
+
+    /// Name of the top level location used as the interior for buildings like
+    /// the farmhouse and greenhouse.
+    pub non_instanced_indoors_name: Option<String>,
+}
+
+impl Building {
+    pub(crate) fn from_node<'a, 'input: 'a>(
+        node: Node<'a, 'input>,
+    ) -> SaveResult<'a, 'input, Self> {
+        let indoors = node
+            .child("indoors")
+            .node()
+            .ok()
+            .map(Location::from_node)
+            .transpose()?;
+
+        // Null names are serialized as `<string xsi:nil="true" />`.
+        let non_instanced_indoors_name: Option<String> = node
+            .child("nonInstancedIndoorsName")
+            .child("string")
+            .try_into()
+            .ok();
+
+        Ok(Building {
+            // Buildings didn't have IDs before 1.6.
+            id: node.child("id").try_into().unwrap_or_default(),
+            ty: node.child("buildingType").try_into()?,
+            tile: Point {
+                x: node.child("tileX").try_into()?,
+                y: node.child("tileY").try_into()?,
+            },
+            tiles_wide: node.child("tilesWide").try_into()?,
+            tiles_high: node.child("tilesHigh").try_into()?,
+            days_of_construction_left: node
+                .child("daysOfConstructionLeft")
+                .try_into()
+                .unwrap_or_default(),
+            days_until_upgrade: node
+                .child("daysUntilUpgrade")
+                .try_into()
+                .unwrap_or_default(),
+            indoors,
+            non_instanced_indoors_name: non_instanced_indoors_name.filter(|name| !name.is_empty()),
+        })
+    }
+}
@@ -0,0 +1,122 @@
+use roxmltree::Node;
+use std::convert::TryInto;
+use strum::EnumString;
+
+use crate::common::Season;
+
+use super::{Finder, SaveError, SaveResult};
+
+const POINTS_PER_HEART: i32 = 250;
+
+// Non-spouse NPCs accept two gifts a week.
+const GIFTS_PER_WEEK: i32 = 2;
+
+#[derive(Clone, Debug, EnumString, Eq, Hash, PartialEq)]
+pub enum FriendshipStatus {
+    Friendly,
+    Dating,
+    Engaged,
+    Married,
+    Divorced,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorldDate {
+    pub year: i32,
+    pub season: Season,
+    pub day_of_month: i32,
+}
+
+impl WorldDate {
+    pub(crate) fn from_node<'a, 'input: 'a>(
+        node: Node<'a, 'input>,
+    ) -> SaveResult<'a, 'input, Self> {
+        Ok(WorldDate {
+            year: node.child("Year").try_into()?,
+            season: Season::from_node(node.child("Season").try_into()?)?,
+            day_of_month: node.child("DayOfMonth").try_into()?,
+        })
+    }
+
+    fn optional_from_node<'a, 'input: 'a>(
+        node: Node<'a, 'input>,
+        name: &str,
+    ) -> SaveResult<'a, 'input, Option<Self>> {
+        node.child(name)
+            .node()
+            .ok()
+            .map(Self::from_node)
+            .transpose()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Friendship {
+    pub points: i32,
+    pub gifts_this_week: i32,
+    pub gifts_today: i32,
+    pub last_gift_date: Option<WorldDate>,
+    pub talked_to_today: bool,
+    pub proposal_rejected: bool,
+    pub wedding_date: Option<WorldDate>,
+    pub status: FriendshipStatus,
+    pub proposer: i64,
+    pub roommate_marriage: bool,
+}
+
+impl Friendship {
+    pub(crate) fn from_node<'a, 'input: 'a>(
+        node: Node<'a, 'input>,
+    ) -> SaveResult<'a, 'input, Self> {
+        let status_raw: String = node.child("Status").try_into()?;
+        let status = status_raw
+            .parse::<FriendshipStatus>()
+            .map_err(|e| SaveError::Generic {
+                message: format!("Unknown friendship status {}: {e}", status_raw),
+                node,
+            })?;
+
+        Ok(Friendship {
+            points: node.child("Points").try_into()?,
+            gifts_this_week: node.child("GiftsThisWeek").try_into()?,
+            gifts_today: node.child("GiftsToday").try_into()?,
+            last_gift_date: WorldDate::optional_from_node(node, "LastGiftDate")?,
+            talked_to_today: node.child("TalkedToToday").try_into()?,
+            proposal_rejected: node.child("ProposalRejected").try_into()?,
+            wedding_date: WorldDate::optional_from_node(node, "WeddingDate")?,
+            status,
+            proposer: node.child("Proposer").try_into().unwrap_or_default(),
+            roommate_marriage: node
+                .child("RoommateMarriage")
+                .try_into()
+                .unwrap_or_default(),
+        })
+    }
+
+    pub fn hearts(&self) -> i32 {
+        self.points / POINTS_PER_HEART
+    }
+
+    pub fn is_married(&self) -> bool {
+        self.status == FriendshipStatus::Married
+    }
+
+    /// Number of gifts the NPC will still accept this week or `None` if there
+    /// is no weekly limit.
+    ///
+    /// Logic found in `NPC.tryToReceiveActiveObject()`.  Birthday gifts are
+    /// accepted even after the weekly limit is reached.
+    pub fn gifts_left_this_week(&self) -> Option<i32> {
+        if self.is_married() {
+            None
+        } else {
+            Some((GIFTS_PER_WEEK - self.gifts_this_week).max(0))
+        }
+    }
+
+    /// Whether the NPC will accept a gift today.
+    pub fn can_gift_today(&self, is_birthday: bool) -> bool {
+        self.gifts_today == 0
+            && (is_birthday || self.gifts_left_this_week().is_none_or(|left| left > 0))
+    }
+}
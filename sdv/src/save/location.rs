@@ -2,15 +2,27 @@ use indexmap::IndexMap;
 use roxmltree::Node;
 use std::convert::TryInto;
 
-use super::{array_of_bool, map_from_node, object::Object, Finder, SaveResult};
+use super::{
+    animal::FarmAnimal, array_of_bool, building::Building, map_from_node, object::Object,
+    terrain_feature::TerrainFeature, Finder, SaveResult,
+};
 
 use crate::common::Point;
 
 #[derive(Debug)]
 pub struct Location {
     pub name: String,
+
+    /// Set for building interiors which share their `name` with other
+    /// buildings of the same type.
+    pub unique_name: Option<String>,
     pub bundles: Option<IndexMap<i32, Vec<bool>>>,
     pub objects: IndexMap<Point<i32>, Object>,
+    pub terrain_features: IndexMap<Point<i32>, TerrainFeature>,
+    pub buildings: Vec<Building>,
+
+    /// Animals in the location keyed by their ID.
+    pub animals: IndexMap<i64, FarmAnimal>,
 }
 
 impl Location {
@@ -26,10 +38,59 @@ impl Location {
             Object::from_node(node.child("Object").try_into()?)
         });
 
+        let terrain_features = match node.child("terrainFeatures").node().ok() {
+            Some(node) => map_from_node(node, "Vector2", |node| {
+                TerrainFeature::from_node(node.child("TerrainFeature").try_into()?)
+            })?,
+            None => IndexMap::new(),
+        };
+
+        let buildings = match node.child("buildings").node().ok() {
+            Some(node) => node
+                .children()
+                .filter(|n| n.has_tag_name("Building"))
+                .map(Building::from_node)
+                .collect::<SaveResult<Vec<_>>>()?,
+            None => Vec::new(),
+        };
+
+        let animals = match node.child("animals").node().ok() {
+            Some(node) => map_from_node(node, "long", |node| {
+                FarmAnimal::from_node(node.child("FarmAnimal").try_into()?)
+            })?,
+            None => IndexMap::new(),
+        };
+
         Ok(Location {
             name,
+            unique_name: node.child("uniqueName").try_into().ok(),
             bundles,
             objects: objects?,
+            terrain_features,
+            buildings,
+            animals,
         })
     }
+
+    /// Whether an object or terrain feature is on `tile`.
+    ///
+    /// Approximates `GameLocation.IsTileOccupiedBy()`.  Large terrain features
+    /// like bushes are not parsed and some terrain features, like flooring,
+    /// don't block every kind of placement.
+    pub fn is_tile_occupied(&self, tile: &Point<i32>) -> bool {
+        self.objects.contains_key(tile) || self.terrain_features.contains_key(tile)
+    }
+
+    /// Building interiors in this location, including ones nested in other
+    /// interiors.
+    pub fn indoor_locations(&self) -> Vec<(&Building, &Location)> {
+        let mut locations = Vec::new();
+        for building in &self.buildings {
+            if let Some(indoors) = &building.indoors {
+                locations.push((building, indoors));
+                locations.extend(indoors.indoor_locations());
+            }
+        }
+        locations
+    }
 }
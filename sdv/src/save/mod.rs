@@ -12,16 +12,32 @@ use std::{
 };
 use strum::{Display, EnumString};
 
-use crate::common::Season;
+use crate::{
+    common::Season,
+    query::{PlayerTarget, QueryContext},
+};
 
+mod animal;
+mod building;
+mod friendship;
 mod location;
 mod object;
+mod quest;
 mod stats;
+mod terrain_feature;
 mod weather;
 
+pub use animal::FarmAnimal;
+pub use building::Building;
+pub use friendship::{Friendship, FriendshipStatus, WorldDate};
 pub use location::Location;
 pub use object::Object;
+pub use quest::{
+    Quest, SpecialOrder, SpecialOrderDuration, SpecialOrderObjective, SpecialOrderReward,
+    SpecialOrderStatus,
+};
 pub use stats::Stats;
+pub use terrain_feature::{Crop, FruitTree, HoeDirt, TerrainFeature, Tree};
 pub use weather::{LocationWeather, Weather};
 
 #[derive(Debug, Clone)]
@@ -208,6 +224,12 @@ where
     vals
 }
 
+/// The `xsi:type` of a node, i.e. the subclass of a serialized object.
+pub(crate) fn xsi_type(node: Node) -> Option<String> {
+    node.attribute(("http://www.w3.org/2001/XMLSchema-instance", "type"))
+        .map(str::to_string)
+}
+
 pub(crate) fn array_of_i32<'a, 'input>(node: Node<'a, 'input>) -> SaveResult<'a, 'input, Vec<i32>> {
     array_of(node, "ArrayOfInt", "int")
 }
@@ -311,6 +333,7 @@ pub enum Skill {
 #[derive(Debug)]
 pub struct Player {
     pub name: String,
+    pub unique_multiplayer_id: i64,
     pub stats: Stats,
     pub deepest_mine_level: i32,
     pub fish_caught: IndexMap<String, FishCaught>,
@@ -321,6 +344,28 @@ pub struct Player {
     pub professions: IndexSet<Profession>,
     pub experience: IndexMap<Skill, i32>,
     pub items: Vec<Object>,
+
+    /// Friendship with each NPC keyed by NPC name.
+    pub friendship: IndexMap<String, Friendship>,
+
+    pub mail_received: IndexSet<String>,
+
+    /// IDs of seen events.  Most are numeric but festivals and some newer
+    /// events use string IDs.
+    pub events_seen: IndexSet<String>,
+    pub quest_log: Vec<Quest>,
+}
+
+fn get_string_set<'a, 'input: 'a>(
+    node: Node<'a, 'input>,
+    node_name: &str,
+    value_node_name: &str,
+) -> SaveResult<'a, 'input, IndexSet<String>> {
+    if node.child(node_name).node().is_err() {
+        return Ok(IndexSet::new());
+    }
+    let values: Vec<String> = array_of(node, node_name, value_node_name)?;
+    Ok(values.into_iter().collect())
 }
 
 fn get_int_map<'a, 'input: 'a>(
@@ -336,6 +381,7 @@ fn get_int_map<'a, 'input: 'a>(
 impl Player {
     fn from_node<'a, 'input: 'a>(node: Node<'a, 'input>) -> SaveResult<'a, 'input, Self> {
         let name = node.child("name").try_into()?;
+        let unique_multiplayer_id = node.child("UniqueMultiplayerID").try_into()?;
         let stats = node.child("stats").try_into()?;
         let deepest_mine_level = node.child("deepestMineLevel").try_into()?;
 
@@ -391,8 +437,28 @@ impl Player {
             None => Vec::new(),
         };
 
+        let friendship = match node.child("friendshipData").node().ok() {
+            Some(node) => map_from_node(node, "string", |node| {
+                Friendship::from_node(node.child("Friendship").try_into()?)
+            })?,
+            None => IndexMap::new(),
+        };
+
+        let mail_received = get_string_set(node, "mailReceived", "string")?;
+        let events_seen = get_string_set(node, "eventsSeen", "int")?;
+
+        let quest_log: SaveResult<Vec<Quest>> = match node.child("questLog").node().ok() {
+            Some(node) => node
+                .children()
+                .filter(|n| n.has_tag_name("Quest"))
+                .map(Quest::from_node)
+                .collect(),
+            None => Ok(Vec::new()),
+        };
+
         Ok(Player {
             name,
+            unique_multiplayer_id,
             stats,
             deepest_mine_level,
             fish_caught,
@@ -403,6 +469,10 @@ impl Player {
             professions: professions?,
             experience,
             items,
+            friendship,
+            mail_received,
+            events_seen,
+            quest_log: quest_log?,
         })
     }
 
@@ -435,6 +505,8 @@ pub struct FishCaught {
 
 #[derive(Debug)]
 pub struct SaveGame {
+    /// Version of the game that wrote the save, i.e. `1.6.2`.
+    pub game_version: Option<String>,
     pub player: Player,
     pub locations: IndexMap<String, Location>,
     pub current_season: Season,
@@ -443,6 +515,16 @@ pub struct SaveGame {
     pub weather: IndexMap<String, LocationWeather>,
     pub unique_id_for_this_game: i32,
     pub daily_luck: f32,
+    pub lost_books_found: Option<i32>,
+
+    /// Item ID and stack of the Saloon's dish of the day.
+    pub dish_of_the_day: Option<(String, i32)>,
+
+    /// Special orders the players have accepted.
+    pub special_orders: Vec<SpecialOrder>,
+
+    /// Special orders currently posted on the boards.
+    pub available_special_orders: Vec<SpecialOrder>,
 }
 
 impl SaveGame {
@@ -478,16 +560,51 @@ impl SaveGame {
         let day_of_month = save.child("dayOfMonth").try_into()?;
         let year = save.child("year").try_into()?;
 
-        let weather = map_from_node(
-            save.child("locationWeather").try_into()?,
-            "string",
-            |node| LocationWeather::from_node(node.child("LocationWeather").try_into()?),
-        )?;
+        // Saves from before 1.6 key the weather with the `LocationContext`
+        // enum.
+        let weather_node: Node = save.child("locationWeather").try_into()?;
+        let weather_key = if weather_node
+            .child("item")
+            .child("key")
+            .child("LocationContext")
+            .node()
+            .is_ok()
+        {
+            "LocationContext"
+        } else {
+            "string"
+        };
+        let weather = map_from_node(weather_node, weather_key, |node| {
+            LocationWeather::from_node(node.child("LocationWeather").try_into()?)
+        })?;
 
         let unique_id_for_this_game = save.child("uniqueIDForThisGame").try_into()?;
         let daily_luck = save.child("dailyLuck").try_into()?;
+        let lost_books_found = save.child("lostBooksFound").try_into().ok();
+
+        // Saves from before 1.6 only store the dish's parent sheet index.
+        let dish_of_the_day = match save.child("dishOfTheDay").node().ok() {
+            Some(dish) => {
+                let id: String = dish
+                    .child("itemId")
+                    .try_into()
+                    .or_else(|_| dish.child("parentSheetIndex").try_into())?;
+                Some((id, dish.child("stack").try_into()?))
+            }
+            None => None,
+        };
+
+        let special_orders = match save.child("specialOrders").node().ok() {
+            Some(node) => SpecialOrder::array_from_node(node)?,
+            None => Vec::new(),
+        };
+        let available_special_orders = match save.child("availableSpecialOrders").node().ok() {
+            Some(node) => SpecialOrder::array_from_node(node)?,
+            None => Vec::new(),
+        };
 
         Ok(SaveGame {
+            game_version: save.child("gameVersion").try_into().ok(),
             player,
             locations,
             current_season,
@@ -496,6 +613,10 @@ impl SaveGame {
             weather,
             unique_id_for_this_game,
             daily_luck,
+            lost_books_found,
+            dish_of_the_day,
+            special_orders,
+            available_special_orders,
         })
     }
 
@@ -505,6 +626,25 @@ impl SaveGame {
             .ok_or(anyhow!("Can't find location {}", name))
     }
 
+    /// Every location in the save including building interiors.
+    ///
+    /// Interiors are named after their location and building tile so that
+    /// buildings of the same type can be told apart.
+    pub fn all_locations(&self) -> Vec<(String, &Location)> {
+        let mut locations = Vec::new();
+        for (name, location) in &self.locations {
+            locations.push((name.clone(), location));
+            for (building, indoors) in location.indoor_locations() {
+                let name = format!(
+                    "{} ({}, {})",
+                    indoors.name, building.tile.x, building.tile.y
+                );
+                locations.push((name, indoors));
+            }
+        }
+        locations
+    }
+
     pub fn get_bundles(&self) -> Result<&IndexMap<i32, Vec<bool>>> {
         let location = self.get_location("CommunityCenter")?;
         location
@@ -513,6 +653,19 @@ impl SaveGame {
             .ok_or(anyhow!("Can't find bundles in CommunityCenter"))
     }
 
+    /// Whether the save was written by a version of the game older than
+    /// `major`.`minor`.  Saves without a version are assumed to be old.
+    pub fn is_older_than(&self, major: u32, minor: u32) -> bool {
+        let Some(version) = &self.game_version else {
+            return true;
+        };
+        let mut parts = version
+            .split('.')
+            .map(|part| part.parse::<u32>().unwrap_or(0));
+        let version = (parts.next().unwrap_or(0), parts.next().unwrap_or(0));
+        version < (major, minor)
+    }
+
     pub fn get_weather(&self, location: &str) -> &LocationWeather {
         self.weather
             .get(location)
@@ -520,17 +673,325 @@ impl SaveGame {
     }
 }
 
+impl SaveGame {
+    /// Returns the player a query targets.
+    ///
+    /// Only the main player is parsed from saves so every target other than a
+    /// different player's ID resolves to them.
+    fn query_player(&self, target: &PlayerTarget) -> Option<&Player> {
+        match target {
+            PlayerTarget::Id(id) if *id != self.player.unique_multiplayer_id => None,
+            _ => Some(&self.player),
+        }
+    }
+}
+
+impl QueryContext for SaveGame {
+    fn game_id(&self) -> u32 {
+        self.unique_id_for_this_game as u32
+    }
+
+    fn days_played(&self) -> u32 {
+        (self.year as u32 - 1) * 28 * 4
+            + self.current_season.clone() as u32 * 28
+            + self.day_of_month as u32
+    }
+
+    fn daily_luck(&self) -> f64 {
+        self.daily_luck as f64
+    }
+
+    fn year(&self) -> u32 {
+        self.year as u32
+    }
+
+    fn season(&self) -> Season {
+        self.current_season.clone()
+    }
+
+    fn day_of_month(&self) -> u32 {
+        self.day_of_month as u32
+    }
+
+    fn player_stat(&self, player: &PlayerTarget, stat: &str) -> Option<u32> {
+        self.query_player(player)
+            .map(|player| player.stats.get(stat).unwrap_or_default())
+    }
+
+    fn player_has_mail(&self, player: &PlayerTarget, mail: &str) -> Option<bool> {
+        self.query_player(player)
+            .map(|player| player.mail_received.contains(mail))
+    }
+
+    fn player_has_seen_event(&self, player: &PlayerTarget, event: &str) -> Option<bool> {
+        self.query_player(player)
+            .map(|player| player.events_seen.contains(event))
+    }
+
+    // Special orders are shared by the whole team.
+    fn player_special_order_rule_active(&self, _player: &PlayerTarget, rule: &str) -> Option<bool> {
+        Some(
+            self.special_orders
+                .iter()
+                .any(|order| order.rule_active(rule)),
+        )
+    }
+}
+
+/// Load a save from `test-data`.
 #[cfg(test)]
-mod tests {
-    use std::{fs::File, io::BufReader};
+pub(crate) fn load_test_save(name: &str) -> SaveGame {
+    let f = std::fs::File::open(format!("test-data/{name}")).unwrap();
+    let mut r = std::io::BufReader::new(f);
+    SaveGame::from_reader(&mut r).unwrap()
+}
 
+#[cfg(test)]
+mod tests {
     use super::*;
+    use crate::common::Point;
 
     #[test]
     fn load_save() {
-        let f = File::open("test-data/ChimkinHill_370550379").unwrap();
-        let mut r = BufReader::new(f);
-        let save = SaveGame::from_reader(&mut r).unwrap();
+        let save = load_test_save("ChimkinHill_370550379");
         println!("{:?}", save);
     }
+
+    #[test]
+    fn save_answers_queries() {
+        let save = load_test_save("ChimkinHill_370550379");
+
+        assert_eq!(
+            Some(save.days_played()),
+            save.player.stats.get("daysPlayed")
+        );
+        assert_eq!(save.player.stats.get("neverSetStat"), None);
+        assert_eq!(
+            save.player_stat(&PlayerTarget::Current, "TrashCansChecked"),
+            Some(7)
+        );
+        assert_eq!(
+            save.player_stat(&PlayerTarget::Current, "neverSetStat"),
+            Some(0)
+        );
+        assert_eq!(
+            save.player_stat(&PlayerTarget::Id(1), "trashCansChecked"),
+            None
+        );
+    }
+
+    #[test]
+    fn save_has_friendship() {
+        let save = load_test_save("ChimkinHill_370550379");
+
+        assert_eq!(save.player.friendship.len(), 29);
+        let linus = &save.player.friendship["Linus"];
+        assert_eq!(linus.points, 326);
+        assert_eq!(linus.hearts(), 1);
+        assert_eq!(linus.status, FriendshipStatus::Friendly);
+        assert_eq!(
+            linus.last_gift_date,
+            Some(WorldDate {
+                year: 1,
+                season: Season::Spring,
+                day_of_month: 18,
+            })
+        );
+        assert_eq!(save.player.friendship["Lewis"].last_gift_date, None);
+    }
+
+    #[test]
+    fn save_has_buildings_and_animals() {
+        let save = load_test_save("ChimkinHill_370550379");
+
+        let farm = save.get_location("Farm").unwrap();
+        let types: Vec<&str> = farm.buildings.iter().map(|b| b.ty.as_str()).collect();
+        assert_eq!(
+            types,
+            vec![
+                "Farmhouse",
+                "Greenhouse",
+                "Shipping Bin",
+                "Pet Bowl",
+                "Coop"
+            ]
+        );
+
+        let farmhouse = &farm.buildings[0];
+        assert_eq!(farmhouse.tile, Point { x: 76, y: 16 });
+        assert_eq!(
+            farmhouse.non_instanced_indoors_name.as_deref(),
+            Some("FarmHouse")
+        );
+        assert!(farmhouse.indoors.is_none());
+
+        let coop = farm.buildings[4].indoors.as_ref().unwrap();
+        assert_eq!(coop.name, "Coop");
+        assert_eq!(coop.animals.len(), 2);
+        let ollie = &coop.animals[&1254870074145671172];
+        assert_eq!(ollie.name, "Ollie");
+        assert_eq!(ollie.ty, "Brown Chicken");
+        assert_eq!(ollie.friendship, 316);
+        assert_eq!(ollie.happiness, 219);
+        assert_eq!(ollie.current_produce, None);
+
+        assert!(save
+            .all_locations()
+            .iter()
+            .any(|(name, location)| name == "Coop (54, 9)" && location.unique_name.is_some()));
+    }
+
+    #[test]
+    fn save_has_terrain_features() {
+        let save = load_test_save("ChimkinHill_370550379");
+
+        let farm = save.get_location("Farm").unwrap();
+        let farm_crops: Vec<_> = farm
+            .terrain_features
+            .values()
+            .filter_map(TerrainFeature::crop)
+            .collect();
+        assert_eq!(farm_crops.len(), 292);
+        let beans = farm.terrain_features[&Point { x: 91, y: 23 }]
+            .crop()
+            .unwrap();
+        assert_eq!(beans.seed_id.as_deref(), Some("473"));
+        assert_eq!(beans.phase_days, vec![1, 1, 1, 3, 4, 99999]);
+        assert!(beans.full_grown);
+        assert!(!beans.is_ready_for_harvest());
+        assert_eq!(beans.days_until_harvest(), Some(1));
+        assert!(farm.is_tile_occupied(&Point { x: 91, y: 23 }));
+    }
+
+    // Mirrors `Crop.newDay()` for a watered crop.
+    fn grow_crop(crop: &mut Crop) {
+        let last_phase = crop.phase_days.len() as i32 - 1;
+        if crop.full_grown {
+            crop.day_of_current_phase -= 1;
+        } else {
+            let phase = crop.current_phase.min(last_phase) as usize;
+            crop.day_of_current_phase = (crop.day_of_current_phase + 1).min(crop.phase_days[phase]);
+        }
+        if crop.current_phase < last_phase
+            && crop.day_of_current_phase >= crop.phase_days[crop.current_phase as usize]
+        {
+            crop.current_phase += 1;
+            crop.day_of_current_phase = 0;
+        }
+        while crop.current_phase < last_phase && crop.phase_days[crop.current_phase as usize] <= 0 {
+            crop.current_phase += 1;
+        }
+    }
+
+    #[test]
+    fn crops_are_ready_after_days_until_harvest() {
+        let save = load_test_save("ChimkinHill_370550379");
+
+        let crops: Vec<Crop> = save
+            .all_locations()
+            .iter()
+            .flat_map(|(_, location)| location.terrain_features.values())
+            .filter_map(TerrainFeature::crop)
+            .filter(|crop| !crop.dead)
+            .cloned()
+            .collect();
+        assert_eq!(crops.len(), 300);
+
+        for crop in crops {
+            let mut grown = crop.clone();
+            let mut days = 0;
+            while !grown.is_ready_for_harvest() {
+                grow_crop(&mut grown);
+                days += 1;
+                assert!(days < 100, "{:?} never became ready", crop);
+            }
+            assert_eq!(crop.days_until_harvest(), Some(days), "{:?}", crop);
+        }
+    }
+
+    #[test]
+    fn save_has_mail_events_and_quests() {
+        let save = load_test_save("ChimkinHill_370550379");
+
+        assert_eq!(
+            save.player_has_mail(&PlayerTarget::Current, "Linus_Introduction"),
+            Some(true)
+        );
+        assert_eq!(
+            save.player_has_mail(&PlayerTarget::Current, "JojaMember"),
+            Some(false)
+        );
+        assert_eq!(
+            save.player_has_seen_event(&PlayerTarget::Current, "festival_spring13"),
+            Some(true)
+        );
+        assert_eq!(
+            save.player_has_seen_event(&PlayerTarget::Id(1), "60367"),
+            None
+        );
+
+        let quests: Vec<&str> = save
+            .player
+            .quest_log
+            .iter()
+            .map(|quest| quest.id.as_str())
+            .collect();
+        assert_eq!(quests, vec!["8", "133", "101", "19", "24"]);
+        let jodi = save
+            .player
+            .quest_log
+            .iter()
+            .find(|quest| quest.id == "101")
+            .unwrap();
+        assert_eq!(jodi.kind.as_deref(), Some("ItemDeliveryQuest"));
+        assert_eq!(jodi.title, "Jodi's Request");
+        assert_eq!(jodi.money_reward, 350);
+        assert_eq!(jodi.reward_description, None);
+
+        assert!(save.special_orders.is_empty());
+        assert_eq!(
+            save.player_special_order_rule_active(&PlayerTarget::Current, "DROP_QI_BEANS"),
+            Some(false)
+        );
+
+        let wizard = save
+            .available_special_orders
+            .iter()
+            .find(|order| order.key == "Wizard2")
+            .unwrap();
+        assert_eq!(wizard.state, SpecialOrderStatus::InProgress);
+        assert_eq!(wizard.duration, SpecialOrderDuration::Week);
+        assert_eq!(
+            "OneDay".parse::<SpecialOrderDuration>(),
+            Ok(SpecialOrderDuration::OneDay)
+        );
+        assert_eq!(
+            "Fortnight".parse::<SpecialOrderDuration>(),
+            Ok(SpecialOrderDuration::Other("Fortnight".to_string()))
+        );
+        assert_eq!(wizard.objectives.len(), 2);
+        assert_eq!(
+            wizard.rewards,
+            vec![
+                SpecialOrderReward::Money {
+                    amount: 5000,
+                    multiplier: 1.0
+                },
+                SpecialOrderReward::Mail(vec!["WizardReward".to_string()]),
+            ]
+        );
+        let keys: Vec<&str> = save
+            .available_special_orders
+            .iter()
+            .map(|order| order.key.as_str())
+            .collect();
+        assert_eq!(
+            keys,
+            vec!["Wizard2", "Pam", "QiChallenge10", "QiChallenge3"]
+        );
+        assert_eq!(
+            save.available_special_orders[2].special_rules,
+            vec!["SC_HARD".to_string()]
+        );
+    }
 }
@@ -72,8 +72,16 @@ impl Object {
             None => None,
         };
 
+        // Saves from before 1.6 only store the parent sheet index, or just the
+        // name for tools.
+        let id = node
+            .child("itemId")
+            .try_into()
+            .or_else(|_| node.child("parentSheetIndex").try_into())
+            .or_else(|_| node.child("name").try_into())?;
+
         Ok(Object {
-            id: node.child("itemId").try_into()?,
+            id,
             is_lost: node.child("isLostItem").try_into()?,
             category: node.child("category").try_into()?,
             has_been_in_inventory: node.child("hasBeenInInventory").try_into()?,
@@ -85,7 +93,10 @@ impl Object {
             special_variable: node.child("SpecialVariable").try_into()?,
             //TODO: display_name: node.child("DisplayName").try_into()?,
             display_name: String::new(), //node.child("DisplayName").try_into()?,
-            stack: node.child("stack").try_into()?,
+            stack: node
+                .child("stack")
+                .try_into()
+                .or_else(|_| node.child("Stack").try_into())?,
             tile_location: node.child("tileLocation").try_into().ok(),
             owner: node.child("owner").try_into().ok(),
             ty: node.child("type").try_into().unwrap_or(ObjectType::Unknown),
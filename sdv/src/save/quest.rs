@@ -0,0 +1,220 @@
+use roxmltree::Node;
+use std::convert::TryInto;
+use strum::EnumString;
+
+use super::{array_of, xsi_type, Finder, SaveError, SaveResult};
+
+fn parse_enum<'a, 'input: 'a, T>(node: Node<'a, 'input>, name: &str) -> SaveResult<'a, 'input, T>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    let raw: String = node.child(name).try_into()?;
+    raw.parse().map_err(|e| SaveError::Generic {
+        message: format!("Unknown {} {}: {}", name, raw, e),
+        node,
+    })
+}
+
+#[derive(Clone, Debug)]
+pub struct Quest {
+    /// The quest's class (i.e. `ItemDeliveryQuest`) or `None` for plain
+    /// quests.
+    pub kind: Option<String>,
+    pub id: String,
+    pub quest_type: i32,
+    pub title: String,
+    pub description: String,
+    pub current_objective: String,
+    pub reward_description: Option<String>,
+    pub money_reward: i32,
+
+    /// Days left to complete a daily quest.  `0` for quests without a
+    /// deadline.
+    pub days_left: i32,
+    pub accepted: bool,
+    pub completed: bool,
+    pub daily_quest: bool,
+}
+
+impl Quest {
+    pub(crate) fn from_node<'a, 'input: 'a>(
+        node: Node<'a, 'input>,
+    ) -> SaveResult<'a, 'input, Self> {
+        // The game uses "-1" when a quest has no reward description.
+        let reward_description: Option<String> = node
+            .child("rewardDescription")
+            .try_into()
+            .ok()
+            .filter(|desc: &String| !desc.is_empty() && desc != "-1");
+
+        Ok(Quest {
+            kind: xsi_type(node),
+            id: node.child("id").try_into()?,
+            quest_type: node.child("questType").try_into()?,
+            title: node.child("_questTitle").try_into()?,
+            description: node.child("_questDescription").try_into()?,
+            current_objective: node.child("_currentObjective").try_into()?,
+            reward_description,
+            money_reward: node.child("moneyReward").try_into()?,
+            days_left: node.child("daysLeft").try_into()?,
+            accepted: node.child("accepted").try_into()?,
+            completed: node.child("completed").try_into()?,
+            daily_quest: node.child("dailyQuest").try_into()?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, EnumString, Eq, Hash, PartialEq)]
+pub enum SpecialOrderStatus {
+    InProgress,
+    Failed,
+    Complete,
+}
+
+/// Mirrors the game's `QuestDuration` enum.
+#[derive(Clone, Debug, EnumString, Eq, Hash, PartialEq)]
+pub enum SpecialOrderDuration {
+    Week,
+    Month,
+    TwoWeeks,
+    TwoDays,
+    ThreeDays,
+    OneDay,
+
+    /// A duration this crate doesn't know about.
+    #[strum(default)]
+    Other(String),
+}
+
+#[derive(Clone, Debug)]
+pub struct SpecialOrderObjective {
+    /// The objective's class (i.e. `DeliverObjective`).
+    pub kind: Option<String>,
+    pub description: String,
+    pub current_count: i32,
+    pub max_count: i32,
+}
+
+impl SpecialOrderObjective {
+    fn from_node<'a, 'input: 'a>(node: Node<'a, 'input>) -> SaveResult<'a, 'input, Self> {
+        Ok(SpecialOrderObjective {
+            kind: xsi_type(node),
+            description: node.child("description").try_into()?,
+            current_count: node.child("currentCount").try_into()?,
+            max_count: node.child("maxCount").try_into()?,
+        })
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.current_count >= self.max_count
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SpecialOrderReward {
+    Money {
+        amount: i32,
+        multiplier: f32,
+    },
+    Mail(Vec<String>),
+
+    /// A reward type that isn't parsed yet, identified by its class.
+    Other(Option<String>),
+}
+
+impl SpecialOrderReward {
+    fn from_node<'a, 'input: 'a>(node: Node<'a, 'input>) -> SaveResult<'a, 'input, Self> {
+        let kind = xsi_type(node);
+        Ok(match kind.as_deref() {
+            Some("MoneyReward") => SpecialOrderReward::Money {
+                amount: node.child("amount").child("int").try_into()?,
+                multiplier: node
+                    .child("multiplier")
+                    .child("float")
+                    .try_into()
+                    .unwrap_or(1.0),
+            },
+            Some("MailReward") => {
+                SpecialOrderReward::Mail(array_of(node, "grantedMails", "string")?)
+            }
+            _ => SpecialOrderReward::Other(kind),
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SpecialOrder {
+    pub key: String,
+    pub requester: String,
+    pub order_type: String,
+
+    /// Rules (i.e. `DROP_QI_BEANS`) active while the order is in progress.
+    pub special_rules: Vec<String>,
+
+    /// Day, in days played, the order is due.
+    pub due_date: i32,
+    pub duration: SpecialOrderDuration,
+    pub state: SpecialOrderStatus,
+    pub objectives: Vec<SpecialOrderObjective>,
+    pub rewards: Vec<SpecialOrderReward>,
+}
+
+impl SpecialOrder {
+    pub(crate) fn from_node<'a, 'input: 'a>(
+        node: Node<'a, 'input>,
+    ) -> SaveResult<'a, 'input, Self> {
+        let special_rule: String = node.child("specialRule").try_into()?;
+        let special_rules = special_rule
+            .split(',')
+            .map(str::trim)
+            .filter(|rule| !rule.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        let objectives: SaveResult<Vec<_>> = node
+            .children()
+            .filter(|n| n.has_tag_name("objectives"))
+            .map(SpecialOrderObjective::from_node)
+            .collect();
+
+        let rewards: SaveResult<Vec<_>> = node
+            .children()
+            .filter(|n| n.has_tag_name("rewards"))
+            .map(SpecialOrderReward::from_node)
+            .collect();
+
+        Ok(SpecialOrder {
+            key: node.child("questKey").try_into()?,
+            requester: node.child("requester").try_into()?,
+            order_type: node.child("orderType").try_into()?,
+            special_rules,
+            due_date: node.child("dueDate").try_into()?,
+            duration: parse_enum(node, "duration")?,
+            state: parse_enum(node, "questState")?,
+            objectives: objectives?,
+            rewards: rewards?,
+        })
+    }
+
+    pub(crate) fn array_from_node<'a, 'input: 'a>(
+        node: Node<'a, 'input>,
+    ) -> SaveResult<'a, 'input, Vec<Self>> {
+        node.children()
+            .filter(|n| n.has_tag_name("SpecialOrder"))
+            .map(Self::from_node)
+            .collect()
+    }
+
+    /// Whether the order is in progress and applies `rule`.
+    ///
+    /// Logic found in `FarmerTeam.SpecialOrderRuleActive()`.
+    pub fn rule_active(&self, rule: &str) -> bool {
+        self.state == SpecialOrderStatus::InProgress && self.special_rules.iter().any(|r| r == rule)
+    }
+
+    /// Days left before the order is due.
+    pub fn days_left(&self, days_played: u32) -> i32 {
+        self.due_date - days_played as i32
+    }
+}
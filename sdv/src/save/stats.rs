@@ -1,7 +1,8 @@
+use indexmap::IndexMap;
 use roxmltree::Node;
 use std::convert::{TryFrom, TryInto};
 
-use super::{Finder, NodeFinder, SaveError, SaveResult};
+use super::{map_from_node, Finder, NodeFinder, SaveError, SaveResult};
 
 #[derive(Debug)]
 pub struct Stats {
@@ -58,10 +59,29 @@ pub struct Stats {
     pub good_friends: u32,
     pub total_money_gifted: u32,
     pub individual_money_earned: u32,
+
+    /// Generic stat values keyed by stat name.
+    ///
+    /// Newer saves store most stats here instead of in the fields above.
+    pub values: IndexMap<String, u32>,
 }
 
 impl Stats {
     fn from_node<'a, 'input: 'a>(node: Node<'a, 'input>) -> SaveResult<'a, 'input, Stats> {
+        // Saves from before 1.6 keep stats without their own field in
+        // `stat_dictionary`.
+        let values_node = node
+            .child("Values")
+            .node()
+            .or_else(|_| node.child("stat_dictionary").node())
+            .ok();
+        let values = match values_node {
+            Some(node) => {
+                map_from_node(node, "string", |node| node.child("unsignedInt").try_into())?
+            }
+            None => IndexMap::new(),
+        };
+
         Ok(Stats {
             seeds_sown: node.child("seedsSown").try_into().unwrap_or_default(),
             items_shipped: node.child("itemsShipped").try_into().unwrap_or_default(),
@@ -155,6 +175,80 @@ impl Stats {
                 .child("individualMoneyEarned")
                 .try_into()
                 .unwrap_or_default(),
+            values,
+        })
+    }
+
+    /// Returns the value of a stat or `None` if the save doesn't have it.
+    ///
+    /// Like the game, stat names are case insensitive.  Newer saves only
+    /// store stats that have been set in `values` so callers should treat
+    /// `None` as 0 for them.  Older saves store the stats with their own
+    /// fields, which are used when `values` doesn't have the stat.
+    pub fn get(&self, name: &str) -> Option<u32> {
+        self.values
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| *value)
+            .or_else(|| self.field(name))
+    }
+
+    fn field(&self, name: &str) -> Option<u32> {
+        Some(match name.to_ascii_lowercase().as_str() {
+            "seedssown" => self.seeds_sown,
+            "itemsshipped" => self.items_shipped,
+            "itemscooked" => self.items_cooked,
+            "itemscrafted" => self.items_crafted,
+            "chickeneggslayed" => self.chicken_eggs_layed,
+            "duckeggslayed" => self.duck_eggs_layed,
+            "cowmilkproduced" => self.cow_milk_produced,
+            "goatmilkproduced" => self.goat_milk_produced,
+            "rabbitwoolproduced" => self.rabbit_wool_produced,
+            "sheepwoolproduced" => self.sheep_wool_produced,
+            "cheesemade" => self.cheese_made,
+            "goatcheesemade" => self.goat_cheese_made,
+            "trufflesfound" => self.truffles_found,
+            "stonegathered" => self.stone_gathered,
+            "rockscrushed" => self.rocks_crushed,
+            "dirthoed" => self.dirt_hoed,
+            "giftsgiven" => self.gifts_given,
+            "timesunconscious" => self.times_unconscious,
+            "averagebedtime" => self.average_bedtime,
+            "timesfished" => self.times_fished,
+            "fishcaught" => self.fish_caught,
+            "boulderscracked" => self.boulders_cracked,
+            "stumpschopped" => self.stumps_chopped,
+            "stepstaken" => self.steps_taken,
+            "monsterskilled" => self.monsters_killed,
+            "diamondsfound" => self.diamonds_found,
+            "prismaticshardsfound" => self.prismatic_shards_found,
+            "otherpreciousgemsfound" => self.other_precious_gems_found,
+            "cavecarrotsfound" => self.cave_carrots_found,
+            "copperfound" => self.copper_found,
+            "ironfound" => self.iron_found,
+            "coalfound" => self.coal_found,
+            "coinsfound" => self.coins_found,
+            "goldfound" => self.gold_found,
+            "iridiumfound" => self.iridium_found,
+            "barssmelted" => self.bars_smelted,
+            "beveragesmade" => self.beverages_made,
+            "preservesmade" => self.preserves_made,
+            "piecesoftrashrecycled" => self.pieces_of_trash_recycled,
+            "mysticstonescrushed" => self.mystic_stones_crushed,
+            "daysplayed" => self.days_played,
+            "weedseliminated" => self.weeds_eliminated,
+            "stickschopped" => self.sticks_chopped,
+            "notesfound" => self.notes_found,
+            "questscompleted" => self.quests_completed,
+            "starlevelcropsshipped" => self.star_level_crops_shipped,
+            "cropsshipped" => self.crops_shipped,
+            "itemsforaged" => self.items_foraged,
+            "slimeskilled" => self.slimes_killed,
+            "geodescracked" => self.geodes_cracked,
+            "goodfriends" => self.good_friends,
+            "totalmoneygifted" => self.total_money_gifted,
+            "individualmoneyearned" => self.individual_money_earned,
+            _ => return None,
         })
     }
 }
@@ -165,3 +259,32 @@ impl<'a, 'input: 'a> TryFrom<NodeFinder<'a, 'input>> for Stats {
         Self::from_node(finder.node()?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_fall_back_to_fields() {
+        // Older saves store stats in their own elements.
+        let doc = roxmltree::Document::parse(
+            "<stats><stepsTaken>2</stepsTaken><daysPlayed>4</daysPlayed></stats>",
+        )
+        .unwrap();
+        let stats = Stats::from_node(doc.root_element()).unwrap();
+        assert_eq!(stats.get("stepsTaken"), Some(2));
+        assert_eq!(stats.get("DaysPlayed"), Some(4));
+        assert_eq!(stats.get("geodesCracked"), Some(0));
+        assert_eq!(stats.get("Book_Trash"), None);
+
+        // Newer saves store them in `Values`.
+        let doc = roxmltree::Document::parse(
+            "<stats><Values><item><key><string>stepsTaken</string></key>\
+             <value><unsignedInt>19596</unsignedInt></value></item></Values></stats>",
+        )
+        .unwrap();
+        let stats = Stats::from_node(doc.root_element()).unwrap();
+        assert_eq!(stats.get("stepsTaken"), Some(19596));
+        assert_eq!(stats.get("Book_Trash"), None);
+    }
+}
@@ -0,0 +1,232 @@
+use roxmltree::Node;
+use std::convert::TryInto;
+
+use super::{array_of, xsi_type, Finder, SaveResult};
+
+// Fruit trees produce better fruit for every year past maturity.
+const DAYS_PER_YEAR: i32 = 28 * 4;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Crop {
+    /// Item ID of the seed the crop was planted from.
+    pub seed_id: Option<String>,
+    pub harvest_id: Option<String>,
+
+    /// Days spent in each phase.  The last phase, which lasts until harvest,
+    /// is `99999`.
+    pub phase_days: Vec<i32>,
+    pub current_phase: i32,
+
+    /// Days spent in the current phase or, for fully grown regrowing crops,
+    /// days left until they can be harvested again.
+    pub day_of_current_phase: i32,
+    pub full_grown: bool,
+    pub dead: bool,
+    pub forage_crop: bool,
+}
+
+impl Crop {
+    fn from_node<'a, 'input: 'a>(node: Node<'a, 'input>) -> SaveResult<'a, 'input, Self> {
+        let id = |name: &str| -> Option<String> {
+            let id: String = node.child(name).try_into().ok()?;
+            (!id.is_empty() && id != "-1").then_some(id)
+        };
+
+        Ok(Crop {
+            seed_id: id("seedIndex"),
+            harvest_id: id("indexOfHarvest"),
+            phase_days: array_of(node, "phaseDays", "int")?,
+            current_phase: node.child("currentPhase").try_into()?,
+            day_of_current_phase: node.child("dayOfCurrentPhase").try_into()?,
+            full_grown: node.child("fullGrown").try_into().unwrap_or_default(),
+            dead: node.child("dead").try_into().unwrap_or_default(),
+            forage_crop: node.child("forageCrop").try_into().unwrap_or_default(),
+        })
+    }
+
+    fn last_phase(&self) -> i32 {
+        self.phase_days.len() as i32 - 1
+    }
+
+    /// Whether the crop has reached its last phase.  Regrowing crops stay in
+    /// it after their first harvest.
+    pub fn is_mature(&self) -> bool {
+        self.current_phase >= self.last_phase()
+    }
+
+    /// Logic found in `Crop.isReadyForHarvest()`.
+    pub fn is_ready_for_harvest(&self) -> bool {
+        !self.dead && self.is_mature() && (!self.full_grown || self.day_of_current_phase <= 0)
+    }
+
+    /// Days until the crop can be harvested or `None` if it's dead.
+    ///
+    /// Assumes the crop is watered every day.  Logic found in `Crop.newDay()`.
+    pub fn days_until_harvest(&self) -> Option<i32> {
+        if self.dead {
+            return None;
+        }
+
+        if self.is_mature() {
+            return Some(if self.full_grown {
+                self.day_of_current_phase.max(0)
+            } else {
+                0
+            });
+        }
+
+        let phase = self.current_phase.max(0) as usize;
+        let last_phase = self.last_phase() as usize;
+        let remaining = self.phase_days[phase] - self.day_of_current_phase
+            + self.phase_days[phase + 1..last_phase].iter().sum::<i32>();
+        Some(remaining.max(0))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HoeDirt {
+    pub watered: bool,
+    pub fertilizer: Option<String>,
+    pub crop: Option<Crop>,
+}
+
+impl HoeDirt {
+    fn from_node<'a, 'input: 'a>(node: Node<'a, 'input>) -> SaveResult<'a, 'input, Self> {
+        let state: i32 = node.child("state").try_into().unwrap_or_default();
+        let fertilizer: Option<String> = node.child("fertilizer").try_into().ok();
+
+        Ok(HoeDirt {
+            watered: state == 1,
+            fertilizer: fertilizer.filter(|fertilizer| !fertilizer.is_empty()),
+            crop: node
+                .child("crop")
+                .node()
+                .ok()
+                .map(Crop::from_node)
+                .transpose()?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tree {
+    /// Tree type from `Data/WildTrees`.
+    pub tree_type: String,
+    pub growth_stage: i32,
+    pub stump: bool,
+    pub tapped: bool,
+    pub has_seed: bool,
+    pub has_moss: bool,
+    pub fertilized: bool,
+}
+
+impl Tree {
+    fn from_node<'a, 'input: 'a>(node: Node<'a, 'input>) -> SaveResult<'a, 'input, Self> {
+        Ok(Tree {
+            tree_type: node.child("treeType").try_into()?,
+            growth_stage: node.child("growthStage").try_into()?,
+            stump: node.child("stump").try_into().unwrap_or_default(),
+            tapped: node.child("tapped").try_into().unwrap_or_default(),
+            has_seed: node.child("hasSeed").try_into().unwrap_or_default(),
+            has_moss: node.child("hasMoss").try_into().unwrap_or_default(),
+            fertilized: node.child("fertilized").try_into().unwrap_or_default(),
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FruitTree {
+    /// Fruit tree ID from `Data/FruitTrees`.
+    pub tree_id: String,
+    pub growth_stage: i32,
+
+    /// Days until the tree matures.  Keeps counting down once the tree is
+    /// mature so it is negative for trees older than that.
+    pub days_until_mature: i32,
+    pub fruit_count: usize,
+    pub stump: bool,
+}
+
+impl FruitTree {
+    fn from_node<'a, 'input: 'a>(node: Node<'a, 'input>) -> SaveResult<'a, 'input, Self> {
+        let fruit_count = node
+            .child("fruit")
+            .node()
+            .map_or(0, |node| node.children().filter(|n| n.is_element()).count());
+
+        Ok(FruitTree {
+            tree_id: node.child("treeId").try_into()?,
+            growth_stage: node.child("growthStage").try_into()?,
+            days_until_mature: node.child("daysUntilMature").try_into()?,
+            fruit_count,
+            stump: node.child("stump").try_into().unwrap_or_default(),
+        })
+    }
+
+    pub fn is_mature(&self) -> bool {
+        self.days_until_mature <= 0
+    }
+
+    /// Days since the tree matured or `None` if it hasn't yet.
+    pub fn days_mature(&self) -> Option<i32> {
+        self.is_mature().then_some(-self.days_until_mature)
+    }
+
+    /// Quality of the fruit the tree produces.
+    ///
+    /// Logic found in `FruitTree.GetQuality()`.
+    pub fn fruit_quality(&self) -> i32 {
+        match self.days_mature() {
+            Some(days) if days >= 3 * DAYS_PER_YEAR => 4,
+            Some(days) if days >= 2 * DAYS_PER_YEAR => 2,
+            Some(days) if days >= DAYS_PER_YEAR => 1,
+            _ => 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TerrainFeature {
+    HoeDirt(HoeDirt),
+    Tree(Tree),
+    FruitTree(FruitTree),
+    Grass {
+        grass_type: i32,
+        number_of_weeds: i32,
+    },
+    Flooring {
+        which_floor: String,
+    },
+
+    /// A terrain feature type that isn't parsed yet, identified by its class.
+    Other(Option<String>),
+}
+
+impl TerrainFeature {
+    pub(crate) fn from_node<'a, 'input: 'a>(
+        node: Node<'a, 'input>,
+    ) -> SaveResult<'a, 'input, Self> {
+        let kind = xsi_type(node);
+        Ok(match kind.as_deref() {
+            Some("HoeDirt") => TerrainFeature::HoeDirt(HoeDirt::from_node(node)?),
+            Some("Tree") => TerrainFeature::Tree(Tree::from_node(node)?),
+            Some("FruitTree") => TerrainFeature::FruitTree(FruitTree::from_node(node)?),
+            Some("Grass") => TerrainFeature::Grass {
+                grass_type: node.child("grassType").try_into()?,
+                number_of_weeds: node.child("numberOfWeeds").try_into()?,
+            },
+            Some("Flooring") => TerrainFeature::Flooring {
+                which_floor: node.child("whichFloor").try_into()?,
+            },
+            _ => TerrainFeature::Other(kind),
+        })
+    }
+
+    /// The crop planted in the feature, if any.
+    pub fn crop(&self) -> Option<&Crop> {
+        match self {
+            TerrainFeature::HoeDirt(dirt) => dirt.crop.as_ref(),
+            _ => None,
+        }
+    }
+}
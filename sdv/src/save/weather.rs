@@ -1,4 +1,5 @@
 use num_derive::FromPrimitive;
+use num_traits::FromPrimitive as _;
 use roxmltree::Node;
 use std::convert::TryInto;
 use strum::EnumString;
@@ -30,17 +31,23 @@ impl LocationWeather {
     pub(crate) fn from_node<'a, 'input: 'a>(
         node: Node<'a, 'input>,
     ) -> SaveResult<'a, 'input, Self> {
-        let weather_for_tomorrow_raw: String = node
-            .child("weatherForTomorrow")
-            .child("string")
-            .try_into()?;
-        let weather_for_tomorrow =
-            weather_for_tomorrow_raw
-                .parse::<Weather>()
-                .map_err(|e| SaveError::Generic {
-                    message: format!("Unknown weather {}: {e}", weather_for_tomorrow_raw),
+        // Saves from before 1.6 store the weather as an integer.
+        let weather_for_tomorrow = match node.child("weatherForTomorrow").child("int").try_into() {
+            Ok(raw) => Weather::from_i32(raw).ok_or_else(|| SaveError::Generic {
+                message: format!("Unknown weather {}", raw),
+                node,
+            })?,
+            Err(_) => {
+                let raw: String = node
+                    .child("weatherForTomorrow")
+                    .child("string")
+                    .try_into()?;
+                raw.parse::<Weather>().map_err(|e| SaveError::Generic {
+                    message: format!("Unknown weather {}: {e}", raw),
                     node,
-                })?;
+                })?
+            }
+        };
         let is_raining = node.child("isRaining").child("boolean").try_into()?;
         let is_snowing = node.child("isSnowing").child("boolean").try_into()?;
         let is_lightning = node.child("isLightning").child("boolean").try_into()?;
@@ -0,0 +1,192 @@
+// Generates `vectors.json` used by the `rng` tests.
+//
+// This is a straight port of .NET's `System.Random` (seeded compatibility
+// implementation) and the game's random helpers and is intentionally kept
+// independent of the Rust implementation.
+//
+// Usage: node generate.js > vectors.json
+
+"use strict";
+
+const INT_MAX = 2147483647;
+const INT_MIN = -2147483648;
+const SEED = 161803398;
+
+class DotNetRandom {
+  constructor(seed) {
+    const subtraction = seed === INT_MIN ? INT_MAX : Math.abs(seed);
+    let mj = SEED - subtraction;
+    this.seedArray = new Array(56).fill(0);
+    this.seedArray[55] = mj;
+    let mk = 1;
+    for (let i = 1; i < 55; i++) {
+      const ii = (21 * i) % 55;
+      this.seedArray[ii] = mk;
+      mk = mj - mk;
+      if (mk < 0) mk += INT_MAX;
+      mj = this.seedArray[ii];
+    }
+    for (let k = 1; k < 5; k++) {
+      for (let i = 1; i < 56; i++) {
+        // Matches C#'s unchecked int32 arithmetic.
+        this.seedArray[i] = (this.seedArray[i] - this.seedArray[1 + ((i + 30) % 55)]) | 0;
+        if (this.seedArray[i] < 0) this.seedArray[i] += INT_MAX;
+      }
+    }
+    this.inext = 0;
+    this.inextp = 21;
+  }
+
+  internalSample() {
+    let locINext = this.inext;
+    let locINextp = this.inextp;
+    if (++locINext >= 56) locINext = 1;
+    if (++locINextp >= 56) locINextp = 1;
+    let retVal = (this.seedArray[locINext] - this.seedArray[locINextp]) | 0;
+    if (retVal === INT_MAX) retVal--;
+    if (retVal < 0) retVal += INT_MAX;
+    this.seedArray[locINext] = retVal;
+    this.inext = locINext;
+    this.inextp = locINextp;
+    return retVal;
+  }
+
+  sample() {
+    return this.internalSample() * (1.0 / INT_MAX);
+  }
+
+  getSampleForLargeRange() {
+    let result = this.internalSample();
+    const negative = this.internalSample() % 2 === 0;
+    if (negative) result = -result;
+    let d = result;
+    d += INT_MAX - 1;
+    d /= 2 * INT_MAX - 1;
+    return d;
+  }
+
+  next() {
+    return this.internalSample();
+  }
+
+  nextMax(maxValue) {
+    return Math.trunc(this.sample() * maxValue);
+  }
+
+  nextRange(minValue, maxValue) {
+    const range = maxValue - minValue;
+    if (range <= INT_MAX) {
+      return Math.trunc(this.sample() * range) + minValue;
+    }
+    return Math.trunc(this.getSampleForLargeRange() * range) + minValue;
+  }
+
+  nextDouble() {
+    return this.sample();
+  }
+
+  nextBytes(count) {
+    const bytes = [];
+    for (let i = 0; i < count; i++) {
+      bytes.push(this.internalSample() & 0xff);
+    }
+    return bytes;
+  }
+
+  nextUInt64() {
+    return (
+      BigInt(this.nextMax(1 << 22)) |
+      (BigInt(this.nextMax(1 << 22)) << 22n) |
+      (BigInt(this.nextMax(1 << 20)) << 44n)
+    );
+  }
+
+  nextInt64Range(minValue, maxValue) {
+    const exclusiveRange = BigInt.asUintN(64, maxValue - minValue);
+    if (exclusiveRange > 1n) {
+      let bits = 0n;
+      while (1n << bits < exclusiveRange) bits++;
+      for (;;) {
+        const result = this.nextUInt64() >> (64n - bits);
+        if (result < exclusiveRange) return result + minValue;
+      }
+    }
+    return minValue;
+  }
+
+  // Utility.Shuffle()
+  shuffle(list) {
+    let n = list.length;
+    while (n > 1) {
+      const k = this.nextMax(n--);
+      const value = list[k];
+      list[k] = list[n];
+      list[n] = value;
+    }
+    return list;
+  }
+
+  chooseWeighted(weights) {
+    const total = weights.reduce((acc, w) => acc + Math.max(w, 0), 0);
+    if (total <= 0) return null;
+    let roll = this.nextMax(total);
+    for (let i = 0; i < weights.length; i++) {
+      roll -= Math.max(weights[i], 0);
+      if (roll < 0) return i;
+    }
+    return null;
+  }
+}
+
+function doubleBits(value) {
+  const view = new DataView(new ArrayBuffer(8));
+  view.setFloat64(0, value);
+  return view.getBigUint64(0).toString(16).padStart(16, "0");
+}
+
+function generate(seed) {
+  const rng = new DotNetRandom(seed);
+  const ops = [];
+  const push = (op, args, result) => ops.push({ op, args, result });
+
+  for (let i = 0; i < 4; i++) push("next", [], rng.next());
+  for (let i = 0; i < 4; i++) push("next_double", [], doubleBits(rng.nextDouble()));
+  for (const max of [1, 2, 7, 100, INT_MAX]) push("next_max", [max], rng.nextMax(max));
+  for (const [min, max] of [
+    [0, 0],
+    [1, 10],
+    [-50, 50],
+    [INT_MIN, INT_MAX],
+    [-10, INT_MAX],
+  ]) {
+    push("next_range", [min, max], rng.nextRange(min, max));
+  }
+  push("next_bytes", [10], rng.nextBytes(10));
+  for (const [min, max] of [
+    [0n, 1n],
+    [0n, 1000n],
+    [-5000000000n, 5000000000n],
+    [0n, 9223372036854775807n],
+    [-9223372036854775808n, 9223372036854775807n],
+  ]) {
+    push("next_i64_range", [min, max], rng.nextInt64Range(min, max));
+  }
+  push("shuffle", [10], rng.shuffle([...Array(10).keys()]));
+  push("choose_weighted", [[1, 0, 5, 10, 2]], rng.chooseWeighted([1, 0, 5, 10, 2]));
+  push("choose_weighted", [[0, 0]], rng.chooseWeighted([0, 0]));
+  push("next", [], rng.next());
+
+  return { seed, ops };
+}
+
+const seeds = [0, 1, 34 + 327349652 / 2, -123456789, INT_MAX, INT_MIN];
+const vectors = seeds.map(generate);
+
+// BigInts are written as bare JSON integers so that they are parsed exactly.
+const json = JSON.stringify(
+  vectors,
+  (_key, value) => (typeof value === "bigint" ? `__bigint__${value}` : value),
+  2
+).replace(/"__bigint__(-?\d+)"/g, "$1");
+
+console.log(json);